@@ -0,0 +1,193 @@
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+
+use actix_web::dev::RequestHead;
+use actix_web::http::header::HeaderName;
+
+/// IP network in the CIDR notation, ex. `10.0.0.0/8` or `::1/128`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct IpNet {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNet {
+    /// Parses all the `networks`.
+    ///
+    /// ## Panics
+    ///
+    /// If any of `networks` is not a valid CIDR notation.
+    pub fn parse_all<I>(networks: I) -> Vec<IpNet>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        networks
+            .into_iter()
+            .map(|network| {
+                let network = network.as_ref();
+                network.parse().unwrap_or_else(|_| {
+                    panic!("Invalid IP network: {:?}", network)
+                })
+            })
+            .collect()
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, canonical(ip)) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNet {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '/');
+        let addr = parts
+            .next()
+            .and_then(|addr| addr.parse::<IpAddr>().ok())
+            .map(canonical)
+            .ok_or(())?;
+        let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match parts.next() {
+            Some(prefix) => prefix.parse::<u8>().map_err(|_| ())?,
+            None => max_prefix,
+        };
+        if prefix > max_prefix {
+            return Err(());
+        }
+
+        Ok(IpNet {
+            addr,
+            prefix,
+        })
+    }
+}
+
+/// Converts the IPv4-mapped IPv6 addresses into the IPv4 ones.
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => IpAddr::V6(v6),
+        },
+        v4 => v4,
+    }
+}
+
+/// Determines the client IP address.
+///
+/// If connection peer is one of the `trusted_proxies`, `X-Forwarded-For`
+/// header entries are walked from right to left, skipping the trusted
+/// proxies; the first untrusted one is the client address.
+/// `None` is returned if the client address can't be reliably determined.
+pub fn client_ip(
+    head: &RequestHead,
+    trusted_proxies: &[IpNet],
+) -> Option<IpAddr> {
+    let is_trusted =
+        |ip: IpAddr| trusted_proxies.iter().any(|net| net.contains(ip));
+    let mut ip = head.peer_addr?.ip();
+    if !is_trusted(ip) {
+        return Some(ip);
+    }
+
+    let forwarded = HeaderName::from_static("x-forwarded-for");
+    let mut entries = Vec::new();
+    for value in head.headers().get_all(&forwarded) {
+        let value = value.to_str().ok()?;
+        entries.extend(value.split(',').map(str::trim));
+    }
+
+    for entry in entries.into_iter().rev() {
+        ip = entry
+            .parse::<IpAddr>()
+            .or_else(|_| entry.parse::<SocketAddr>().map(|addr| addr.ip()))
+            .ok()?;
+        if !is_trusted(ip) {
+            return Some(ip);
+        }
+    }
+
+    Some(ip)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::{client_ip, IpNet};
+
+    #[test]
+    fn test_ipv4() {
+        let net = "10.0.0.0/8".parse::<IpNet>().unwrap();
+
+        assert!(net.contains("10.1.2.3".parse().unwrap()));
+        assert!(net.contains("::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!net.contains("11.1.2.3".parse().unwrap()));
+        assert!(!net.contains("::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_ipv6() {
+        let net = "::1/128".parse::<IpNet>().unwrap();
+        assert!(net.contains("::1".parse().unwrap()));
+        assert!(!net.contains("::2".parse().unwrap()));
+
+        let net = "fd00::/8".parse::<IpNet>().unwrap();
+        assert!(net.contains("fd12:3456::1".parse().unwrap()));
+        assert!(!net.contains("fe80::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_any() {
+        let net = "0.0.0.0/0".parse::<IpNet>().unwrap();
+
+        assert!(net.contains("192.168.0.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!("10.0.0.0/33".parse::<IpNet>().is_err());
+        assert!("10.0.0/8".parse::<IpNet>().is_err());
+        assert!("::1/129".parse::<IpNet>().is_err());
+        assert!("localhost".parse::<IpNet>().is_err());
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let trusted = IpNet::parse_all(&["10.0.0.1"]);
+        let mut req = TestRequest::with_header(
+            "X-Forwarded-For",
+            "10.9.9.9, 192.168.1.1, 10.0.0.1",
+        )
+        .to_srv_request();
+
+        req.head_mut().peer_addr = Some("10.0.0.1:80".parse().unwrap());
+        assert_eq!(
+            client_ip(req.head(), &trusted),
+            Some("192.168.1.1".parse().unwrap())
+        );
+
+        // Untrusted peer can't spoof the address
+        req.head_mut().peer_addr = Some("172.16.0.1:80".parse().unwrap());
+        assert_eq!(
+            client_ip(req.head(), &trusted),
+            Some("172.16.0.1".parse().unwrap())
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Time source abstraction for the time-sensitive checks.
+//!
+//! Everything in this crate which depends on the current time
+//! reads it from the [`Clock`] instance, which defaults to the
+//! [`SystemClock`]; tests can use the [`ManualClock`] instead
+//! in order to get the deterministic results without sleeping.
+//!
+//! [`Clock`]: ./trait.Clock.html
+//! [`SystemClock`]: ./struct.SystemClock.html
+//! [`ManualClock`]: ./struct.ManualClock.html
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// Source of the current time.
+pub trait Clock: Debug + Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> SystemTime;
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> SystemTime {
+        (**self).now()
+    }
+}
+
+/// [`Clock`](./trait.Clock.html) backed by the system time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// [`Clock`](./trait.Clock.html) which time is changed manually.
+///
+/// Clones are sharing the same time, so the clone can be passed
+/// to the configuration and the original one is used to control it.
+///
+/// ## Example
+///
+/// ```rust
+/// # use std::time::{Duration, UNIX_EPOCH};
+/// # use actix_web_httpauth::clock::{Clock, ManualClock};
+/// let clock = ManualClock::new(UNIX_EPOCH);
+/// let shared = clock.clone();
+///
+/// clock.advance(Duration::from_secs(30));
+/// assert_eq!(shared.now(), UNIX_EPOCH + Duration::from_secs(30));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualClock(Arc<Mutex<SystemTime>>);
+
+impl ManualClock {
+    /// Creates new clock, stopped at the `now` time.
+    pub fn new(now: SystemTime) -> ManualClock {
+        ManualClock(Arc::new(Mutex::new(now)))
+    }
+
+    /// Sets the current time.
+    pub fn set(&self, now: SystemTime) {
+        *self.time() = now;
+    }
+
+    /// Moves the current time forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.time() += duration;
+    }
+
+    fn time(&self) -> std::sync::MutexGuard<'_, SystemTime> {
+        match self.0.lock() {
+            Ok(time) => time,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.time()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{Clock, ManualClock, SystemClock};
+
+    #[test]
+    fn test_system_clock() {
+        assert!(SystemClock.now() > UNIX_EPOCH);
+    }
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new(UNIX_EPOCH);
+        assert_eq!(clock.now(), UNIX_EPOCH);
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(clock.now(), UNIX_EPOCH + Duration::from_secs(5));
+
+        clock.set(UNIX_EPOCH + Duration::from_secs(60));
+        assert_eq!(clock.now(), UNIX_EPOCH + Duration::from_secs(60));
+    }
+}
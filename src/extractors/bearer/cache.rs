@@ -0,0 +1,518 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use actix_web::dev::ServiceRequest;
+use actix_web::http::StatusCode;
+use actix_web::Error;
+use futures::future::{self, Future, IntoFuture};
+use sha2::{Digest, Sha256};
+
+use super::{BearerAuth, Config};
+use crate::clock::{Clock, SystemClock};
+use crate::extractors::AuthenticationError;
+use crate::headers::www_authenticate::bearer;
+
+/// Tokens are cached by their SHA-256 digest,
+/// so the cache does not hold the plaintext tokens.
+pub(super) type Key = [u8; 32];
+
+/// Returns the cache key of the `token`.
+pub(super) fn key(token: &str) -> Key {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+#[derive(Debug)]
+struct Entry<V> {
+    value: V,
+    expires: SystemTime,
+    tick: u64,
+}
+
+/// Bounded cache of the validation results,
+/// evicting the least recently used tokens.
+#[derive(Debug)]
+pub(super) struct Lru<V> {
+    entries: HashMap<Key, Entry<V>>,
+    recency: BTreeMap<u64, Key>,
+    tick: u64,
+    /// Bumped on invalidation, so the results of the validations
+    /// started before it are not cached.
+    generation: u64,
+}
+
+impl<V> Default for Lru<V> {
+    fn default() -> Self {
+        Lru {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            generation: 0,
+        }
+    }
+}
+
+impl<V: Clone> Lru<V> {
+    pub(super) fn get(&mut self, key: &Key, now: SystemTime) -> Option<V> {
+        let tick = self.tick + 1;
+        let entry = self.entries.get_mut(key)?;
+        if entry.expires <= now {
+            let tick = entry.tick;
+            self.entries.remove(key);
+            self.recency.remove(&tick);
+            return None;
+        }
+
+        self.recency.remove(&entry.tick);
+        self.recency.insert(tick, *key);
+        entry.tick = tick;
+        self.tick = tick;
+
+        Some(entry.value.clone())
+    }
+}
+
+impl<V> Lru<V> {
+    fn remove(&mut self, key: &Key) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.recency.remove(&entry.tick);
+        }
+        self.generation += 1;
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.generation += 1;
+    }
+
+    pub(super) fn insert(
+        &mut self,
+        key: Key,
+        value: V,
+        expires: SystemTime,
+        capacity: usize,
+    ) {
+        if let Some(previous) = self.entries.remove(&key) {
+            self.recency.remove(&previous.tick);
+        }
+        while self.entries.len() >= capacity {
+            let oldest = match self.recency.keys().next() {
+                Some(oldest) => *oldest,
+                None => break,
+            };
+            if let Some(key) = self.recency.remove(&oldest) {
+                self.entries.remove(&key);
+            }
+        }
+
+        self.tick += 1;
+        self.recency.insert(self.tick, key);
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                expires,
+                tick: self.tick,
+            },
+        );
+    }
+}
+
+/// Wrapper for the [`HttpAuthentication::bearer`] validation callback,
+/// which caches its results by the token, ex. to skip the database
+/// lookups for the repeated requests with the same opaque token.
+///
+/// Accepted tokens are cached for the `ttl` duration; rejected ones are
+/// not cached unless the [`negative_ttl`](#method.negative_ttl) is set.
+/// Cache holds at most `capacity` tokens, evicting the least recently
+/// used ones.
+///
+/// Wrapped callback is not called for the cached tokens, so its side
+/// effects, ex. the values inserted into the request extensions, are
+/// not repeated. Cached rejections are responded with the
+/// `invalid_token` error, formed from the [`Config`] instance fetched
+/// from the app data.
+///
+/// Clones are sharing the same cache, so a clone kept by the app,
+/// ex. the revocation endpoint, can [`invalidate`](#method.invalidate)
+/// the tokens immediately.
+///
+/// ## Example
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use actix_web::App;
+/// # use futures::future;
+/// # use actix_web_httpauth::extractors::bearer::CachedValidator;
+/// # use actix_web_httpauth::middleware::HttpAuthentication;
+/// let validator = CachedValidator::new(
+///     |req, _credentials| {
+///         // Look the token up in the database here
+///         future::ok(req)
+///     },
+///     Duration::from_secs(60),
+///     10_000,
+/// )
+/// .negative_ttl(Duration::from_secs(5));
+///
+/// let middleware = HttpAuthentication::bearer(move |req, credentials| {
+///     validator.validate(req, credentials)
+/// });
+/// let app = App::new().wrap(middleware);
+/// ```
+///
+/// [`HttpAuthentication::bearer`]: ../../middleware/struct.HttpAuthentication.html#method.bearer
+/// [`Config`]: ./struct.Config.html
+pub struct CachedValidator<F> {
+    inner: Arc<F>,
+    cache: Arc<Mutex<Lru<bool>>>,
+    ttl: Duration,
+    negative_ttl: Option<Duration>,
+    capacity: usize,
+    clock: Arc<dyn Clock>,
+}
+
+impl<F, O> CachedValidator<F>
+where
+    F: Fn(ServiceRequest, BearerAuth) -> O,
+    O: IntoFuture<Item = ServiceRequest, Error = Error>,
+    O::Future: 'static,
+{
+    /// Creates new validator, which caches the results of the `inner`
+    /// callback for `ttl`, holding at most `capacity` tokens.
+    ///
+    /// ## Panics
+    ///
+    /// If `capacity` is zero.
+    pub fn new(inner: F, ttl: Duration, capacity: usize) -> Self {
+        assert!(capacity > 0, "Cache capacity must be positive");
+
+        CachedValidator {
+            inner: Arc::new(inner),
+            cache: Arc::new(Mutex::new(Lru::default())),
+            ttl,
+            negative_ttl: None,
+            capacity,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Cache the rejected tokens for `ttl` too.
+    ///
+    /// Only the `HTTP 401` bearer errors are cached, so the transient
+    /// failures are retried. Duration should be short, so the quickly
+    /// fixed tokens are not locked out.
+    pub fn negative_ttl(mut self, ttl: Duration) -> Self {
+        self.negative_ttl = Some(ttl);
+        self
+    }
+
+    /// Set the time source, ex. the [`ManualClock`] for tests.
+    ///
+    /// [`ManualClock`]: ../../clock/struct.ManualClock.html
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Validates the `credentials`, suitable to be called
+    /// from the [`HttpAuthentication::bearer`] validation callback.
+    ///
+    /// [`HttpAuthentication::bearer`]: ../../middleware/struct.HttpAuthentication.html#method.bearer
+    pub fn validate(
+        &self,
+        req: ServiceRequest,
+        credentials: BearerAuth,
+    ) -> Box<dyn Future<Item = ServiceRequest, Error = Error>> {
+        let key = key(credentials.token());
+        let mut cache = self.cache();
+        let generation = cache.generation;
+        let cached = cache.get(&key, self.clock.now());
+        drop(cache);
+        match cached {
+            Some(true) => return Box::new(future::ok(req)),
+            Some(false) => {
+                let config = req
+                    .app_data::<Config>()
+                    .map(|config| config.get_ref().clone())
+                    .unwrap_or_default();
+                return Box::new(future::err(
+                    AuthenticationError::from(config)
+                        .with_error(bearer::Error::InvalidToken)
+                        .into(),
+                ));
+            }
+            None => (),
+        }
+
+        let cache = self.cache.clone();
+        let clock = self.clock.clone();
+        let (ttl, negative_ttl) = (self.ttl, self.negative_ttl);
+        let capacity = self.capacity;
+        let f = (self.inner)(req, credentials).into_future();
+
+        Box::new(f.then(move |result| {
+            let ttl = match result {
+                Ok(_) => Some(ttl),
+                Err(ref e) if is_rejection(e) => negative_ttl,
+                Err(_) => None,
+            };
+            if let Some(ttl) = ttl {
+                let mut cache = lock(&cache);
+                // Token may have been invalidated while it was validated
+                if cache.generation == generation {
+                    cache.insert(
+                        key,
+                        result.is_ok(),
+                        clock.now() + ttl,
+                        capacity,
+                    );
+                }
+            }
+
+            result
+        }))
+    }
+
+    fn cache(&self) -> MutexGuard<'_, Lru<bool>> {
+        lock(&self.cache)
+    }
+}
+
+impl<F> CachedValidator<F> {
+    /// Removes the cached result for the `token`, ex. on logout,
+    /// so it is validated by the wrapped callback again.
+    pub fn invalidate(&self, token: &str) {
+        lock(&self.cache).remove(&key(token));
+    }
+
+    /// Removes all cached results.
+    pub fn invalidate_all(&self) {
+        lock(&self.cache).clear();
+    }
+}
+
+pub(super) fn lock<V>(cache: &Mutex<Lru<V>>) -> MutexGuard<'_, Lru<V>> {
+    match cache.lock() {
+        Ok(cache) => cache,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Returns `true` if the error is the bearer token rejection.
+fn is_rejection(error: &Error) -> bool {
+    error
+        .as_error::<AuthenticationError<bearer::Bearer>>()
+        .is_some_and(|error| error.status_code() == StatusCode::UNAUTHORIZED)
+}
+
+impl<F> Clone for CachedValidator<F> {
+    fn clone(&self) -> Self {
+        CachedValidator {
+            inner: self.inner.clone(),
+            cache: self.cache.clone(),
+            ttl: self.ttl,
+            negative_ttl: self.negative_ttl,
+            capacity: self.capacity,
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+impl<F> fmt::Debug for CachedValidator<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CachedValidator")
+            .field("ttl", &self.ttl)
+            .field("negative_ttl", &self.negative_ttl)
+            .field("capacity", &self.capacity)
+            .field("clock", &self.clock)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use actix_web::dev::ServiceRequest;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::Error;
+    use futures::future::{self, Either, FutureResult};
+    use futures::sync::oneshot;
+    use futures::Future;
+
+    use super::CachedValidator;
+    use crate::extractors::bearer::{BearerAuth, Config};
+    use crate::extractors::AuthenticationError;
+    use crate::headers::www_authenticate::bearer;
+    use crate::middleware::tests::{validation_status, Fixture};
+
+    type Validator = CachedValidator<
+        Box<
+            dyn Fn(
+                ServiceRequest,
+                BearerAuth,
+            ) -> FutureResult<ServiceRequest, Error>,
+        >,
+    >;
+
+    fn validator(fixture: &Fixture, capacity: usize) -> Validator {
+        let counter = fixture.clone();
+        let inner: Box<dyn Fn(ServiceRequest, BearerAuth) -> _> =
+            Box::new(move |req, credentials: BearerAuth| {
+                counter.call();
+                match credentials.token() {
+                    "revoked" => future::err(
+                        AuthenticationError::from(Config::default())
+                            .with_error(bearer::Error::InvalidToken)
+                            .into(),
+                    ),
+                    "unavailable" => future::err(
+                        actix_web::error::ErrorServiceUnavailable("down"),
+                    ),
+                    _ => future::ok(req),
+                }
+            });
+        CachedValidator::new(inner, Duration::from_secs(60), capacity)
+            .negative_ttl(Duration::from_secs(5))
+            .clock(Arc::new(fixture.clock.clone()))
+    }
+
+    fn status(validator: &Validator, token: &str) -> StatusCode {
+        let req = TestRequest::default().to_srv_request();
+        validation_status(
+            validator.validate(req, BearerAuth::new(token.to_string())),
+        )
+    }
+
+    #[test]
+    fn test_hit_and_expiry() {
+        let fixture = Fixture::new();
+        let validator = validator(&fixture, 16);
+
+        assert_eq!(status(&validator, "token"), StatusCode::OK);
+        assert_eq!(status(&validator, "token"), StatusCode::OK);
+        assert_eq!(fixture.calls(), 1);
+
+        fixture.clock.advance(Duration::from_secs(60));
+        assert_eq!(status(&validator, "token"), StatusCode::OK);
+        assert_eq!(fixture.calls(), 2);
+    }
+
+    #[test]
+    fn test_negative_cache() {
+        let fixture = Fixture::new();
+        let validator = validator(&fixture, 16);
+
+        assert_eq!(status(&validator, "revoked"), StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&validator, "revoked"), StatusCode::UNAUTHORIZED);
+        assert_eq!(fixture.calls(), 1);
+
+        fixture.clock.advance(Duration::from_secs(5));
+        assert_eq!(status(&validator, "revoked"), StatusCode::UNAUTHORIZED);
+        assert_eq!(fixture.calls(), 2);
+
+        // Transient failures are not cached
+        for _ in 0..2 {
+            assert_eq!(
+                status(&validator, "unavailable"),
+                StatusCode::SERVICE_UNAVAILABLE
+            );
+        }
+        assert_eq!(fixture.calls(), 4);
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let revoked = Arc::new(Mutex::new(HashSet::new()));
+        let revocations = revoked.clone();
+        let inner: Box<dyn Fn(ServiceRequest, BearerAuth) -> _> =
+            Box::new(move |req, credentials: BearerAuth| {
+                if revocations.lock().unwrap().contains(credentials.token()) {
+                    future::err(
+                        AuthenticationError::from(Config::default()).into(),
+                    )
+                } else {
+                    future::ok(req)
+                }
+            });
+        let validator: Validator =
+            CachedValidator::new(inner, Duration::from_secs(60), 16);
+        let handle = validator.clone();
+
+        assert_eq!(status(&validator, "first"), StatusCode::OK);
+        assert_eq!(status(&validator, "second"), StatusCode::OK);
+        revoked.lock().unwrap().extend(vec!["first", "second"]);
+        // Cached results are still used until invalidated
+        assert_eq!(status(&validator, "first"), StatusCode::OK);
+
+        handle.invalidate("first");
+        assert_eq!(status(&validator, "first"), StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&validator, "second"), StatusCode::OK);
+
+        handle.invalidate_all();
+        assert_eq!(status(&validator, "second"), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_invalidate_pending() {
+        let (sender, receiver) = oneshot::channel::<()>();
+        let receiver = Arc::new(Mutex::new(Some(receiver)));
+        let fixture = Fixture::new();
+        let counter = fixture.clone();
+        let validator = CachedValidator::new(
+            move |req, _credentials| {
+                counter.call();
+                let pending = receiver.lock().unwrap().take();
+                match pending {
+                    Some(receiver) => {
+                        Either::A(receiver.map(move |()| req).map_err(|_| {
+                            actix_web::error::ErrorInternalServerError("gone")
+                        }))
+                    }
+                    None => Either::B(future::ok(req)),
+                }
+            },
+            Duration::from_secs(60),
+            16,
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let pending =
+            validator.validate(req, BearerAuth::new("token".to_string()));
+        validator.invalidate("token");
+        sender.send(()).unwrap();
+        assert!(test::block_on(pending).is_ok());
+
+        // Result of the validation started before invalidation is dropped
+        let req = TestRequest::default().to_srv_request();
+        let result = test::block_on(
+            validator.validate(req, BearerAuth::new("token".to_string())),
+        );
+        assert!(result.is_ok());
+        assert_eq!(fixture.calls(), 2);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let fixture = Fixture::new();
+        let validator = validator(&fixture, 2);
+
+        status(&validator, "first");
+        status(&validator, "second");
+        // Touching the first token makes the second one the oldest
+        status(&validator, "first");
+        status(&validator, "third");
+        assert_eq!(fixture.calls(), 3);
+
+        status(&validator, "first");
+        assert_eq!(fixture.calls(), 3);
+        status(&validator, "second");
+        assert_eq!(fixture.calls(), 4);
+    }
+}
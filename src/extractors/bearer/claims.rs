@@ -0,0 +1,102 @@
+use std::error::Error as StdError;
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+
+/// Error occurred while peeking at the token claims,
+/// see [`peek_claims`](./fn.peek_claims.html).
+#[derive(Debug)]
+pub enum PeekError {
+    /// Token does not consist of the dot-separated segments.
+    Malformed,
+    /// Payload segment is not the base64url encoded value.
+    Base64(base64::DecodeError),
+    /// Payload can't be deserialized into the requested type.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for PeekError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PeekError::Malformed => f.write_str("Token payload is missing"),
+            PeekError::Base64(e) => fmt::Display::fmt(e, f),
+            PeekError::Json(e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+impl StdError for PeekError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            PeekError::Malformed => None,
+            PeekError::Base64(e) => Some(e),
+            PeekError::Json(e) => Some(e),
+        }
+    }
+}
+
+/// Decodes the payload segment of the JWT-like `token`
+/// into the `T` claims **without verifying the signature**.
+///
+/// Claims returned are whatever the client has put into the token,
+/// so this function is meant for the tooling and the development-only
+/// diagnostics endpoints: it must never be used to authenticate
+/// or to authorize the requests.
+///
+/// ## Example
+///
+/// ```rust
+/// # use std::collections::HashMap;
+/// # use actix_web_httpauth::extractors::bearer::peek_claims;
+/// // {"sub":"1234567890"}, signature is not checked
+/// let token = "eyJhbGciOiJIUzI1NiJ9.eyJzdWIiOiIxMjM0NTY3ODkwIn0.c2ln";
+/// let claims: HashMap<String, String> = peek_claims(token).unwrap();
+///
+/// assert_eq!(claims["sub"], "1234567890");
+/// ```
+pub fn peek_claims<T: DeserializeOwned>(token: &str) -> Result<T, PeekError> {
+    let payload = token.split('.').nth(1).ok_or(PeekError::Malformed)?;
+    // Padding is not used in JWTs, but tolerated
+    let payload = base64::decode_config(
+        payload.trim_end_matches('='),
+        base64::URL_SAFE_NO_PAD,
+    )
+    .map_err(PeekError::Base64)?;
+
+    serde_json::from_slice(&payload).map_err(PeekError::Json)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{peek_claims, PeekError};
+
+    #[test]
+    fn test_peek_claims() {
+        let payload = base64::encode_config(
+            r#"{"sub":"alice","scope":"email"}"#,
+            base64::URL_SAFE_NO_PAD,
+        );
+        let token = format!("eyJhbGciOiJub25lIn0.{}.", payload);
+
+        let claims: Value = peek_claims(&token).unwrap();
+        assert_eq!(claims, json!({"sub": "alice", "scope": "email"}));
+    }
+
+    #[test]
+    fn test_malformed_token() {
+        match peek_claims::<Value>("opaque") {
+            Err(PeekError::Malformed) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        match peek_claims::<Value>("header.%%%.signature") {
+            Err(PeekError::Base64(_)) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        match peek_claims::<Value>("header.bm90IGpzb24.signature") {
+            Err(PeekError::Json(_)) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
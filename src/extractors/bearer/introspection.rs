@@ -0,0 +1,527 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use actix_web::dev::ServiceRequest;
+use actix_web::http::StatusCode;
+use actix_web::{Error, HttpMessage, HttpResponse, ResponseError};
+use futures::future::{self, Future};
+
+use super::cache::{self, Lru};
+use super::{BearerAuth, Config};
+use crate::clock::{Clock, SystemClock};
+use crate::extractors::AuthenticationError;
+use crate::headers::www_authenticate::bearer;
+use crate::middleware::SingleFlight;
+
+/// Token information returned by the introspection endpoint,
+/// as described in [RFC 7662](https://tools.ietf.org/html/rfc7662#section-2.2).
+///
+/// On successful validation it is stored in the request extensions,
+/// so the handlers can access the token `scope`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Introspection {
+    /// Whether the token is currently active.
+    pub active: bool,
+    /// Space-delimited list of the token scopes.
+    pub scope: Option<String>,
+    /// Token expiration time.
+    pub exp: Option<SystemTime>,
+}
+
+impl Introspection {
+    fn is_active(&self, now: SystemTime) -> bool {
+        self.active && self.exp.is_none_or(|exp| exp > now)
+    }
+}
+
+/// Error occurred while calling the introspection endpoint.
+#[derive(Debug, Clone)]
+pub enum IntrospectionError {
+    /// Endpoint did not respond in time,
+    /// resolves into the `HTTP 503` status code.
+    Timeout,
+    /// Request has failed or the endpoint response is malformed,
+    /// resolves into the `HTTP 502` status code.
+    Failed(String),
+}
+
+impl fmt::Display for IntrospectionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntrospectionError::Timeout => {
+                f.write_str("Introspection endpoint timed out")
+            }
+            IntrospectionError::Failed(reason) => {
+                write!(f, "Introspection request failed: {}", reason)
+            }
+        }
+    }
+}
+
+impl StdError for IntrospectionError {}
+
+impl ResponseError for IntrospectionError {
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            IntrospectionError::Timeout => {
+                HttpResponse::new(StatusCode::SERVICE_UNAVAILABLE)
+            }
+            IntrospectionError::Failed(_) => {
+                HttpResponse::new(StatusCode::BAD_GATEWAY)
+            }
+        }
+    }
+}
+
+/// HTTP client for the token introspection endpoint.
+///
+/// Implementation is expected to `POST` the `token` to the introspection
+/// endpoint, authenticating itself with the client credentials, and to parse
+/// the `active`, `scope` and `exp` response fields.
+///
+/// No default implementation is provided, since the crate does not depend
+/// on any HTTP client; it is up to the app, ex. with the `awc` client.
+pub trait IntrospectionClient: Send + Sync {
+    /// Introspects the `token`.
+    fn introspect(
+        &self,
+        token: &str,
+    ) -> Box<dyn Future<Item = Introspection, Error = IntrospectionError>>;
+}
+
+/// Ready-made validator for the [`HttpAuthentication::bearer`] middleware,
+/// which checks the tokens with the
+/// [RFC 7662](https://tools.ietf.org/html/rfc7662) introspection endpoint.
+///
+/// Active tokens are cached by their SHA-256 digest for the configured
+/// duration (one minute by default), but never past their expiration
+/// time; cache holds at most 1024 tokens by default, evicting the least
+/// recently used ones. Inactive tokens are rejected with the
+/// `invalid_token` error. Challenge is formed from the
+/// [`Config`] instance fetched from the app data.
+///
+/// With [`single_flight`](#method.single_flight) enabled, concurrent
+/// requests with the same token share one introspection call.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::App;
+/// # use futures::future::{self, Future};
+/// # use actix_web_httpauth::extractors::bearer::{
+/// #     Introspection, IntrospectionClient, IntrospectionError,
+/// #     IntrospectionValidator,
+/// # };
+/// # use actix_web_httpauth::middleware::HttpAuthentication;
+/// struct Client;
+///
+/// impl IntrospectionClient for Client {
+///     fn introspect(
+///         &self,
+///         _token: &str,
+///     ) -> Box<dyn Future<Item = Introspection, Error = IntrospectionError>>
+///     {
+///         // Call the introspection endpoint here
+///         Box::new(future::ok(Introspection::default()))
+///     }
+/// }
+///
+/// let validator = IntrospectionValidator::new(Client);
+/// let middleware = HttpAuthentication::bearer(move |req, credentials| {
+///     validator.validate(req, credentials)
+/// });
+/// let app = App::new().wrap(middleware);
+/// ```
+///
+/// [`HttpAuthentication::bearer`]: ../../middleware/struct.HttpAuthentication.html#method.bearer
+/// [`Config`]: ./struct.Config.html
+#[derive(Clone)]
+pub struct IntrospectionValidator {
+    inner: Arc<Inner>,
+}
+
+type Lookup = Box<dyn Future<Item = Introspection, Error = IntrospectionError>>;
+
+type Flight = SingleFlight<
+    Box<dyn Fn(&str) -> Lookup + Send + Sync>,
+    Introspection,
+    IntrospectionError,
+>;
+
+struct Inner {
+    client: Arc<dyn IntrospectionClient>,
+    cache: Mutex<Lru<Introspection>>,
+    cache_ttl: Duration,
+    cache_capacity: usize,
+    clock: Arc<dyn Clock>,
+    flight: Option<Flight>,
+}
+
+impl Inner {
+    fn cached(&self, token: &str) -> Option<Introspection> {
+        cache::lock(&self.cache).get(&cache::key(token), self.clock.now())
+    }
+
+    fn remember(&self, token: &str, introspection: Introspection) {
+        let mut expires = self.clock.now() + self.cache_ttl;
+        if let Some(exp) = introspection.exp {
+            expires = expires.min(exp);
+        }
+
+        cache::lock(&self.cache).insert(
+            cache::key(token),
+            introspection,
+            expires,
+            self.cache_capacity,
+        );
+    }
+
+    /// Calls the introspection endpoint, or joins the in-flight call
+    /// for the same token if single-flight is enabled.
+    fn introspect(&self, token: &str) -> Lookup {
+        match self.flight {
+            Some(ref flight) => flight.call(token),
+            None => self.client.introspect(token),
+        }
+    }
+}
+
+impl IntrospectionValidator {
+    /// Creates new validator, which uses the `client`
+    /// to call the introspection endpoint.
+    pub fn new<C>(client: C) -> IntrospectionValidator
+    where
+        C: IntrospectionClient + 'static,
+    {
+        IntrospectionValidator {
+            inner: Arc::new(Inner {
+                client: Arc::new(client),
+                cache: Mutex::new(Lru::default()),
+                cache_ttl: Duration::from_secs(60),
+                cache_capacity: 1024,
+                clock: Arc::new(SystemClock),
+                flight: None,
+            }),
+        }
+    }
+
+    /// Set for how long the active tokens are cached.
+    ///
+    /// `Duration::from_secs(0)` disables the caching.
+    ///
+    /// ## Panics
+    ///
+    /// If called after the validator was cloned.
+    pub fn cache_ttl(mut self, value: Duration) -> IntrospectionValidator {
+        self.inner_mut().cache_ttl = value;
+        self
+    }
+
+    /// Set the maximum number of the cached tokens.
+    ///
+    /// ## Panics
+    ///
+    /// If `value` is zero, or if called after the validator was cloned.
+    pub fn cache_capacity(mut self, value: usize) -> IntrospectionValidator {
+        assert!(value > 0, "Cache capacity must be positive");
+        self.inner_mut().cache_capacity = value;
+        self
+    }
+
+    /// Set the time source, ex. the [`ManualClock`] for tests.
+    ///
+    /// ## Panics
+    ///
+    /// If called after the validator was cloned.
+    ///
+    /// [`ManualClock`]: ../../clock/struct.ManualClock.html
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> IntrospectionValidator {
+        self.inner_mut().clock = clock;
+        self
+    }
+
+    /// Share one introspection call between the concurrent requests
+    /// with the same token, so the burst of requests arriving before
+    /// the token is cached does not flood the introspection endpoint.
+    ///
+    /// If the request which started the call is dropped before it
+    /// completes, the waiting requests are calling the endpoint
+    /// on their own instead.
+    ///
+    /// See [`SingleFlight`] for the same for the custom validators.
+    ///
+    /// ## Panics
+    ///
+    /// If called after the validator was cloned.
+    ///
+    /// [`SingleFlight`]: ../../middleware/struct.SingleFlight.html
+    pub fn single_flight(mut self) -> IntrospectionValidator {
+        let inner = self.inner_mut();
+        let client = inner.client.clone();
+        inner.flight = Some(SingleFlight::new(Box::new(move |token: &str| {
+            client.introspect(token)
+        })));
+        self
+    }
+
+    /// Validates the `credentials`, suitable to be called
+    /// from the [`HttpAuthentication::bearer`] validation callback.
+    ///
+    /// [`HttpAuthentication::bearer`]: ../../middleware/struct.HttpAuthentication.html#method.bearer
+    pub fn validate(
+        &self,
+        req: ServiceRequest,
+        credentials: BearerAuth,
+    ) -> Box<dyn Future<Item = ServiceRequest, Error = Error>> {
+        if let Some(introspection) = self.inner.cached(credentials.token()) {
+            req.extensions_mut().insert(introspection);
+            return Box::new(future::ok(req));
+        }
+
+        let inner = self.inner.clone();
+        let token = credentials.token().to_string();
+        let introspection = inner.introspect(&token);
+
+        Box::new(introspection.then(move |result| match result {
+            Ok(ref introspection)
+                if introspection.is_active(inner.clock.now()) =>
+            {
+                if inner.cache_ttl > Duration::from_secs(0) {
+                    inner.remember(&token, introspection.clone());
+                }
+                req.extensions_mut().insert(introspection.clone());
+                Ok(req)
+            }
+            Ok(_) => {
+                let config = req
+                    .app_data::<Config>()
+                    .map(|config| config.get_ref().clone())
+                    .unwrap_or_default();
+                Err(AuthenticationError::from(config)
+                    .with_error(bearer::Error::InvalidToken)
+                    .into())
+            }
+            Err(e) => Err(e.into()),
+        }))
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Arc::get_mut(&mut self.inner)
+            .expect("IntrospectionValidator was cloned already")
+    }
+}
+
+impl fmt::Debug for IntrospectionValidator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IntrospectionValidator")
+            .field("cache_ttl", &self.inner.cache_ttl)
+            .field("cache_capacity", &self.inner.cache_capacity)
+            .field("clock", &self.inner.clock)
+            .field("single_flight", &self.inner.flight.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::HttpMessage;
+    use futures::future::{self, Future};
+    use futures::sync::oneshot;
+
+    use super::{
+        Introspection, IntrospectionClient, IntrospectionError,
+        IntrospectionValidator,
+    };
+    use crate::extractors::bearer::BearerAuth;
+    use crate::middleware::tests::{validation_status, Fixture};
+
+    struct Client(Fixture);
+
+    impl IntrospectionClient for Client {
+        fn introspect(
+            &self,
+            token: &str,
+        ) -> Box<dyn Future<Item = Introspection, Error = IntrospectionError>>
+        {
+            self.0.call();
+            let result = match token {
+                "active" | "also-active" => Ok(Introspection {
+                    active: true,
+                    scope: Some("email".to_string()),
+                    exp: Some(UNIX_EPOCH + Duration::from_secs(3600)),
+                }),
+                "expired" => Ok(Introspection {
+                    active: true,
+                    scope: None,
+                    exp: Some(UNIX_EPOCH),
+                }),
+                "slow" => Err(IntrospectionError::Timeout),
+                _ => Ok(Introspection::default()),
+            };
+
+            Box::new(future::result(result))
+        }
+    }
+
+    /// Client whose first call is pending until the test completes it.
+    #[derive(Clone, Default)]
+    struct PendingClient {
+        calls: Arc<AtomicUsize>,
+        pending: Arc<Mutex<Option<oneshot::Sender<Introspection>>>>,
+    }
+
+    impl IntrospectionClient for PendingClient {
+        fn introspect(
+            &self,
+            _token: &str,
+        ) -> Box<dyn Future<Item = Introspection, Error = IntrospectionError>>
+        {
+            let active = Introspection {
+                active: true,
+                scope: None,
+                exp: None,
+            };
+            if self.calls.fetch_add(1, Ordering::SeqCst) > 0 {
+                return Box::new(future::ok(active));
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            *self.pending.lock().unwrap() = Some(sender);
+            Box::new(receiver.map_err(|_| {
+                IntrospectionError::Failed("cancelled".to_string())
+            }))
+        }
+    }
+
+    fn validator(fixture: &Fixture) -> IntrospectionValidator {
+        IntrospectionValidator::new(Client(fixture.clone()))
+            .clock(Arc::new(fixture.clock.clone()))
+    }
+
+    fn status(validator: &IntrospectionValidator, token: &str) -> StatusCode {
+        let req = TestRequest::default().to_srv_request();
+        validation_status(
+            validator.validate(req, BearerAuth::new(token.to_string())),
+        )
+    }
+
+    #[test]
+    fn test_active_token() {
+        let validator = validator(&Fixture::new());
+        let req = TestRequest::default().to_srv_request();
+
+        let req =
+            test::block_on(validator.validate(req, BearerAuth::new("active")))
+                .unwrap();
+        let extensions = req.extensions();
+        let introspection = extensions.get::<Introspection>().unwrap();
+        assert_eq!(introspection.scope.as_ref().unwrap(), "email");
+    }
+
+    #[test]
+    fn test_inactive_token() {
+        let validator = validator(&Fixture::new());
+
+        assert_eq!(status(&validator, "unknown"), StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&validator, "expired"), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_timeout() {
+        let validator = validator(&Fixture::new());
+
+        assert_eq!(status(&validator, "slow"), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn test_cache() {
+        let fixture = Fixture::new();
+        let validator = validator(&fixture);
+
+        assert_eq!(status(&validator, "active"), StatusCode::OK);
+        assert_eq!(status(&validator, "active"), StatusCode::OK);
+        assert_eq!(fixture.calls(), 1);
+
+        fixture.clock.advance(Duration::from_secs(61));
+        assert_eq!(status(&validator, "active"), StatusCode::OK);
+        assert_eq!(fixture.calls(), 2);
+
+        // Inactive tokens are not cached
+        assert_eq!(status(&validator, "unknown"), StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&validator, "unknown"), StatusCode::UNAUTHORIZED);
+        assert_eq!(fixture.calls(), 4);
+    }
+
+    #[test]
+    fn test_cache_capacity() {
+        let fixture = Fixture::new();
+        let validator = validator(&fixture).cache_capacity(1);
+
+        assert_eq!(status(&validator, "active"), StatusCode::OK);
+        assert_eq!(status(&validator, "also-active"), StatusCode::OK);
+        assert_eq!(status(&validator, "also-active"), StatusCode::OK);
+        assert_eq!(fixture.calls(), 2);
+
+        // Least recently used token is evicted
+        assert_eq!(status(&validator, "active"), StatusCode::OK);
+        assert_eq!(fixture.calls(), 3);
+    }
+
+    #[test]
+    fn test_single_flight() {
+        let client = PendingClient::default();
+        let validator =
+            IntrospectionValidator::new(client.clone()).single_flight();
+        let validate = || {
+            let req = TestRequest::default().to_srv_request();
+            validator.validate(req, BearerAuth::new("token"))
+        };
+
+        let first = validate();
+        let second = validate();
+        assert_eq!(client.calls.load(Ordering::SeqCst), 1);
+
+        let sender = client.pending.lock().unwrap().take().unwrap();
+        sender
+            .send(Introspection {
+                active: true,
+                scope: Some("email".to_string()),
+                exp: None,
+            })
+            .unwrap();
+        assert!(test::block_on(first).is_ok());
+        let req = test::block_on(second).unwrap();
+        let extensions = req.extensions();
+        let introspection = extensions.get::<Introspection>().unwrap();
+        assert_eq!(introspection.scope.as_ref().unwrap(), "email");
+        assert_eq!(client.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_single_flight_cancelled() {
+        let client = PendingClient::default();
+        let validator = IntrospectionValidator::new(client.clone())
+            .cache_ttl(Duration::from_secs(0))
+            .single_flight();
+        let validate = || {
+            let req = TestRequest::default().to_srv_request();
+            validator.validate(req, BearerAuth::new("token"))
+        };
+
+        let first = validate();
+        let second = validate();
+        drop(first);
+
+        // Waiter calls the endpoint on its own
+        assert!(test::block_on(second).is_ok());
+        assert_eq!(client.calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,1404 @@
+//! Extractor for the "Bearer" HTTP Authentication Scheme
+
+use std::borrow::Cow;
+use std::default::Default;
+use std::fmt;
+use std::sync::Arc;
+
+use actix_web::dev::{Payload, RequestHead, ServiceRequest};
+use actix_web::http::header::{
+    HeaderName, HeaderValue, AUTHORIZATION, LINK, SEC_WEBSOCKET_PROTOCOL,
+};
+use actix_web::{FromRequest, HttpRequest};
+
+use super::config::AuthExtractorConfig;
+use super::error_options::ErrorOptions;
+use super::errors::AuthenticationError;
+use super::form_login::FormToken;
+use super::lookup::HeaderLookup;
+use super::openapi::SecuritySchemeDescriptor;
+use super::{basic, AssuranceLevel, AuthExtractor, AuthExtractorRef};
+use crate::headers::authorization::{self, split_scheme, ParseError, Scheme};
+use crate::headers::www_authenticate::bearer;
+pub use crate::headers::www_authenticate::bearer::Error;
+use crate::headers::www_authenticate::bearer_or_basic::BearerOrBasic;
+use crate::utils::Callback;
+
+mod cache;
+mod claims;
+mod introspection;
+#[cfg(feature = "paseto")]
+mod paseto;
+mod session;
+
+pub use self::cache::CachedValidator;
+pub use self::claims::{peek_claims, PeekError};
+pub use self::introspection::{
+    Introspection, IntrospectionClient, IntrospectionError,
+    IntrospectionValidator,
+};
+#[cfg(feature = "paseto")]
+pub use self::paseto::{PasetoAuth, PasetoConfig};
+pub use self::session::{
+    InMemorySessionStore, SessionStore, SessionSubject, SessionValidator,
+};
+
+type RevocationCheck = dyn Fn(&str) -> bool + Send + Sync;
+
+/// Marks the requests which token came in the `access_token` query parameter.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct QueryToken;
+
+/// Marks the requests which token came in the `Sec-WebSocket-Protocol`
+/// header, holding the subprotocol to be echoed back.
+#[derive(Debug, Clone)]
+pub(crate) struct SubprotocolToken {
+    /// First offered subprotocol, which is not carrying the token.
+    pub(crate) protocol: Option<HeaderValue>,
+    /// Prefix of the protocol entry carrying the token.
+    pub(crate) prefix: Cow<'static, str>,
+}
+
+impl SubprotocolToken {
+    /// Returns `true` if the `protocol` is the one carrying the token.
+    pub(crate) fn is_token(&self, protocol: &HeaderValue) -> bool {
+        protocol
+            .to_str()
+            .map_or(true, |protocol| protocol.starts_with(&*self.prefix))
+    }
+}
+
+/// [BearerAuth](./struct/BearerAuth.html) extractor configuration.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    challenge: bearer::Bearer,
+    lookup: HeaderLookup,
+    bearer_format: Option<Cow<'static, str>>,
+    separator: Option<char>,
+    revocation_check: Option<Callback<RevocationCheck>>,
+    strict_scheme: bool,
+    supported_schemes: Vec<HeaderValue>,
+    unsupported_scheme_error: bool,
+    errors: ErrorOptions,
+    omit_challenge: bool,
+    trim_quotes: bool,
+    pub(crate) form_body: bool,
+    query_token: bool,
+    subprotocol_prefix: Option<Cow<'static, str>>,
+    token_endpoint: Option<HeaderValue>,
+}
+
+impl Config {
+    /// Set challenge `scope` attribute.
+    ///
+    /// The `"scope"` attribute is a space-delimited list of case-sensitive
+    /// scope values indicating the required scope of the access token for
+    /// accessing the requested resource.
+    pub fn scope<T: Into<Cow<'static, str>>>(mut self, value: T) -> Config {
+        self.challenge.scope = Some(value.into());
+        self
+    }
+
+    /// Set challenge `realm` attribute.
+    ///
+    /// The "realm" attribute indicates the scope of protection in the manner
+    /// described in HTTP/1.1 [RFC2617](https://tools.ietf.org/html/rfc2617#section-1.2).
+    pub fn realm<T: Into<Cow<'static, str>>>(mut self, value: T) -> Config {
+        self.challenge.realm = Some(value.into());
+        self
+    }
+
+    /// Set challenge `domain` attribute, the list of URIs which are
+    /// sharing the protection space, ex. `vec!["/api", "/admin"]`.
+    pub fn domain<I>(mut self, uris: I) -> Config
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'static, str>>,
+    {
+        self.challenge.domain = uris.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the list of headers to look for the token in.
+    ///
+    /// Headers are tried in the provided order and the first present one
+    /// is used. By default only the `Authorization` header is used.
+    ///
+    /// ## Panics
+    ///
+    /// If any of `names` is not a valid header name.
+    pub fn header_priority<I>(mut self, names: I) -> Config
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.lookup.set_names(names);
+        self
+    }
+
+    /// Proceed to the next header from the [`header_priority`] list
+    /// when the present one is malformed, instead of failing.
+    ///
+    /// [`header_priority`]: #method.header_priority
+    pub fn fallthrough_on_malformed(mut self, value: bool) -> Config {
+        self.lookup.set_fallthrough(value);
+        self
+    }
+
+    /// Set the options of the error responses, shared with the other
+    /// extractors, see [`ErrorOptions`].
+    ///
+    /// [`ErrorOptions`]: ../struct.ErrorOptions.html
+    pub fn error_options(mut self, options: ErrorOptions) -> Config {
+        self.errors = options;
+        self
+    }
+
+    /// Split the token into parts by the `separator`,
+    /// ex. for the `Bearer <user>.<service>` tokens.
+    ///
+    /// Parts are available via [`BearerAuth::parts`],
+    /// while [`BearerAuth::token`] still returns the raw value.
+    ///
+    /// [`BearerAuth::parts`]: ./struct.BearerAuth.html#method.parts
+    /// [`BearerAuth::token`]: ./struct.BearerAuth.html#method.token
+    pub fn split_token(mut self, separator: char) -> Config {
+        self.separator = Some(separator);
+        self
+    }
+
+    /// Set the function which tells if the token was revoked,
+    /// ex. by consulting the blocklist.
+    ///
+    /// It is called with the raw token before the validator runs,
+    /// revoked tokens are rejected with the `invalid_token` error.
+    pub fn revocation_check(mut self, check: Arc<RevocationCheck>) -> Config {
+        self.revocation_check = Some(Callback::new(check));
+        self
+    }
+
+    /// Take the token from the `access_token` query parameter, as defined
+    /// by [RFC 6750](https://tools.ietf.org/html/rfc6750#section-2.3),
+    /// when there is no `Authorization` header.
+    ///
+    /// Responses to such requests are marked with `Cache-Control: no-store`
+    /// by the [`HttpAuthentication`] middleware, unless the inner service
+    /// sets the `Cache-Control` header by itself. Extractors can't alter
+    /// the response, so when the `BearerAuth` extractor is used directly
+    /// in the handler, without the middleware, the handler has to set
+    /// the header. Disabled by default.
+    ///
+    /// ## Security
+    ///
+    /// Tokens in the URI are exposed to the access logs, proxies
+    /// and browser history, and are leaked with the `Referer` header.
+    /// Enable this only for the clients which can't send the header,
+    /// ex. the `EventSource` or `<img>` requests in the browsers.
+    ///
+    /// [`HttpAuthentication`]: ../../middleware/struct.HttpAuthentication.html
+    pub fn allow_query_token(mut self, value: bool) -> Config {
+        self.query_token = value;
+        self
+    }
+
+    /// Take the token from the `Sec-WebSocket-Protocol` entry starting
+    /// with the `prefix`, ex. `"access_token."`, when there is no
+    /// `Authorization` header, since the browsers can't set the headers
+    /// of the WebSocket handshake.
+    ///
+    /// Once the request is validated, the [`HttpAuthentication`]
+    /// middleware echoes the first of the other offered subprotocols in
+    /// the `Sec-WebSocket-Protocol` response header, so the handshake
+    /// completes; the entry carrying the token is never echoed back.
+    /// Disabled by default.
+    ///
+    /// [`HttpAuthentication`]: ../../middleware/struct.HttpAuthentication.html
+    pub fn subprotocol_token<T>(mut self, prefix: T) -> Config
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.subprotocol_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Take the token from the `access_token` field of the urlencoded
+    /// form body, as defined by
+    /// [RFC 6750](https://tools.ietf.org/html/rfc6750#section-2.2),
+    /// when there is no `Authorization` header.
+    ///
+    /// Body is buffered by the [`HttpAuthentication`] middleware if the
+    /// request method is not `GET` and its `Content-Length` does not exceed
+    /// 16 KiB; it is put back into the request for the handler.
+    /// Disabled by default.
+    ///
+    /// [`HttpAuthentication`]: ../../middleware/struct.HttpAuthentication.html
+    pub fn allow_form_body(mut self, value: bool) -> Config {
+        self.form_body = value;
+        self
+    }
+
+    /// Strip a single pair of the double quotes surrounding the token,
+    /// ex. `Bearer "mF_9.B5f-4.1JqM"` sent by some buggy clients.
+    ///
+    /// Tokens quoted on one side only are left as is.
+    /// Disabled by default, so the token bytes are preserved exactly.
+    pub fn trim_quotes(mut self, value: bool) -> Config {
+        self.trim_quotes = value;
+        self
+    }
+
+    /// Respond distinctly when the credentials for some other
+    /// authentication scheme are sent, ex. `Authorization: Weird xyz`.
+    ///
+    /// Error response still has the `HTTP 401` status code, but it lists
+    /// the challenges of all supported schemes: the `Bearer` one, extended
+    /// with the `error_description="unsupported authentication scheme"`
+    /// attribute, followed by the [`supported_schemes`] ones, signalling
+    /// the client to retry with a known scheme. Disabled by default.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web_httpauth::extractors::bearer::Config;
+    /// let config = Config::default()
+    ///     .realm("api")
+    ///     .strict_scheme(true)
+    ///     .supported_schemes(vec!["Basic realm=\"api\""])
+    ///     .unsupported_scheme_error(true);
+    /// ```
+    ///
+    /// [`supported_schemes`]: #method.supported_schemes
+    pub fn strict_scheme(mut self, value: bool) -> Config {
+        self.strict_scheme = value;
+        self
+    }
+
+    /// Set the challenges of the other schemes supported by the application,
+    /// ex. `Basic realm="api"`, advertised in the [`strict_scheme`]
+    /// rejections after the `Bearer` one. They are sent as is, without
+    /// being passed to the challenge interceptor.
+    ///
+    /// ## Panics
+    ///
+    /// If any of `challenges` is not a valid header value.
+    ///
+    /// [`strict_scheme`]: #method.strict_scheme
+    pub fn supported_schemes<I>(mut self, challenges: I) -> Config
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.supported_schemes = challenges
+            .into_iter()
+            .map(|challenge| {
+                let challenge = challenge.as_ref();
+                HeaderValue::from_str(challenge).unwrap_or_else(|_| {
+                    panic!("Invalid challenge: {:?}", challenge)
+                })
+            })
+            .collect();
+        self
+    }
+
+    /// Set the `error="unsupported_scheme"` attribute of the `Bearer`
+    /// challenge in the [`strict_scheme`] rejections.
+    ///
+    /// Since RFC 6750 does not define this error code, clients might
+    /// not recognize it. Disabled by default.
+    ///
+    /// [`strict_scheme`]: #method.strict_scheme
+    pub fn unsupported_scheme_error(mut self, value: bool) -> Config {
+        self.unsupported_scheme_error = value;
+        self
+    }
+
+    /// Advertise the `url` of the token endpoint in the `HTTP 401`
+    /// responses, so the clients can discover how to obtain the token:
+    /// `Link: <https://auth.example/token>; rel="oauth2-token-endpoint"`.
+    ///
+    /// ## Panics
+    ///
+    /// If `url` can't be used in the header value.
+    pub fn token_endpoint(mut self, url: &str) -> Config {
+        let link = format!("<{}>; rel=\"oauth2-token-endpoint\"", url);
+        let link = HeaderValue::from_str(&link)
+            .unwrap_or_else(|_| panic!("Invalid token endpoint: {:?}", url));
+        self.token_endpoint = Some(link);
+        self
+    }
+
+    /// Respond with the bare `HTTP 401`, without the `WWW-Authenticate`
+    /// header at all, ex. for the SPA backends behind the proxies
+    /// rewriting the challenges into the browser-dialog triggering ones.
+    ///
+    /// Applies to the extraction errors and to the errors created from
+    /// this config, ex. with `AuthenticationError::from(config)`;
+    /// `HTTP 403` responses still carry the challenge.
+    /// Disabled by default.
+    pub fn omit_challenge_on_401(mut self, value: bool) -> Config {
+        self.omit_challenge = value;
+        self
+    }
+
+    /// Set the hint about the bearer token format, ex. `"JWT"`.
+    ///
+    /// It is used for documentation purposes only,
+    /// see [`openapi_scheme`](#method.openapi_scheme).
+    pub fn bearer_format<T: Into<Cow<'static, str>>>(
+        mut self,
+        value: T,
+    ) -> Config {
+        self.bearer_format = Some(value.into());
+        self
+    }
+
+    /// Builds the error carrying the configured challenge
+    /// and the CORS headers for the request.
+    pub(crate) fn challenge_error(
+        &self,
+        head: &RequestHead,
+    ) -> AuthenticationError<bearer::Bearer> {
+        self.errors
+            .apply(AuthenticationError::new(self.challenge.clone()), head)
+            .omit_challenge_on_401(self.omit_challenge)
+            .with_token_endpoint(&self.token_endpoint)
+    }
+
+    /// Describe the enforced authentication scheme for the OpenAPI
+    /// documents generation.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web_httpauth::extractors::bearer::Config;
+    /// let config = Config::default().bearer_format("JWT").scope("email photo");
+    /// let descriptor = config.openapi_scheme();
+    ///
+    /// assert_eq!(descriptor.scheme(), "bearer");
+    /// assert_eq!(descriptor.bearer_format(), Some("JWT"));
+    /// assert_eq!(descriptor.scopes(), ["email", "photo"]);
+    /// ```
+    pub fn openapi_scheme(&self) -> SecuritySchemeDescriptor {
+        SecuritySchemeDescriptor::new(
+            "bearer",
+            self.bearer_format.clone(),
+            self.challenge.scope.as_ref().map(AsRef::as_ref),
+        )
+    }
+}
+
+impl AsRef<bearer::Bearer> for Config {
+    fn as_ref(&self) -> &bearer::Bearer {
+        &self.challenge
+    }
+}
+
+impl AuthExtractorConfig for Config {
+    type Inner = bearer::Bearer;
+
+    fn into_inner(self) -> Self::Inner {
+        self.challenge
+    }
+
+    fn into_error(self) -> AuthenticationError<Self::Inner> {
+        let omit_challenge = self.omit_challenge;
+        let token_endpoint = self.token_endpoint.clone();
+        let errors = self.errors.clone();
+
+        errors
+            .apply_headless(AuthenticationError::new(self.into_inner()))
+            .omit_challenge_on_401(omit_challenge)
+            .with_token_endpoint(&token_endpoint)
+    }
+}
+
+/// Extractor for HTTP Bearer auth
+///
+/// # Example
+///
+/// ```rust
+/// use actix_web_httpauth::extractors::bearer::BearerAuth;
+///
+/// fn index(auth: BearerAuth) -> String {
+///     format!("Hello, user with token {}!", auth.token())
+/// }
+/// ```
+///
+/// If authentication fails, this extractor fetches the [`Config`] instance
+/// from the [app data] in order to properly form the `WWW-Authenticate`
+/// response header.
+///
+/// ## Example
+///
+/// ```rust
+/// use actix_web::{web, App};
+/// use actix_web_httpauth::extractors::bearer::{BearerAuth, Config};
+///
+/// fn index(auth: BearerAuth) -> String {
+///     format!("Hello, {}!", auth.token())
+/// }
+///
+/// fn main() {
+///     let app = App::new()
+///         .data(
+///             Config::default()
+///                 .realm("Restricted area")
+///                 .scope("email photo"),
+///         )
+///         .service(web::resource("/index.html").route(web::get().to(index)));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BearerAuth {
+    credentials: authorization::Bearer,
+    parts: Vec<String>,
+}
+
+impl BearerAuth {
+    /// Creates the credentials directly, without parsing the request,
+    /// ex. for testing the validators in isolation.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web_httpauth::extractors::bearer::BearerAuth;
+    /// let auth = BearerAuth::new("mF_9.B5f-4.1JqM");
+    ///
+    /// assert_eq!(auth.token(), "mF_9.B5f-4.1JqM");
+    /// ```
+    pub fn new<T>(token: T) -> BearerAuth
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        BearerAuth {
+            credentials: authorization::Bearer::new(token),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Returns bearer token provided by client.
+    pub fn token(&self) -> &str {
+        self.credentials.token()
+    }
+
+    /// Returns the token parts, if [`Config::split_token`] was configured.
+    ///
+    /// Slice is empty otherwise.
+    ///
+    /// [`Config::split_token`]: ./struct.Config.html#method.split_token
+    pub fn parts(&self) -> &[String] {
+        &self.parts
+    }
+
+    /// Error is boxed, since the bearer challenge is several times
+    /// larger than the other ones.
+    pub(crate) fn extract(
+        head: &RequestHead,
+        config: &Config,
+    ) -> Result<Self, Box<AuthenticationError<bearer::Bearer>>> {
+        BearerAuth::extract_credentials(head, config).map_err(|error| {
+            let error = config
+                .errors
+                .apply(*error, head)
+                .omit_challenge_on_401(config.omit_challenge)
+                .with_token_endpoint(&config.token_endpoint);
+            Box::new(error)
+        })
+    }
+
+    /// Looks for the token in the query, in the WebSocket subprotocols
+    /// and in the form body, if any of them is allowed.
+    fn fallback_token(
+        head: &RequestHead,
+        config: &Config,
+    ) -> Option<authorization::Bearer> {
+        let query = head.uri.query().filter(|_| config.query_token);
+        let token = query.and_then(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(name, _)| name == "access_token")
+                .map(|(_, value)| value.into_owned())
+        });
+        if let Some(token) = token {
+            head.extensions_mut().insert(QueryToken);
+            return Some(authorization::Bearer::new(token));
+        }
+
+        if let Some(ref prefix) = config.subprotocol_prefix {
+            let protocols = head
+                .headers()
+                .get_all(SEC_WEBSOCKET_PROTOCOL)
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .map(str::trim)
+                .filter(|protocol| !protocol.is_empty());
+            let mut token = None;
+            let mut protocol = None;
+            for entry in protocols {
+                match entry.strip_prefix(&**prefix) {
+                    Some(value) if !value.is_empty() => {
+                        token = token.or_else(|| Some(value.to_string()))
+                    }
+                    Some(_) => (),
+                    None => protocol = protocol.or(Some(entry)),
+                }
+            }
+            if let Some(token) = token {
+                head.extensions_mut().insert(SubprotocolToken {
+                    protocol: protocol.and_then(|protocol| {
+                        HeaderValue::from_str(protocol).ok()
+                    }),
+                    prefix: prefix.clone(),
+                });
+                return Some(authorization::Bearer::new(token));
+            }
+        }
+
+        if config.form_body {
+            let extensions = head.extensions();
+            let token = extensions.get::<FormToken>()?;
+            return Some(authorization::Bearer::new(token.0.clone()));
+        }
+
+        None
+    }
+
+    fn extract_credentials(
+        head: &RequestHead,
+        config: &Config,
+    ) -> Result<Self, Box<AuthenticationError<bearer::Bearer>>> {
+        let headers = head.headers();
+        config
+            .errors
+            .check_size(&config.lookup, headers, &config.challenge)?;
+
+        let auth = config
+            .lookup
+            .find(headers, |header| {
+                let credentials = authorization::Bearer::parse(header)?;
+                if !config.trim_quotes {
+                    return Ok(credentials);
+                }
+
+                match credentials
+                    .token()
+                    .strip_prefix('"')
+                    .and_then(|token| token.strip_suffix('"'))
+                {
+                    Some("") => Err(ParseError::Invalid),
+                    Some(token) => {
+                        Ok(authorization::Bearer::new(token.to_string()))
+                    }
+                    None => Ok(credentials),
+                }
+            })
+            .or_else(|err| match err {
+                ParseError::MissingHeader => {
+                    BearerAuth::fallback_token(head, config).ok_or(err)
+                }
+                err => Err(err),
+            })
+            .map(|credentials| {
+                let parts = match config.separator {
+                    Some(separator) => credentials
+                        .token()
+                        .split(separator)
+                        .map(String::from)
+                        .collect(),
+                    None => Vec::new(),
+                };
+
+                BearerAuth {
+                    credentials,
+                    parts,
+                }
+            })
+            .map_err(|err| {
+                let mut error =
+                    AuthenticationError::new(config.challenge.clone())
+                        .with_parse_error(err);
+                if !config.strict_scheme
+                    || !config.lookup.has_foreign_scheme(headers, "Bearer")
+                {
+                    return error;
+                }
+
+                if config.unsupported_scheme_error {
+                    error.challenge_mut().params.push((
+                        Cow::Borrowed("error"),
+                        Cow::Borrowed("unsupported_scheme"),
+                    ));
+                }
+                config.supported_schemes.iter().fold(
+                    error.with_error_description(
+                        "unsupported authentication scheme",
+                    ),
+                    |error, challenge| {
+                        error.with_foreign_challenge(challenge.clone())
+                    },
+                )
+            })?;
+
+        match config.revocation_check {
+            Some(ref is_revoked) if is_revoked(auth.token()) => {
+                let error = AuthenticationError::new(config.challenge.clone())
+                    .with_error(Error::InvalidToken)
+                    .with_error_description("token revoked");
+                Err(Box::new(error))
+            }
+            _ => Ok(auth),
+        }
+    }
+}
+
+impl FromRequest for BearerAuth {
+    type Config = Config;
+    type Future = Result<Self, Self::Error>;
+    type Error = AuthenticationError<bearer::Bearer>;
+
+    fn from_request(
+        req: &HttpRequest,
+        _payload: &mut Payload,
+    ) -> <Self as FromRequest>::Future {
+        let config =
+            req.app_data::<Self::Config>().cloned().unwrap_or_default();
+
+        BearerAuth::extract(req.head(), &config).map_err(|error| *error)
+    }
+}
+
+/// Borrowed "Bearer" credentials, a non-allocating alternative
+/// to the [`BearerAuth`] extractor.
+///
+/// Token is read from the `Authorization` header only; [`Config`]
+/// is used for the error challenge, other options, ex. the token
+/// splitting or the revocation check, are not applied.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::test::TestRequest;
+/// # use actix_web_httpauth::extractors::bearer::BearerAuthRef;
+/// # use actix_web_httpauth::extractors::AuthExtractorRef;
+/// let req = TestRequest::with_header("Authorization", "Bearer mF_9.B5f-4.1JqM")
+///     .to_srv_request();
+/// let mut scratch = Vec::new();
+/// let credentials =
+///     BearerAuthRef::from_service_request_ref(&req, &mut scratch).unwrap();
+///
+/// assert_eq!(credentials.token(), "mF_9.B5f-4.1JqM");
+/// ```
+///
+/// [`BearerAuth`]: ./struct.BearerAuth.html
+/// [`Config`]: ./struct.Config.html
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct BearerAuthRef<'a> {
+    token: &'a str,
+}
+
+impl<'a> BearerAuthRef<'a> {
+    /// Returns bearer token provided by client.
+    pub fn token(&self) -> &'a str {
+        self.token
+    }
+
+    /// Parses the `Bearer` credentials from the `header`.
+    pub fn parse(
+        header: &'a HeaderValue,
+    ) -> Result<BearerAuthRef<'a>, ParseError> {
+        let (scheme, token) = split_scheme(header.to_str()?);
+        if scheme != "Bearer" {
+            return Err(ParseError::MissingScheme);
+        }
+
+        match token {
+            Some(token) => Ok(BearerAuthRef {
+                token,
+            }),
+            None => Err(ParseError::Invalid),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for BearerAuthRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Bearer ******")
+    }
+}
+
+impl<'a> AuthExtractorRef<'a> for BearerAuthRef<'a> {
+    type Error = AuthenticationError<bearer::Bearer>;
+
+    fn from_service_request_ref(
+        req: &'a ServiceRequest,
+        _scratch: &'a mut Vec<u8>,
+    ) -> Result<Self, Self::Error> {
+        req.headers()
+            .get(AUTHORIZATION)
+            .ok_or(ParseError::Invalid)
+            .and_then(BearerAuthRef::parse)
+            .map_err(|_| match req.app_data::<Config>() {
+                Some(config) => config.challenge_error(req.head()),
+                None => Config::default().challenge_error(req.head()),
+            })
+    }
+}
+
+impl AuthExtractor for BearerAuth {
+    type Future = Result<Self, Self::Error>;
+    type Error = AuthenticationError<bearer::Bearer>;
+
+    fn from_service_request(req: &ServiceRequest) -> Self::Future {
+        let config = req
+            .app_data::<Config>()
+            .map(|config| config.get_ref().clone())
+            .unwrap_or_default();
+
+        BearerAuth::extract(req.head(), &config).map_err(|error| *error)
+    }
+
+    fn uses_form_token() -> bool {
+        true
+    }
+}
+
+impl AssuranceLevel for BearerAuth {
+    fn assurance_level(&self) -> u8 {
+        2
+    }
+}
+
+/// Errors advertising both the `Bearer` and the `Basic` schemes.
+impl AuthenticationError<BearerOrBasic> {
+    /// Creates the error with the `Bearer` challenge formed from the
+    /// `bearer` config, followed by the `Basic` one formed from the `basic`
+    /// config, so both the token-based clients and the scripts using
+    /// the username and password can tell how to authenticate.
+    ///
+    /// Each challenge is sent in the separate `WWW-Authenticate` header;
+    /// `Cache-Control`, the error observer and the challenge interceptor
+    /// are taken from the `bearer` config.
+    ///
+    /// Usually returned from the validation callback of the middleware
+    /// using the [`Dispatch<BearerAuth, BasicAuth>`] extractor, which
+    /// advertises both schemes on the missing credentials already.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web_httpauth::extractors::{basic, bearer};
+    /// # use actix_web_httpauth::extractors::AuthenticationError;
+    /// let error = AuthenticationError::bearer_or_basic(
+    ///     bearer::Config::default().realm("api"),
+    ///     basic::Config::default().realm("api"),
+    /// );
+    /// ```
+    ///
+    /// [`Dispatch<BearerAuth, BasicAuth>`]: ../dispatch/enum.Dispatch.html
+    pub fn bearer_or_basic(bearer: Config, basic: basic::Config) -> Self {
+        let errors = bearer.errors.clone();
+
+        errors.apply_headless(AuthenticationError::federated(vec![
+            BearerOrBasic::Bearer(bearer.into_inner()),
+            BearerOrBasic::Basic(basic.into_inner()),
+        ]))
+    }
+}
+
+/// Extended error customization for HTTP `Bearer` auth.
+impl AuthenticationError<bearer::Bearer> {
+    /// Creates the error for a valid token which lacks the
+    /// `required_scopes`, as described in
+    /// [RFC 6750](https://tools.ietf.org/html/rfc6750#section-3.1).
+    ///
+    /// Resulting error has the `HTTP 403` status code, the
+    /// `insufficient_scope` error and the challenge `scope` attribute
+    /// set to the space-delimited `required_scopes`. Challenge is sent
+    /// despite the status code, so the clients can tell which scopes
+    /// to request; neither [`Config::omit_challenge_on_401`] nor
+    /// [`ErrorOptions::redirect_navigation`] are applied to it.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web_httpauth::extractors::bearer::Config;
+    /// # use actix_web_httpauth::extractors::AuthenticationError;
+    /// let error = AuthenticationError::insufficient_scope(
+    ///     Config::default().realm("example"),
+    ///     &["email", "profile"],
+    /// );
+    /// ```
+    ///
+    /// [`Config::omit_challenge_on_401`]: ./struct.Config.html#method.omit_challenge_on_401
+    /// [`ErrorOptions::redirect_navigation`]: ../struct.ErrorOptions.html#method.redirect_navigation
+    pub fn insufficient_scope<I>(config: Config, required_scopes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let scope = required_scopes.into_iter().fold(
+            String::new(),
+            |mut scope, value| {
+                if !scope.is_empty() {
+                    scope.push(' ');
+                }
+                scope.push_str(value.as_ref());
+                scope
+            },
+        );
+
+        AuthenticationError::from(config.scope(scope))
+            .with_error(Error::InsufficientScope)
+    }
+
+    fn with_token_endpoint(self, link: &Option<HeaderValue>) -> Self {
+        match link {
+            Some(link) => self.with_unauthorized_header(LINK, link.clone()),
+            None => self,
+        }
+    }
+
+    /// Attach `Error` to the current Authentication error.
+    ///
+    /// Error status code will be changed to the one provided by the `kind`
+    /// Error.
+    pub fn with_error(mut self, kind: Error) -> Self {
+        *self.status_code_mut() = kind.status_code();
+        self.challenge_mut().error = Some(kind);
+        self
+    }
+
+    /// Require the client to retry with the DPoP proof bound to the `nonce`.
+    ///
+    /// Sets the `use_dpop_nonce` error and sends the `nonce`
+    /// in the `DPoP-Nonce` response header.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web::http::header::HeaderValue;
+    /// # use actix_web::ResponseError;
+    /// # use actix_web_httpauth::extractors::bearer::Config;
+    /// # use actix_web_httpauth::extractors::AuthenticationError;
+    /// let error = AuthenticationError::from(Config::default())
+    ///     .with_dpop_nonce(HeaderValue::from_static("eyJ7S_zG.eyJH0-Z.HX4w-7v"));
+    /// let response = error.error_response();
+    ///
+    /// assert_eq!(
+    ///     response.headers().get("DPoP-Nonce").unwrap(),
+    ///     "eyJ7S_zG.eyJH0-Z.HX4w-7v"
+    /// );
+    /// ```
+    pub fn with_dpop_nonce(self, nonce: HeaderValue) -> Self {
+        self.with_error(Error::UseDpopNonce)
+            .with_header(HeaderName::from_static("dpop-nonce"), nonce)
+    }
+
+    /// Attach error description to the current Authentication error.
+    pub fn with_error_description<T>(mut self, desc: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.challenge_mut().error_description = Some(desc.into());
+        self
+    }
+
+    /// Attach error URI to the current Authentication error.
+    ///
+    /// It is up to implementor to provide properly formed absolute URI.
+    pub fn with_error_uri<T>(mut self, uri: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.challenge_mut().error_uri = Some(uri.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use actix_web::http::header::{
+        HeaderValue, ACCESS_CONTROL_ALLOW_ORIGIN, AUTHORIZATION, CACHE_CONTROL,
+        LINK, LOCATION, ORIGIN, SEC_WEBSOCKET_PROTOCOL, VARY, WWW_AUTHENTICATE,
+    };
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+    use actix_web::ResponseError;
+
+    use super::{BearerAuth, Config, Error};
+    use crate::extractors::{
+        AuthExtractor, AuthLimits, AuthenticationError, ErrorOptions,
+    };
+
+    #[test]
+    fn test_header_priority() {
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer main")
+            .header("X-Forwarded-Authorization", "Bearer forwarded")
+            .data(Config::default().header_priority(vec![
+                "X-Forwarded-Authorization",
+                "Authorization",
+            ]))
+            .to_srv_request();
+
+        let auth = BearerAuth::from_service_request(&req).unwrap();
+        assert_eq!(auth.token(), "forwarded");
+    }
+
+    #[test]
+    fn test_split_token() {
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer user.svc")
+            .data(Config::default().split_token('.'))
+            .to_srv_request();
+
+        let auth = BearerAuth::from_service_request(&req).unwrap();
+        assert_eq!(auth.token(), "user.svc");
+        assert_eq!(auth.parts(), ["user", "svc"]);
+    }
+
+    #[test]
+    fn test_not_split_by_default() {
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer user.svc")
+            .to_srv_request();
+
+        let auth = BearerAuth::from_service_request(&req).unwrap();
+        assert_eq!(auth.token(), "user.svc");
+        assert!(auth.parts().is_empty());
+    }
+
+    #[test]
+    fn test_trim_quotes() {
+        let extract = |value: &str, trim_quotes: bool| {
+            let req = TestRequest::with_header(AUTHORIZATION, value)
+                .data(Config::default().trim_quotes(trim_quotes))
+                .to_srv_request();
+
+            BearerAuth::from_service_request(&req)
+                .map(|auth| auth.token().to_string())
+                .ok()
+        };
+
+        assert_eq!(extract("Bearer \"token\"", true).unwrap(), "token");
+        assert_eq!(extract("Bearer \"token\"", false).unwrap(), "\"token\"");
+        assert_eq!(extract("Bearer token", true).unwrap(), "token");
+        assert_eq!(extract("Bearer \"token", true).unwrap(), "\"token");
+        assert_eq!(extract("Bearer \"", true).unwrap(), "\"");
+        assert!(extract("Bearer \"\"", true).is_none());
+    }
+
+    #[test]
+    fn test_cache_control() {
+        let cache_control = |config: Config| {
+            let req = TestRequest::default().data(config).to_srv_request();
+            let error = BearerAuth::from_service_request(&req).unwrap_err();
+
+            error.error_response().headers().get(CACHE_CONTROL).cloned()
+        };
+
+        assert_eq!(cache_control(Config::default()).unwrap(), "no-store");
+        assert_eq!(
+            cache_control(Config::default().error_options(
+                ErrorOptions::default().cache_control(Some("private"))
+            ))
+            .unwrap(),
+            "private"
+        );
+        assert!(cache_control(
+            Config::default()
+                .error_options(ErrorOptions::default().cache_control(None))
+        )
+        .is_none());
+
+        let error = AuthenticationError::from(Config::default())
+            .with_header(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        let response = error.error_response();
+        let values = response.headers().get_all(CACHE_CONTROL);
+        assert_eq!(values.collect::<Vec<_>>(), vec!["no-cache"]);
+    }
+
+    #[test]
+    fn test_query_token() {
+        let uri = "/resource?access_token=mF_9.B5f-4.1JqM&item=1";
+        let req = TestRequest::with_uri(uri)
+            .data(Config::default().allow_query_token(true))
+            .to_srv_request();
+        let auth = BearerAuth::from_service_request(&req).unwrap();
+        assert_eq!(auth.token(), "mF_9.B5f-4.1JqM");
+
+        let req = TestRequest::with_uri(uri)
+            .header(AUTHORIZATION, "Bearer header")
+            .data(Config::default().allow_query_token(true))
+            .to_srv_request();
+        let auth = BearerAuth::from_service_request(&req).unwrap();
+        assert_eq!(auth.token(), "header");
+
+        let req = TestRequest::with_uri(uri).to_srv_request();
+        assert!(BearerAuth::from_service_request(&req).is_err());
+    }
+
+    #[test]
+    fn test_subprotocol_token() {
+        let config = Config::default().subprotocol_token("access_token.");
+        let req = TestRequest::with_header(
+            SEC_WEBSOCKET_PROTOCOL,
+            "graphql-ws, access_token.mF_9.B5f-4",
+        )
+        .data(config.clone())
+        .to_srv_request();
+        let auth = BearerAuth::from_service_request(&req).unwrap();
+        assert_eq!(auth.token(), "mF_9.B5f-4");
+
+        // `Authorization` header takes precedence
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer header")
+            .header(SEC_WEBSOCKET_PROTOCOL, "access_token.subprotocol")
+            .data(config.clone())
+            .to_srv_request();
+        let auth = BearerAuth::from_service_request(&req).unwrap();
+        assert_eq!(auth.token(), "header");
+
+        for protocols in &["graphql-ws", "graphql-ws, access_token."] {
+            let req =
+                TestRequest::with_header(SEC_WEBSOCKET_PROTOCOL, *protocols)
+                    .data(config.clone())
+                    .to_srv_request();
+            assert!(BearerAuth::from_service_request(&req).is_err());
+        }
+
+        let req =
+            TestRequest::with_header(SEC_WEBSOCKET_PROTOCOL, "access_token.t")
+                .to_srv_request();
+        assert!(BearerAuth::from_service_request(&req).is_err());
+    }
+
+    #[test]
+    fn test_token_endpoint() {
+        let config =
+            Config::default().token_endpoint("https://auth.example/token");
+        let req = TestRequest::default().data(config.clone()).to_srv_request();
+
+        let response = BearerAuth::from_service_request(&req)
+            .unwrap_err()
+            .error_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(LINK).unwrap(),
+            "<https://auth.example/token>; rel=\"oauth2-token-endpoint\""
+        );
+        assert!(response.headers().contains_key(WWW_AUTHENTICATE));
+
+        let error = AuthenticationError::insufficient_scope(config, &["email"]);
+        assert!(!error.error_response().headers().contains_key(LINK));
+    }
+
+    #[test]
+    fn test_error_observer() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let observed = reports.clone();
+        let config = Config::default().error_options(
+            ErrorOptions::default().error_observer(
+                move |scheme, error, status| {
+                    observed.lock().unwrap().push((scheme, error, status));
+                },
+            ),
+        );
+
+        let req = TestRequest::default().data(config.clone()).to_srv_request();
+        let error = BearerAuth::from_service_request(&req).unwrap_err();
+        error.error_response();
+        AuthenticationError::insufficient_scope(config, &["email"])
+            .error_response();
+
+        assert_eq!(
+            *reports.lock().unwrap(),
+            vec![
+                ("Bearer", None, StatusCode::UNAUTHORIZED),
+                ("Bearer", Some("insufficient_scope"), StatusCode::FORBIDDEN),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_challenge_interceptor() {
+        let config = Config::default().realm("api").error_options(
+            ErrorOptions::default().challenge_interceptor(Arc::new(|params| {
+                params.remove("realm");
+                params.set("notice", "Authorized use only");
+            })),
+        );
+
+        let req = TestRequest::default().data(config).to_srv_request();
+        let response = BearerAuth::from_service_request(&req)
+            .unwrap_err()
+            .error_response();
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer notice=\"Authorized use only\""
+        );
+    }
+
+    #[test]
+    fn test_revocation_check() {
+        let check = Arc::new(|token: &str| token == "revoked");
+        let config = Config::default().revocation_check(check);
+
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer revoked")
+            .data(config.clone())
+            .to_srv_request();
+        let response = BearerAuth::from_service_request(&req)
+            .unwrap_err()
+            .error_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer error=\"invalid_token\", \
+             error_description=\"token revoked\""
+        );
+
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer valid")
+            .data(config)
+            .to_srv_request();
+        assert!(BearerAuth::from_service_request(&req).is_ok());
+    }
+
+    #[test]
+    fn test_strict_scheme() {
+        let req = TestRequest::with_header(AUTHORIZATION, "Weird xyz")
+            .data(Config::default().strict_scheme(true))
+            .to_srv_request();
+        let response = BearerAuth::from_service_request(&req)
+            .unwrap_err()
+            .error_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer error_description=\"unsupported authentication scheme\""
+        );
+
+        let config = Config::default()
+            .realm("api")
+            .strict_scheme(true)
+            .supported_schemes(vec!["Basic realm=\"api\"", "Token"])
+            .unsupported_scheme_error(true);
+        let req = TestRequest::with_header(AUTHORIZATION, "Weird xyz")
+            .data(config.clone())
+            .to_srv_request();
+        let response = BearerAuth::from_service_request(&req)
+            .unwrap_err()
+            .error_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let challenges = response
+            .headers()
+            .get_all(WWW_AUTHENTICATE)
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            challenges,
+            vec![
+                "Bearer realm=\"api\", error=\"unsupported_scheme\", \
+                 error_description=\"unsupported authentication scheme\"",
+                "Basic realm=\"api\"",
+                "Token",
+            ]
+        );
+
+        // Missing credentials are not the unsupported scheme ones
+        let req = TestRequest::default().data(config).to_srv_request();
+        let response = BearerAuth::from_service_request(&req)
+            .unwrap_err()
+            .error_response();
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer realm=\"api\""
+        );
+
+        let req = TestRequest::with_header(AUTHORIZATION, "Weird xyz")
+            .to_srv_request();
+        let response = BearerAuth::from_service_request(&req)
+            .unwrap_err()
+            .error_response();
+        assert_eq!(response.headers().get(WWW_AUTHENTICATE).unwrap(), "Bearer");
+    }
+
+    #[test]
+    fn test_redirect_navigation() {
+        let config = Config::default().error_options(
+            ErrorOptions::default().redirect_navigation("/login?next=%2F"),
+        );
+
+        let req = TestRequest::with_header("Sec-Fetch-Mode", "navigate")
+            .data(config.clone())
+            .to_srv_request();
+        let response = BearerAuth::from_service_request(&req)
+            .unwrap_err()
+            .error_response();
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(
+            response.headers().get(LOCATION).unwrap(),
+            "/login?next=%2F"
+        );
+        assert!(response.headers().get(WWW_AUTHENTICATE).is_none());
+        assert_eq!(response.headers().get(VARY).unwrap(), "Sec-Fetch-Mode");
+
+        let req = TestRequest::with_header("Sec-Fetch-Mode", "cors")
+            .data(config)
+            .to_srv_request();
+        let response = BearerAuth::from_service_request(&req)
+            .unwrap_err()
+            .error_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().get(LOCATION).is_none());
+        assert_eq!(response.headers().get(WWW_AUTHENTICATE).unwrap(), "Bearer");
+    }
+
+    #[test]
+    fn test_cors_headers_fn() {
+        let config = Config::default().error_options(
+            ErrorOptions::default().cors_headers_fn(|head| {
+                let origin = head.headers().get(ORIGIN).cloned();
+                origin
+                    .map(|origin| vec![(ACCESS_CONTROL_ALLOW_ORIGIN, origin)])
+                    .unwrap_or_default()
+            }),
+        );
+        let req = TestRequest::with_header(ORIGIN, "https://example.com")
+            .data(config)
+            .to_srv_request();
+
+        let response = BearerAuth::from_service_request(&req)
+            .unwrap_err()
+            .error_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "https://example.com"
+        );
+    }
+
+    #[test]
+    fn test_omit_challenge_on_401() {
+        let config = Config::default()
+            .realm("example")
+            .omit_challenge_on_401(true);
+        let req = TestRequest::default().data(config.clone()).to_srv_request();
+
+        let response = BearerAuth::from_service_request(&req)
+            .unwrap_err()
+            .error_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(!response.headers().contains_key(WWW_AUTHENTICATE));
+
+        let response =
+            AuthenticationError::from(config.clone()).error_response();
+        assert!(!response.headers().contains_key(WWW_AUTHENTICATE));
+
+        let error =
+            AuthenticationError::insufficient_scope(config, vec!["email"]);
+        assert!(error
+            .error_response()
+            .headers()
+            .contains_key(WWW_AUTHENTICATE));
+    }
+
+    #[test]
+    fn test_insufficient_scope() {
+        let error = AuthenticationError::insufficient_scope(
+            Config::default().realm("example"),
+            vec!["email", "profile"],
+        );
+        let response = error.error_response();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer realm=\"example\", scope=\"email profile\", \
+             error=\"insufficient_scope\""
+        );
+    }
+
+    #[test]
+    fn test_dpop_errors() {
+        let error = AuthenticationError::from(Config::default())
+            .with_error(Error::InvalidDpopProof);
+        let response = error.error_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer error=\"invalid_dpop_proof\""
+        );
+
+        let error = AuthenticationError::from(Config::default())
+            .with_dpop_nonce(HeaderValue::from_static("n-0S6_WzA2Mj"));
+        let response = error.error_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer error=\"use_dpop_nonce\""
+        );
+        assert_eq!(
+            response.headers().get("DPoP-Nonce").unwrap(),
+            "n-0S6_WzA2Mj"
+        );
+    }
+
+    #[test]
+    fn test_max_header_bytes() {
+        let req =
+            TestRequest::with_header(AUTHORIZATION, "Bearer mF_9.B5f-4.1JqM")
+                .data(Config::default().error_options(
+                    ErrorOptions::default().max_header_bytes(16),
+                ))
+                .to_srv_request();
+
+        let error = BearerAuth::from_service_request(&req).unwrap_err();
+        assert_eq!(
+            error.error_response().status(),
+            StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        let config = Config::default()
+            .header_priority(vec!["X-Forwarded-Authorization", "Authorization"])
+            .error_options(ErrorOptions::default().limits(AuthLimits {
+                max_headers: 2,
+                max_total_bytes: 32,
+            }));
+
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer main")
+            .header("X-Forwarded-Authorization", "Bearer forwarded")
+            .data(config.clone())
+            .to_srv_request();
+        assert!(BearerAuth::from_service_request(&req).is_ok());
+
+        let too_many = TestRequest::with_header(AUTHORIZATION, "Bearer a")
+            .header(AUTHORIZATION, "Bearer b")
+            .header("X-Forwarded-Authorization", "Bearer c")
+            .data(config.clone())
+            .to_srv_request();
+        let too_long = TestRequest::with_header(AUTHORIZATION, "Bearer main")
+            .header("X-Forwarded-Authorization", "Bearer mF_9.B5f-4.1JqM")
+            .data(config)
+            .to_srv_request();
+        for req in &[too_many, too_long] {
+            let error = BearerAuth::from_service_request(req).unwrap_err();
+            assert_eq!(
+                error.error_response().status(),
+                StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+            );
+        }
+    }
+
+    #[test]
+    fn test_malformed_header() {
+        let req = TestRequest::with_header(AUTHORIZATION, "Basic main")
+            .header("X-Forwarded-Authorization", "Bearer forwarded")
+            .data(Config::default().header_priority(vec![
+                "Authorization",
+                "X-Forwarded-Authorization",
+            ]))
+            .to_srv_request();
+
+        assert!(BearerAuth::from_service_request(&req).is_err());
+    }
+}
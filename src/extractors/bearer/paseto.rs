@@ -0,0 +1,439 @@
+use actix_web::dev::{Payload, RequestHead, ServiceRequest};
+use actix_web::{FromRequest, HttpRequest};
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::de::DeserializeOwned;
+
+use super::{BearerAuth, Config, Error};
+use crate::extractors::config::AuthExtractorConfig;
+use crate::extractors::{AuthExtractor, AuthenticationError};
+use crate::headers::www_authenticate::bearer;
+
+/// Header of the supported tokens, only the `v4.public` ones
+/// are signed with the Ed25519 keys.
+const HEADER: &str = "v4.public.";
+
+/// [`PasetoAuth`] extractor configuration, created with the public key.
+///
+/// Default config, also used when none is registered in the app data,
+/// has no key to verify the tokens with: such server misconfiguration
+/// is answered with the bare `HTTP 500` response.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::App;
+/// # use actix_web_httpauth::extractors::bearer::{Config, PasetoConfig};
+/// let public_key = [
+///     0x1e, 0xb9, 0xdb, 0xbb, 0xbc, 0x04, 0x7c, 0x03, 0xfd, 0x70, 0x60, 0x4e,
+///     0x00, 0x71, 0xf0, 0x98, 0x7e, 0x16, 0xb2, 0x8b, 0x75, 0x72, 0x25, 0xc1,
+///     0x1f, 0x00, 0x41, 0x5d, 0x0e, 0x20, 0xb1, 0xa2,
+/// ];
+/// let app = App::new().data(
+///     PasetoConfig::new(public_key).bearer(Config::default().realm("api")),
+/// );
+/// ```
+///
+/// [`PasetoAuth`]: ./struct.PasetoAuth.html
+#[derive(Debug, Clone, Default)]
+pub struct PasetoConfig {
+    bearer: Config,
+    key: Option<VerifyingKey>,
+    implicit_assertion: Vec<u8>,
+}
+
+impl PasetoConfig {
+    /// Creates new config, verifying the token signatures
+    /// with the Ed25519 `public_key`.
+    ///
+    /// ## Panics
+    ///
+    /// If `public_key` is not a valid Ed25519 public key.
+    pub fn new(public_key: [u8; 32]) -> PasetoConfig {
+        let key = VerifyingKey::from_bytes(&public_key)
+            .unwrap_or_else(|_| panic!("Invalid Ed25519 public key"));
+
+        PasetoConfig {
+            key: Some(key),
+            ..PasetoConfig::default()
+        }
+    }
+
+    /// Set the implicit assertion, which is signed along with the token
+    /// but is not sent by the client, ex. the audience. Empty by default.
+    pub fn implicit_assertion<T: Into<Vec<u8>>>(
+        mut self,
+        value: T,
+    ) -> PasetoConfig {
+        self.implicit_assertion = value.into();
+        self
+    }
+
+    /// Set the config used to read the token and to form the challenge.
+    pub fn bearer(mut self, config: Config) -> PasetoConfig {
+        self.bearer = config;
+        self
+    }
+}
+
+impl AsRef<bearer::Bearer> for PasetoConfig {
+    fn as_ref(&self) -> &bearer::Bearer {
+        self.bearer.as_ref()
+    }
+}
+
+impl AuthExtractorConfig for PasetoConfig {
+    type Inner = bearer::Bearer;
+
+    fn into_inner(self) -> Self::Inner {
+        self.bearer.into_inner()
+    }
+
+    fn into_error(self) -> AuthenticationError<Self::Inner> {
+        self.bearer.into_error()
+    }
+}
+
+/// Extractor for the [PASETO] `v4.public` bearer tokens, signed with
+/// the Ed25519 key configured by the [`PasetoConfig`].
+///
+/// Token is read in the same way as by the [`BearerAuth`] extractor, and
+/// the JSON payload is deserialized into `T` once the signature has been
+/// verified. Malformed tokens, the other versions and purposes, and the
+/// invalid signatures are rejected with the `invalid_token` error.
+///
+/// Payload claims, ex. `exp`, are not checked by the extractor.
+///
+/// ## Example
+///
+/// ```rust
+/// # use serde::Deserialize;
+/// use actix_web_httpauth::extractors::bearer::PasetoAuth;
+///
+/// #[derive(Deserialize)]
+/// struct Claims {
+///     sub: String,
+/// }
+///
+/// fn index(auth: PasetoAuth<Claims>) -> String {
+///     format!("Hello, {}!", auth.payload().sub)
+/// }
+/// ```
+///
+/// [PASETO]: https://github.com/paseto-standard/paseto-spec
+/// [`PasetoConfig`]: ./struct.PasetoConfig.html
+/// [`BearerAuth`]: ./struct.BearerAuth.html
+#[derive(Debug, Clone)]
+pub struct PasetoAuth<T> {
+    payload: T,
+    footer: Vec<u8>,
+}
+
+impl<T> PasetoAuth<T> {
+    /// Returns the verified token payload.
+    pub fn payload(&self) -> &T {
+        &self.payload
+    }
+
+    /// Consumes the credentials, returning the verified token payload.
+    pub fn into_payload(self) -> T {
+        self.payload
+    }
+
+    /// Returns the token footer, which is empty if there is none.
+    pub fn footer(&self) -> &[u8] {
+        &self.footer
+    }
+}
+
+impl<T: DeserializeOwned> PasetoAuth<T> {
+    /// Error is boxed, like the [`BearerAuth`] one.
+    ///
+    /// [`BearerAuth`]: ./struct.BearerAuth.html
+    fn extract(
+        head: &RequestHead,
+        config: &PasetoConfig,
+    ) -> Result<Self, Box<AuthenticationError<bearer::Bearer>>> {
+        let auth = BearerAuth::extract(head, &config.bearer)?;
+        let key = match config.key {
+            Some(ref key) => key,
+            None => {
+                #[cfg(feature = "tracing")]
+                tracing::error!("PASETO verification key is not configured");
+                let error =
+                    AuthenticationError::misconfigured(config.bearer.clone());
+                return Err(Box::new(error));
+            }
+        };
+
+        PasetoAuth::verify(auth.token(), key, &config.implicit_assertion)
+            .map_err(|description| {
+                let error = config
+                    .bearer
+                    .challenge_error(head)
+                    .with_error(Error::InvalidToken)
+                    .with_error_description(description);
+                Box::new(error)
+            })
+    }
+
+    fn verify(
+        token: &str,
+        key: &VerifyingKey,
+        implicit_assertion: &[u8],
+    ) -> Result<Self, &'static str> {
+        let token = token
+            .strip_prefix(HEADER)
+            .ok_or("unsupported token version")?;
+        let (body, footer) = match token.split_once('.') {
+            Some((body, footer)) => (body, decode(footer)?),
+            None => (token, Vec::new()),
+        };
+        let body = decode(body)?;
+        let message_len = body
+            .len()
+            .checked_sub(Signature::BYTE_SIZE)
+            .ok_or("malformed token")?;
+        let (message, signature) = body.split_at(message_len);
+        let signature =
+            Signature::from_slice(signature).map_err(|_| "malformed token")?;
+
+        let signed =
+            pae(&[HEADER.as_bytes(), message, &footer, implicit_assertion]);
+        key.verify_strict(&signed, &signature)
+            .map_err(|_| "invalid signature")?;
+        let payload =
+            serde_json::from_slice(message).map_err(|_| "invalid payload")?;
+
+        Ok(PasetoAuth {
+            payload,
+            footer,
+        })
+    }
+}
+
+impl<T: DeserializeOwned> FromRequest for PasetoAuth<T> {
+    type Config = PasetoConfig;
+    type Future = Result<Self, Self::Error>;
+    type Error = AuthenticationError<bearer::Bearer>;
+
+    fn from_request(
+        req: &HttpRequest,
+        _payload: &mut Payload,
+    ) -> <Self as FromRequest>::Future {
+        let config =
+            req.app_data::<Self::Config>().cloned().unwrap_or_default();
+
+        PasetoAuth::extract(req.head(), &config).map_err(|error| *error)
+    }
+}
+
+impl<T: DeserializeOwned> AuthExtractor for PasetoAuth<T> {
+    type Future = Result<Self, Self::Error>;
+    type Error = AuthenticationError<bearer::Bearer>;
+
+    fn from_service_request(req: &ServiceRequest) -> Self::Future {
+        let config = req
+            .app_data::<PasetoConfig>()
+            .map(|config| config.get_ref().clone())
+            .unwrap_or_default();
+
+        PasetoAuth::extract(req.head(), &config).map_err(|error| *error)
+    }
+
+    fn uses_form_token() -> bool {
+        true
+    }
+}
+
+/// Decodes the unpadded base64url token `segment`.
+fn decode(segment: &str) -> Result<Vec<u8>, &'static str> {
+    // Padding is not allowed by the specification
+    if segment.contains('=') {
+        return Err("malformed token");
+    }
+
+    base64::decode_config(segment, base64::URL_SAFE_NO_PAD)
+        .map_err(|_| "malformed token")
+}
+
+/// Pre-Authentication Encoding of the `pieces`, which is signed
+/// instead of the token itself.
+fn pae(pieces: &[&[u8]]) -> Vec<u8> {
+    let le64 = |n: usize| (n as u64 & (u64::MAX >> 1)).to_le_bytes();
+
+    let mut output = le64(pieces.len()).to_vec();
+    for piece in pieces {
+        output.extend_from_slice(&le64(piece.len()));
+        output.extend_from_slice(piece);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+    use actix_web::ResponseError;
+    use ed25519_dalek::{Signer, SigningKey};
+    use serde_json::{json, Value};
+
+    use super::{pae, PasetoAuth, PasetoConfig, HEADER};
+    use crate::extractors::bearer::Config;
+    use crate::extractors::AuthExtractor;
+
+    // Keys and tokens of the 4-S-* test vectors
+    const SEED: &str =
+        "b4cbfb43df4ce210727d953e4a713307fa19bb7d9f85041438d9e11b942a3774";
+    const PUBLIC_KEY: &str =
+        "1eb9dbbbbc047c03fd70604e0071f0987e16b28b757225c11f00415d0e20b1a2";
+
+    fn key(hex: &str) -> [u8; 32] {
+        let mut key = [0; 32];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap();
+        }
+        key
+    }
+
+    fn sign(message: &[u8], footer: &[u8]) -> String {
+        let signing_key = SigningKey::from_bytes(&key(SEED));
+        let signature =
+            signing_key.sign(&pae(&[HEADER.as_bytes(), message, footer, b""]));
+        let mut body = message.to_vec();
+        body.extend_from_slice(&signature.to_bytes());
+
+        let mut token = HEADER.to_string()
+            + &base64::encode_config(&body, base64::URL_SAFE_NO_PAD);
+        if !footer.is_empty() {
+            token.push('.');
+            token.push_str(&base64::encode_config(
+                footer,
+                base64::URL_SAFE_NO_PAD,
+            ));
+        }
+        token
+    }
+
+    fn extract(
+        token: &str,
+        config: PasetoConfig,
+    ) -> Result<PasetoAuth<Value>, String> {
+        let req = TestRequest::with_header(
+            AUTHORIZATION,
+            format!("Bearer {}", token),
+        )
+        .data(config)
+        .to_srv_request();
+
+        PasetoAuth::from_service_request(&req).map_err(|error| {
+            let res = error.error_response();
+            let challenge = res.headers().get(WWW_AUTHENTICATE).unwrap();
+            challenge.to_str().unwrap().to_string()
+        })
+    }
+
+    #[test]
+    fn test_pae() {
+        assert_eq!(pae(&[]), b"\x00\x00\x00\x00\x00\x00\x00\x00");
+        assert_eq!(
+            pae(&[b"test"]),
+            b"\x01\x00\x00\x00\x00\x00\x00\x00\x04\x00\x00\x00\x00\x00\x00\x00test"
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn test_vectors() {
+        let config = PasetoConfig::new(key(PUBLIC_KEY));
+        let payload = json!({
+            "data": "this is a signed message",
+            "exp": "2022-01-01T00:00:00+00:00",
+        });
+
+        let auth = extract("v4.public.eyJkYXRhIjoidGhpcyBpcyBhIHNpZ25lZCBtZXNzYWdlIiwiZXhwIjoiMjAyMi0wMS0wMVQwMDowMDowMCswMDowMCJ9bg_XBBzds8lTZShVlwwKSgeKpLT3yukTw6JUz3W4h_ExsQV-P0V54zemZDcAxFaSeef1QlXEFtkqxT1ciiQEDA", config.clone()).unwrap();
+        assert_eq!(auth.payload(), &payload);
+        assert!(auth.footer().is_empty());
+
+        let auth = extract("v4.public.eyJkYXRhIjoidGhpcyBpcyBhIHNpZ25lZCBtZXNzYWdlIiwiZXhwIjoiMjAyMi0wMS0wMVQwMDowMDowMCswMDowMCJ9v3Jt8mx_TdM2ceTGoqwrh4yDFn0XsHvvV_D0DtwQxVrJEBMl0F2caAdgnpKlt4p7xBnx1HcO-SPo8FPp214HDw.eyJraWQiOiJ6VmhNaVBCUDlmUmYyc25FY1Q3Z0ZUaW9lQTlDT2NOeTlEZmdMMVc2MGhhTiJ9", config.clone()).unwrap();
+        assert_eq!(auth.into_payload(), payload);
+
+        let token = "v4.public.eyJkYXRhIjoidGhpcyBpcyBhIHNpZ25lZCBtZXNzYWdlIiwiZXhwIjoiMjAyMi0wMS0wMVQwMDowMDowMCswMDowMCJ9NPWciuD3d0o5eXJXG5pJy-DiVEoyPYWs1YSTwWHNJq6DZD3je5gf-0M4JR9ipdUSJbIovzmBECeaWmaqcaP0DQ.eyJraWQiOiJ6VmhNaVBCUDlmUmYyc25FY1Q3Z0ZUaW9lQTlDT2NOeTlEZmdMMVc2MGhhTiJ9";
+        assert!(extract(token, config.clone()).is_err());
+        let config = config.implicit_assertion(r#"{"test-vector":"4-S-3"}"#);
+        let auth = extract(token, config).unwrap();
+        assert_eq!(
+            auth.footer(),
+            br#"{"kid":"zVhMiPBP9fRf2snEcT7gFTioeA9COcNy9DfgL1W60haN"}"#
+        );
+    }
+
+    #[test]
+    fn test_invalid_token() {
+        let config = PasetoConfig::new(key(PUBLIC_KEY))
+            .bearer(Config::default().realm("api"));
+        let token = sign(br#"{"sub":"alice"}"#, b"kid");
+        assert_eq!(
+            extract(&token, config.clone()).unwrap().payload(),
+            &json!({"sub": "alice"})
+        );
+
+        let body = token.split('.').nth(2).unwrap();
+        let mut message =
+            base64::decode_config(body, base64::URL_SAFE_NO_PAD).unwrap();
+        message[8] ^= 1;
+        let tampered = token.replace(
+            body,
+            &base64::encode_config(&message, base64::URL_SAFE_NO_PAD),
+        );
+        let local = token.replacen("public", "local", 1);
+        let v3 = token.replacen("v4", "v3", 1);
+        let padded = token.clone() + "=";
+        let truncated = &token[..HEADER.len() + 16];
+        let not_json = sign(b"not json", b"");
+        for (token, description) in &[
+            (tampered.as_str(), "invalid signature"),
+            (local.as_str(), "unsupported token version"),
+            (v3.as_str(), "unsupported token version"),
+            (padded.as_str(), "malformed token"),
+            (truncated, "malformed token"),
+            (not_json.as_str(), "invalid payload"),
+        ] {
+            assert_eq!(
+                extract(token, config.clone()).unwrap_err(),
+                format!(
+                    "Bearer realm=\"api\", error=\"invalid_token\", \
+                     error_description=\"{}\"",
+                    description
+                ),
+                "{}",
+                token
+            );
+        }
+
+        // Public key of the RFC 8032 test vector
+        let other_key = key(
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        );
+        let config = PasetoConfig::new(other_key);
+        let error = extract(&token, config).unwrap_err();
+        assert!(error.ends_with("error_description=\"invalid signature\""));
+    }
+
+    #[test]
+    fn test_missing_key() {
+        let token = sign(br#"{"sub":"alice"}"#, b"");
+        let req = TestRequest::with_header(
+            AUTHORIZATION,
+            format!("Bearer {}", token),
+        )
+        .to_srv_request();
+
+        let error =
+            PasetoAuth::<Value>::from_service_request(&req).unwrap_err();
+        let res = error.error_response();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(res.headers().get(WWW_AUTHENTICATE).is_none());
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use actix_web::dev::ServiceRequest;
+use actix_web::{Error, HttpMessage};
+use futures::future::{self, FutureResult};
+
+use super::{BearerAuth, Config};
+use crate::clock::{Clock, SystemClock};
+use crate::extractors::AuthenticationError;
+use crate::headers::www_authenticate::bearer;
+use crate::utils;
+
+/// Storage for the opaque bearer session tokens.
+///
+/// [`InMemorySessionStore`] is suitable for the single-instance
+/// deployments, distributed ones can implement this trait against
+/// the shared storage, ex. Redis.
+///
+/// [`InMemorySessionStore`]: ./struct.InMemorySessionStore.html
+pub trait SessionStore: Send + Sync {
+    /// Issues the new session token for the `subject`, ex. on login.
+    fn issue(&self, subject: &str) -> String;
+
+    /// Returns the session subject if the `token` is valid.
+    fn validate(&self, token: &str) -> Option<String>;
+
+    /// Invalidates the `token`, ex. on logout.
+    fn revoke(&self, token: &str);
+}
+
+/// In-memory [`SessionStore`] implementation,
+/// sessions are valid for the configured `ttl`.
+///
+/// Tokens are 128 bits read from the OS random number generator.
+/// Expired sessions are removed once they are validated, or swept
+/// all at once when the number of the stored sessions has doubled
+/// since the last sweep, so issuing stays amortized O(1).
+///
+/// ## Example
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use actix_web_httpauth::extractors::bearer::{InMemorySessionStore, SessionStore};
+/// let store = InMemorySessionStore::new(Duration::from_secs(3600));
+/// let token = store.issue("Aladdin");
+///
+/// assert_eq!(store.validate(&token).unwrap(), "Aladdin");
+///
+/// store.revoke(&token);
+/// assert!(store.validate(&token).is_none());
+/// ```
+///
+/// [`SessionStore`]: ./trait.SessionStore.html
+pub struct InMemorySessionStore {
+    sessions: Mutex<Sessions>,
+    ttl: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+#[derive(Debug, Default)]
+struct Sessions {
+    map: HashMap<String, (String, SystemTime)>,
+    /// Number of the sessions at which the expired ones are swept.
+    sweep_at: usize,
+}
+
+impl InMemorySessionStore {
+    /// Minimal number of the sessions triggering the sweep.
+    const MIN_SWEEP: usize = 1024;
+
+    /// Creates new store, which sessions are valid for `ttl`.
+    pub fn new(ttl: Duration) -> InMemorySessionStore {
+        InMemorySessionStore {
+            sessions: Mutex::new(Sessions {
+                map: HashMap::new(),
+                sweep_at: Self::MIN_SWEEP,
+            }),
+            ttl,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Set the time source, ex. the [`ManualClock`] for tests.
+    ///
+    /// [`ManualClock`]: ../../clock/struct.ManualClock.html
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> InMemorySessionStore {
+        self.clock = clock;
+        self
+    }
+
+    fn sessions(&self) -> MutexGuard<'_, Sessions> {
+        match self.sessions.lock() {
+            Ok(sessions) => sessions,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl SessionStore for InMemorySessionStore {
+    fn issue(&self, subject: &str) -> String {
+        let now = self.clock.now();
+        let token = utils::random_token();
+
+        let mut sessions = self.sessions();
+        if sessions.map.len() >= sessions.sweep_at {
+            sessions.map.retain(|_, (_, expires)| *expires > now);
+            sessions.sweep_at = (sessions.map.len() * 2).max(Self::MIN_SWEEP);
+        }
+        sessions
+            .map
+            .insert(token.clone(), (subject.to_string(), now + self.ttl));
+
+        token
+    }
+
+    fn validate(&self, token: &str) -> Option<String> {
+        let now = self.clock.now();
+        let mut sessions = self.sessions();
+        match sessions.map.get(token) {
+            Some((subject, expires)) if *expires > now => Some(subject.clone()),
+            Some(_) => {
+                sessions.map.remove(token);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn revoke(&self, token: &str) {
+        self.sessions().map.remove(token);
+    }
+}
+
+impl fmt::Debug for InMemorySessionStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InMemorySessionStore")
+            .field("ttl", &self.ttl)
+            .field("clock", &self.clock)
+            .finish()
+    }
+}
+
+/// Subject of the validated session,
+/// stored in the request extensions by the [`SessionValidator`].
+///
+/// [`SessionValidator`]: ./struct.SessionValidator.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSubject(pub String);
+
+/// Ready-made validator for the [`HttpAuthentication::bearer`] middleware,
+/// which checks the tokens with the [`SessionStore`].
+///
+/// Unknown or expired tokens are rejected with the `invalid_token` error,
+/// challenge is formed from the [`Config`] instance fetched from the app data.
+///
+/// ## Example
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// # use actix_web::App;
+/// # use actix_web_httpauth::extractors::bearer::{InMemorySessionStore, SessionValidator};
+/// # use actix_web_httpauth::middleware::HttpAuthentication;
+/// let store = Arc::new(InMemorySessionStore::new(Duration::from_secs(3600)));
+/// let validator = SessionValidator::new(store.clone());
+///
+/// let app = App::new().wrap(HttpAuthentication::bearer(move |req, credentials| {
+///     validator.validate(req, credentials)
+/// }));
+/// ```
+///
+/// [`HttpAuthentication::bearer`]: ../../middleware/struct.HttpAuthentication.html#method.bearer
+/// [`SessionStore`]: ./trait.SessionStore.html
+/// [`Config`]: ./struct.Config.html
+#[derive(Clone)]
+pub struct SessionValidator {
+    store: Arc<dyn SessionStore>,
+}
+
+impl SessionValidator {
+    /// Creates new validator backed by the `store`.
+    pub fn new(store: Arc<dyn SessionStore>) -> SessionValidator {
+        SessionValidator {
+            store,
+        }
+    }
+
+    /// Validates the `credentials`, suitable to be called
+    /// from the [`HttpAuthentication::bearer`] validation callback.
+    ///
+    /// [`HttpAuthentication::bearer`]: ../../middleware/struct.HttpAuthentication.html#method.bearer
+    pub fn validate(
+        &self,
+        req: ServiceRequest,
+        credentials: BearerAuth,
+    ) -> FutureResult<ServiceRequest, Error> {
+        match self.store.validate(credentials.token()) {
+            Some(subject) => {
+                req.extensions_mut().insert(SessionSubject(subject));
+                future::ok(req)
+            }
+            None => {
+                let config = req
+                    .app_data::<Config>()
+                    .map(|config| config.get_ref().clone())
+                    .unwrap_or_default();
+                future::err(
+                    AuthenticationError::from(config)
+                        .with_error(bearer::Error::InvalidToken)
+                        .into(),
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Debug for SessionValidator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SessionValidator")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use actix_web::test::TestRequest;
+    use actix_web::HttpMessage;
+    use futures::Future;
+
+    use super::{
+        InMemorySessionStore, SessionStore, SessionSubject, SessionValidator,
+    };
+    use crate::clock::ManualClock;
+    use crate::extractors::bearer::BearerAuth;
+
+    #[test]
+    fn test_session_expiration() {
+        let clock = ManualClock::new(UNIX_EPOCH);
+        let store = InMemorySessionStore::new(Duration::from_secs(60))
+            .clock(Arc::new(clock.clone()));
+        let token = store.issue("Aladdin");
+
+        assert_ne!(token, store.issue("Aladdin"));
+        assert_eq!(store.validate(&token).unwrap(), "Aladdin");
+
+        clock.advance(Duration::from_secs(60));
+        assert!(store.validate(&token).is_none());
+    }
+
+    #[test]
+    fn test_sweep() {
+        let clock = ManualClock::new(UNIX_EPOCH);
+        let store = InMemorySessionStore::new(Duration::from_secs(60))
+            .clock(Arc::new(clock.clone()));
+        for _ in 0..InMemorySessionStore::MIN_SWEEP {
+            store.issue("Aladdin");
+        }
+        clock.advance(Duration::from_secs(60));
+
+        let token = store.issue("Aladdin");
+        assert_eq!(store.sessions().map.len(), 1);
+        assert_eq!(store.sessions().sweep_at, InMemorySessionStore::MIN_SWEEP);
+        assert_eq!(store.validate(&token).unwrap(), "Aladdin");
+    }
+
+    #[test]
+    fn test_validator() {
+        let store =
+            Arc::new(InMemorySessionStore::new(Duration::from_secs(60)));
+        let validator = SessionValidator::new(store.clone());
+        let token = store.issue("Aladdin");
+
+        let req = TestRequest::default().to_srv_request();
+        let req = validator
+            .validate(req, BearerAuth::new(token.clone()))
+            .wait()
+            .unwrap();
+        assert_eq!(
+            req.extensions().get::<SessionSubject>().unwrap().0,
+            "Aladdin"
+        );
+
+        store.revoke(&token);
+        let req = TestRequest::default().to_srv_request();
+        assert!(validator
+            .validate(req, BearerAuth::new(token))
+            .wait()
+            .is_err());
+    }
+}
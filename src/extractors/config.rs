@@ -11,6 +11,17 @@ pub trait AuthExtractorConfig {
 
     /// Convert the config instance into a HTTP challenge.
     fn into_inner(self) -> Self::Inner;
+
+    /// Convert the config instance into the authentication error.
+    ///
+    /// Default implementation creates the error from the challenge
+    /// returned by [`into_inner`](#tymethod.into_inner).
+    fn into_error(self) -> AuthenticationError<Self::Inner>
+    where
+        Self: Sized,
+    {
+        AuthenticationError::new(self.into_inner())
+    }
 }
 
 impl<T> From<T> for AuthenticationError<<T as AuthExtractorConfig>::Inner>
@@ -18,6 +29,6 @@ where
     T: AuthExtractorConfig,
 {
     fn from(config: T) -> Self {
-        AuthenticationError::new(config.into_inner())
+        config.into_error()
     }
 }
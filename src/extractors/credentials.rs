@@ -0,0 +1,430 @@
+//! Extractor which yields any of the supported credentials
+
+use std::fmt;
+
+use actix_web::dev::{Payload, ServiceRequest};
+use actix_web::http::header::WWW_AUTHENTICATE;
+use actix_web::{Error, FromRequest, HttpRequest, HttpResponse, ResponseError};
+
+use super::basic::{self, BasicAuth};
+use super::bearer::{self, BearerAuth};
+use super::hmac::HmacAuth;
+use super::timestamp_key::TimestampKeyAuth;
+use super::token::{self, TokenAuth};
+use super::{AssuranceLevel, AuthExtractor};
+use crate::utils;
+
+/// Authentication scheme tried by the [`Credentials`] extractor.
+///
+/// [`Credentials`]: ./enum.Credentials.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CredentialScheme {
+    /// "Basic" credentials, see [`BasicAuth`](../basic/struct.BasicAuth.html).
+    Basic,
+    /// "Bearer" token, see [`BearerAuth`](../bearer/struct.BearerAuth.html).
+    Bearer,
+    /// "Token" API key, see [`TokenAuth`](../token/struct.TokenAuth.html).
+    Token,
+    /// "Signature" HMAC-signed request,
+    /// see [`HmacAuth`](../hmac/struct.HmacAuth.html).
+    Signature,
+    /// "TSKey" signed timestamp, see [`TimestampKeyAuth`].
+    ///
+    /// [`TimestampKeyAuth`]: ../timestamp_key/struct.TimestampKeyAuth.html
+    TimestampKey,
+}
+
+impl CredentialScheme {
+    fn extract_service_request(
+        self,
+        req: &ServiceRequest,
+    ) -> Result<Credentials, Error> {
+        match self {
+            CredentialScheme::Basic => BasicAuth::from_service_request(req)
+                .map(Credentials::Basic)
+                .map_err(Into::into),
+            CredentialScheme::Bearer => BearerAuth::from_service_request(req)
+                .map(Credentials::Bearer)
+                .map_err(Into::into),
+            CredentialScheme::Token => TokenAuth::from_service_request(req)
+                .map(Credentials::Token)
+                .map_err(Into::into),
+            CredentialScheme::Signature => HmacAuth::from_service_request(req)
+                .map(Credentials::Signature)
+                .map_err(Into::into),
+            CredentialScheme::TimestampKey => {
+                TimestampKeyAuth::from_service_request(req)
+                    .map(Credentials::TimestampKey)
+                    .map_err(Into::into)
+            }
+        }
+    }
+
+    fn extract_request(
+        self,
+        req: &HttpRequest,
+        payload: &mut Payload,
+    ) -> Result<Credentials, Error> {
+        match self {
+            CredentialScheme::Basic => BasicAuth::from_request(req, payload)
+                .map(Credentials::Basic)
+                .map_err(Into::into),
+            CredentialScheme::Bearer => BearerAuth::from_request(req, payload)
+                .map(Credentials::Bearer)
+                .map_err(Into::into),
+            CredentialScheme::Token => TokenAuth::from_request(req, payload)
+                .map(Credentials::Token)
+                .map_err(Into::into),
+            CredentialScheme::Signature => HmacAuth::from_request(req, payload)
+                .map(Credentials::Signature)
+                .map_err(Into::into),
+            CredentialScheme::TimestampKey => {
+                TimestampKeyAuth::from_request(req, payload)
+                    .map(Credentials::TimestampKey)
+                    .map_err(Into::into)
+            }
+        }
+    }
+}
+
+/// Scheme with the extractor config provided explicitly,
+/// instead of the app data one, see [`AuthBuilder`].
+///
+/// [`AuthBuilder`]: ../../middleware/struct.AuthBuilder.html
+#[derive(Debug, Clone)]
+pub(crate) enum ConfiguredScheme {
+    Basic(basic::Config),
+    Bearer(bearer::Config),
+    Token(token::Config),
+}
+
+impl ConfiguredScheme {
+    pub(crate) fn scheme(&self) -> CredentialScheme {
+        match self {
+            ConfiguredScheme::Basic(_) => CredentialScheme::Basic,
+            ConfiguredScheme::Bearer(_) => CredentialScheme::Bearer,
+            ConfiguredScheme::Token(_) => CredentialScheme::Token,
+        }
+    }
+
+    fn extract(&self, req: &ServiceRequest) -> Result<Credentials, Error> {
+        match self {
+            ConfiguredScheme::Basic(config) => {
+                BasicAuth::extract(req.head(), req.app_config(), config)
+                    .map(Credentials::Basic)
+                    .map_err(Into::into)
+            }
+            ConfiguredScheme::Bearer(config) => {
+                BearerAuth::extract(req.head(), config)
+                    .map(Credentials::Bearer)
+                    .map_err(|error| (*error).into())
+            }
+            ConfiguredScheme::Token(config) => {
+                TokenAuth::extract(req, Some(config))
+                    .map(Credentials::Token)
+                    .map_err(Into::into)
+            }
+        }
+    }
+}
+
+/// Returns the first credentials extracted with the `schemes`, in order,
+/// or the error combining the challenges of all of them.
+pub(crate) fn extract_configured(
+    req: &ServiceRequest,
+    schemes: &[ConfiguredScheme],
+) -> Result<Credentials, Error> {
+    first_of(schemes, |scheme| scheme.extract(req))
+}
+
+/// [`Credentials`] extractor configuration.
+///
+/// By default the "Basic" and "Bearer" schemes are tried.
+///
+/// [`Credentials`]: ./enum.Credentials.html
+#[derive(Debug, Clone)]
+pub struct Config {
+    schemes: Vec<CredentialScheme>,
+}
+
+impl Config {
+    /// Set the schemes to try, in the provided order.
+    ///
+    /// ## Panics
+    ///
+    /// If `schemes` is empty.
+    pub fn schemes<I>(mut self, schemes: I) -> Config
+    where
+        I: IntoIterator<Item = CredentialScheme>,
+    {
+        self.schemes = schemes.into_iter().collect();
+        assert!(!self.schemes.is_empty(), "At least one scheme is required");
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            schemes: vec![CredentialScheme::Basic, CredentialScheme::Bearer],
+        }
+    }
+}
+
+/// Extractor for whatever credentials the request carries, tried with
+/// the schemes listed in the [`Config`], ex. for the endpoints accepting
+/// several authentication methods.
+///
+/// Schemes are tried in order and the first extracted credentials are
+/// returned. Each scheme uses its own extractor config, ex. the
+/// [`basic::Config`]; when all of them fail, the error response contains
+/// challenges for all of the tried schemes.
+///
+/// Unlike the [`Dispatch`] extractor it is not generic, so handlers
+/// have a single type to match on.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::{web, App};
+/// # use actix_web_httpauth::extractors::credentials::{Config, CredentialScheme};
+/// # use actix_web_httpauth::extractors::Credentials;
+/// fn index(credentials: Credentials) -> String {
+///     match credentials {
+///         Credentials::Basic(basic) => format!("Hello, {}!", basic.user_id()),
+///         Credentials::Token(_token) => "Hello, API client!".to_string(),
+///         _ => "Hello!".to_string(),
+///     }
+/// }
+///
+/// fn main() {
+///     let app = App::new().service(
+///         web::resource("/index.html")
+///             .data(Config::default().schemes(vec![
+///                 CredentialScheme::Basic,
+///                 CredentialScheme::Token,
+///             ]))
+///             .route(web::get().to(index)),
+///     );
+/// }
+/// ```
+///
+/// [`Config`]: ./struct.Config.html
+/// [`basic::Config`]: ../basic/struct.Config.html
+/// [`Dispatch`]: ../dispatch/enum.Dispatch.html
+#[derive(Debug, Clone)]
+pub enum Credentials {
+    /// "Basic" credentials.
+    Basic(BasicAuth),
+    /// "Bearer" token.
+    Bearer(BearerAuth),
+    /// "Token" API key.
+    Token(TokenAuth),
+    /// "Signature" HMAC-signed request.
+    Signature(HmacAuth),
+    /// "TSKey" signed timestamp.
+    TimestampKey(TimestampKeyAuth),
+}
+
+impl Credentials {
+    /// Returns the scheme the credentials were extracted with.
+    pub fn scheme(&self) -> CredentialScheme {
+        match self {
+            Credentials::Basic(_) => CredentialScheme::Basic,
+            Credentials::Bearer(_) => CredentialScheme::Bearer,
+            Credentials::Token(_) => CredentialScheme::Token,
+            Credentials::Signature(_) => CredentialScheme::Signature,
+            Credentials::TimestampKey(_) => CredentialScheme::TimestampKey,
+        }
+    }
+}
+
+/// Returns the first credentials extracted by `extract`,
+/// or the error combining the challenges of all `schemes`.
+fn first_of<'a, S, F>(
+    schemes: &'a [S],
+    mut extract: F,
+) -> Result<Credentials, Error>
+where
+    F: FnMut(&'a S) -> Result<Credentials, Error>,
+{
+    let mut errors = Vec::with_capacity(schemes.len());
+    for scheme in schemes {
+        match extract(scheme) {
+            Ok(credentials) => return Ok(credentials),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    Err(CredentialsError(errors).into())
+}
+
+impl FromRequest for Credentials {
+    type Config = Config;
+    type Future = Result<Self, Self::Error>;
+    type Error = Error;
+
+    fn from_request(
+        req: &HttpRequest,
+        payload: &mut Payload,
+    ) -> <Self as FromRequest>::Future {
+        let default;
+        let config = match req.app_data::<Config>() {
+            Some(config) => config,
+            None => {
+                default = Config::default();
+                &default
+            }
+        };
+
+        first_of(&config.schemes, |scheme| {
+            scheme.extract_request(req, payload)
+        })
+    }
+}
+
+impl AuthExtractor for Credentials {
+    type Error = Error;
+    type Future = Result<Self, Self::Error>;
+
+    fn from_service_request(req: &ServiceRequest) -> Self::Future {
+        let schemes = match req.app_data::<Config>() {
+            Some(config) => config.schemes.clone(),
+            None => Config::default().schemes,
+        };
+
+        first_of(&schemes, |scheme| scheme.extract_service_request(req))
+    }
+
+    fn subject(&self) -> Option<&str> {
+        match self {
+            Credentials::Basic(credentials) => credentials.subject(),
+            Credentials::Bearer(credentials) => credentials.subject(),
+            Credentials::Token(credentials) => credentials.subject(),
+            Credentials::Signature(credentials) => credentials.subject(),
+            Credentials::TimestampKey(credentials) => credentials.subject(),
+        }
+    }
+
+    fn uses_form_login() -> bool {
+        true
+    }
+
+    fn uses_form_token() -> bool {
+        true
+    }
+}
+
+impl AssuranceLevel for Credentials {
+    fn assurance_level(&self) -> u8 {
+        match self {
+            Credentials::Basic(credentials) => credentials.assurance_level(),
+            Credentials::Bearer(credentials) => credentials.assurance_level(),
+            Credentials::Token(credentials) => credentials.assurance_level(),
+            Credentials::Signature(credentials) => {
+                credentials.assurance_level()
+            }
+            Credentials::TimestampKey(credentials) => {
+                credentials.assurance_level()
+            }
+        }
+    }
+}
+
+/// All schemes have failed, response is rendered by the first error
+/// and extended with the `WWW-Authenticate` headers of the other ones.
+#[derive(Debug)]
+struct CredentialsError(Vec<Error>);
+
+impl fmt::Display for CredentialsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0[0], f)
+    }
+}
+
+impl ResponseError for CredentialsError {
+    fn error_response(&self) -> HttpResponse {
+        let mut errors = self.0.iter();
+        let mut response = match errors.next() {
+            Some(error) => error.as_response_error().error_response(),
+            None => HttpResponse::Unauthorized().finish(),
+        };
+        for error in errors {
+            let challenges = error.as_response_error().error_response();
+            utils::append_header_values(
+                response.headers_mut(),
+                WWW_AUTHENTICATE,
+                challenges.headers().get_all(WWW_AUTHENTICATE).cloned(),
+            );
+        }
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+    use actix_web::test::TestRequest;
+    use actix_web::FromRequest;
+
+    use super::{Config, CredentialScheme, Credentials};
+    use crate::extractors::AuthExtractor;
+
+    #[test]
+    fn test_default_schemes() {
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer token")
+            .to_srv_request();
+        let credentials = Credentials::from_service_request(&req).unwrap();
+        assert_eq!(credentials.scheme(), CredentialScheme::Bearer);
+        assert!(matches!(
+            credentials,
+            Credentials::Bearer(ref bearer) if bearer.token() == "token"
+        ));
+
+        let (req, mut payload) = TestRequest::with_header(
+            AUTHORIZATION,
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+        )
+        .to_http_parts();
+        let credentials =
+            Credentials::from_request(&req, &mut payload).unwrap();
+        assert_eq!(credentials.subject(), Some("Aladdin"));
+
+        let req = TestRequest::with_header(AUTHORIZATION, "Token key")
+            .to_srv_request();
+        let error = Credentials::from_service_request(&req).unwrap_err();
+        let response = error.as_response_error().error_response();
+        let challenges = response
+            .headers()
+            .get_all(WWW_AUTHENTICATE)
+            .collect::<Vec<_>>();
+        assert_eq!(challenges, vec!["Basic", "Bearer"]);
+    }
+
+    #[test]
+    fn test_configured_schemes() {
+        let config = Config::default()
+            .schemes(vec![CredentialScheme::Token, CredentialScheme::Basic]);
+
+        let req = TestRequest::with_header(AUTHORIZATION, "Token key")
+            .data(config.clone())
+            .to_srv_request();
+        let credentials = Credentials::from_service_request(&req).unwrap();
+        assert!(matches!(
+            credentials,
+            Credentials::Token(ref token) if token.key() == "key"
+        ));
+
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer token")
+            .data(config)
+            .to_srv_request();
+        let error = Credentials::from_service_request(&req).unwrap_err();
+        let response = error.as_response_error().error_response();
+        let challenges = response
+            .headers()
+            .get_all(WWW_AUTHENTICATE)
+            .collect::<Vec<_>>();
+        assert_eq!(challenges, vec!["Token", "Basic"]);
+    }
+}
@@ -0,0 +1,366 @@
+//! Extractor which picks the authentication scheme by the request header
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use actix_web::dev::ServiceRequest;
+use actix_web::http::header::{HeaderName, WWW_AUTHENTICATE};
+use actix_web::{Error, HttpResponse, ResponseError};
+use futures::future::{self, Either};
+use futures::{Future, IntoFuture};
+
+use super::{AssuranceLevel, AuthExtractor};
+use crate::utils::{self, Callback};
+
+type Selector = dyn Fn(&ServiceRequest) -> Route + Send + Sync;
+
+/// Inner extractor of the [`Dispatch`] extractor.
+///
+/// [`Dispatch`]: ./enum.Dispatch.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// Use the first extractor.
+    First,
+    /// Use the second extractor.
+    Second,
+}
+
+/// [`Dispatch`] extractor configuration.
+///
+/// By default no header is checked, so both extractors are tried.
+///
+/// [`Dispatch`]: ./enum.Dispatch.html
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    header: Option<HeaderName>,
+    routes: HashMap<String, Route>,
+    default: Option<Route>,
+    selector: Option<Callback<Selector>>,
+}
+
+impl Config {
+    /// Creates new configuration, which picks the inner extractor
+    /// by the `header` value, ex. `"X-Auth-Mode"`.
+    ///
+    /// ## Panics
+    ///
+    /// If `header` is not a valid header name.
+    pub fn new(header: &str) -> Config {
+        let header = HeaderName::from_bytes(header.as_bytes())
+            .unwrap_or_else(|_| panic!("Invalid header name: {:?}", header));
+
+        Config {
+            header: Some(header),
+            ..Config::default()
+        }
+    }
+
+    /// Creates new configuration, which picks the inner extractor
+    /// with the `selector` callback instead of the header,
+    /// ex. by the request path.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web_httpauth::extractors::dispatch::{Config, Route};
+    /// let config = Config::with_selector(|req| {
+    ///     if req.path().starts_with("/internal/") {
+    ///         Route::First
+    ///     } else {
+    ///         Route::Second
+    ///     }
+    /// });
+    /// ```
+    pub fn with_selector<F>(selector: F) -> Config
+    where
+        F: Fn(&ServiceRequest) -> Route + Send + Sync + 'static,
+    {
+        Config {
+            selector: Some(Callback::new(Arc::new(selector))),
+            ..Config::default()
+        }
+    }
+
+    /// Use the `route` extractor when the header is equal to `value`.
+    pub fn route<T: Into<String>>(mut self, value: T, route: Route) -> Config {
+        self.routes.insert(value.into(), route);
+        self
+    }
+
+    /// Use the `route` extractor when the header is absent
+    /// or its value is unknown.
+    ///
+    /// If not set, both extractors are tried in order.
+    pub fn default_route(mut self, route: Route) -> Config {
+        self.default = Some(route);
+        self
+    }
+
+    fn route_for(&self, req: &ServiceRequest) -> Option<Route> {
+        if let Some(ref selector) = self.selector {
+            return Some(selector(req));
+        }
+
+        self.header
+            .as_ref()
+            .and_then(|header| req.headers().get(header))
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| self.routes.get(value).cloned())
+            .or(self.default)
+    }
+}
+
+/// Extractor, which delegates to one of two inner extractors
+/// depending on the request header configured in [`Config`],
+/// ex. for the gradual migrations from one scheme to another,
+/// or on the custom selector, see [`Config::with_selector`].
+///
+/// If the header does not pick the extractor, credentials are extracted
+/// with the `A` extractor first and with the `B` one if it fails;
+/// when both fail, the error response contains challenges for both
+/// authentication schemes.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::{App, Error};
+/// # use actix_web::dev::ServiceRequest;
+/// # use futures::future::{self, FutureResult};
+/// # use actix_web_httpauth::middleware::HttpAuthentication;
+/// # use actix_web_httpauth::extractors::basic::BasicAuth;
+/// # use actix_web_httpauth::extractors::bearer::BearerAuth;
+/// # use actix_web_httpauth::extractors::dispatch::{Config, Dispatch, Route};
+/// fn validator(
+///     req: ServiceRequest,
+///     credentials: Dispatch<BasicAuth, BearerAuth>,
+/// ) -> FutureResult<ServiceRequest, Error> {
+///     match credentials {
+///         Dispatch::First(_basic) => future::ok(req),
+///         Dispatch::Second(_bearer) => future::ok(req),
+///     }
+/// }
+///
+/// let app = App::new()
+///     .data(
+///         Config::new("X-Auth-Mode")
+///             .route("legacy", Route::First)
+///             .route("token", Route::Second),
+///     )
+///     .wrap(HttpAuthentication::with_fn(validator));
+/// ```
+///
+/// [`Config`]: ./struct.Config.html
+/// [`Config::with_selector`]: ./struct.Config.html#method.with_selector
+#[derive(Debug, Clone)]
+pub enum Dispatch<A, B> {
+    /// Credentials extracted by the first extractor.
+    First(A),
+    /// Credentials extracted by the second extractor.
+    Second(B),
+}
+
+impl<A, B> AuthExtractor for Dispatch<A, B>
+where
+    A: AuthExtractor + 'static,
+    B: AuthExtractor + 'static,
+    <A::Future as IntoFuture>::Future: 'static,
+    <B::Future as IntoFuture>::Future: 'static,
+{
+    type Error = Error;
+    type Future = Box<dyn Future<Item = Self, Error = Self::Error>>;
+
+    fn from_service_request(req: &ServiceRequest) -> Self::Future {
+        let route = match req.app_data::<Config>() {
+            Some(config) => config.route_for(req),
+            None => None,
+        };
+
+        match route {
+            Some(Route::First) => Box::new(
+                A::from_service_request(req)
+                    .into_future()
+                    .map(Dispatch::First)
+                    .map_err(Into::into),
+            ),
+            Some(Route::Second) => Box::new(
+                B::from_service_request(req)
+                    .into_future()
+                    .map(Dispatch::Second)
+                    .map_err(Into::into),
+            ),
+            None => {
+                let first = A::from_service_request(req).into_future();
+                let second = B::from_service_request(req).into_future();
+
+                Box::new(first.then(move |result| match result {
+                    Ok(credentials) => {
+                        Either::A(future::ok(Dispatch::First(credentials)))
+                    }
+                    Err(first) => Either::B(
+                        second.map(Dispatch::Second).map_err(move |second| {
+                            DispatchError {
+                                first: first.into(),
+                                second: second.into(),
+                            }
+                            .into()
+                        }),
+                    ),
+                }))
+            }
+        }
+    }
+
+    fn subject(&self) -> Option<&str> {
+        match self {
+            Dispatch::First(credentials) => credentials.subject(),
+            Dispatch::Second(credentials) => credentials.subject(),
+        }
+    }
+
+    fn uses_form_login() -> bool {
+        A::uses_form_login() || B::uses_form_login()
+    }
+
+    fn uses_form_token() -> bool {
+        A::uses_form_token() || B::uses_form_token()
+    }
+}
+
+impl<A, B> AssuranceLevel for Dispatch<A, B>
+where
+    A: AssuranceLevel,
+    B: AssuranceLevel,
+{
+    fn assurance_level(&self) -> u8 {
+        match self {
+            Dispatch::First(credentials) => credentials.assurance_level(),
+            Dispatch::Second(credentials) => credentials.assurance_level(),
+        }
+    }
+}
+
+/// Both extractors have failed, response is rendered by the first error
+/// and extended with the `WWW-Authenticate` header of the second one.
+#[derive(Debug)]
+struct DispatchError {
+    first: Error,
+    second: Error,
+}
+
+impl fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.first, f)
+    }
+}
+
+impl ResponseError for DispatchError {
+    fn error_response(&self) -> HttpResponse {
+        let challenges = self.second.as_response_error().error_response();
+        let mut response = self.first.as_response_error().error_response();
+        utils::append_header_values(
+            response.headers_mut(),
+            WWW_AUTHENTICATE,
+            challenges.headers().get_all(WWW_AUTHENTICATE).cloned(),
+        );
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+    use actix_web::test::TestRequest;
+    use futures::Future;
+
+    use super::{Config, Dispatch, Route};
+    use crate::extractors::basic::BasicAuth;
+    use crate::extractors::bearer::BearerAuth;
+    use crate::extractors::AuthExtractor;
+
+    type Extractor = Dispatch<BasicAuth, BearerAuth>;
+
+    fn config() -> Config {
+        Config::new("X-Auth-Mode")
+            .route("legacy", Route::First)
+            .route("token", Route::Second)
+    }
+
+    #[test]
+    fn test_route_by_header() {
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer token")
+            .header("X-Auth-Mode", "token")
+            .data(config())
+            .to_srv_request();
+        let result = Extractor::from_service_request(&req).wait();
+        assert!(matches!(result, Ok(Dispatch::Second(_))));
+
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer token")
+            .header("X-Auth-Mode", "legacy")
+            .data(config())
+            .to_srv_request();
+        let result = Extractor::from_service_request(&req).wait();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_default_route() {
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer token")
+            .header("X-Auth-Mode", "unknown")
+            .data(config().default_route(Route::First))
+            .to_srv_request();
+
+        assert!(Extractor::from_service_request(&req).wait().is_err());
+    }
+
+    #[test]
+    fn test_selector() {
+        let config = Config::with_selector(|req| {
+            if req.path().starts_with("/internal/") {
+                Route::First
+            } else {
+                Route::Second
+            }
+        });
+        let call = |path: &str, authorization: &str| {
+            let req = TestRequest::with_uri(path)
+                .header(AUTHORIZATION, authorization)
+                .data(config.clone())
+                .to_srv_request();
+            Extractor::from_service_request(&req).wait()
+        };
+
+        let basic = "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==";
+        assert!(matches!(
+            call("/internal/jobs", basic),
+            Ok(Dispatch::First(_))
+        ));
+        assert!(call("/internal/jobs", "Bearer token").is_err());
+        assert!(matches!(
+            call("/api", "Bearer token"),
+            Ok(Dispatch::Second(_))
+        ));
+        assert!(call("/api", basic).is_err());
+    }
+
+    #[test]
+    fn test_try_both() {
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer token")
+            .to_srv_request();
+        let result = Extractor::from_service_request(&req).wait();
+        assert!(matches!(
+            result,
+            Ok(Dispatch::Second(ref bearer)) if bearer.token() == "token"
+        ));
+
+        let req = TestRequest::default().data(config()).to_srv_request();
+        let error = Extractor::from_service_request(&req).wait().unwrap_err();
+        let response = error.as_response_error().error_response();
+        let challenges = response
+            .headers()
+            .get_all(WWW_AUTHENTICATE)
+            .collect::<Vec<_>>();
+        assert_eq!(challenges, vec!["Basic", "Bearer"]);
+    }
+}
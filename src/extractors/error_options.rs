@@ -0,0 +1,196 @@
+use std::sync::Arc;
+
+use actix_web::dev::RequestHead;
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+use actix_web::http::StatusCode;
+
+use super::errors::{
+    CacheControl, ChallengeInterceptor, CorsHeaders, ErrorObserver,
+};
+use super::lookup::{AuthLimits, HeaderLookup};
+use super::AuthenticationError;
+use crate::headers::www_authenticate::{Challenge, ChallengeParams};
+use crate::utils::Callback;
+
+/// Error responses options, shared by the [`basic::Config`]
+/// and the [`bearer::Config`], see their `error_options` method.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::http::header::{HeaderName, HeaderValue};
+/// # use actix_web_httpauth::extractors::{bearer, ErrorOptions};
+/// let config = bearer::Config::default().error_options(
+///     ErrorOptions::default()
+///         .cors_headers_fn(|_head| {
+///             vec![(
+///                 HeaderName::from_static("access-control-allow-origin"),
+///                 HeaderValue::from_static("https://app.example.com"),
+///             )]
+///         })
+///         .cache_control(Some("private"))
+///         .max_header_bytes(8192),
+/// );
+/// ```
+///
+/// [`basic::Config`]: ./basic/struct.Config.html
+/// [`bearer::Config`]: ./bearer/struct.Config.html
+#[derive(Debug, Clone, Default)]
+pub struct ErrorOptions {
+    cors_headers: Option<Callback<CorsHeaders>>,
+    observer: Option<Callback<ErrorObserver>>,
+    interceptor: Option<Callback<ChallengeInterceptor>>,
+    login_redirect: Option<HeaderValue>,
+    cache_control: CacheControl,
+    max_header_bytes: Option<usize>,
+    limits: Option<AuthLimits>,
+}
+
+impl ErrorOptions {
+    /// Set the function producing the CORS headers for the error responses,
+    /// ex. `Access-Control-Allow-Origin`.
+    ///
+    /// Since the CORS middleware wrapped inside of the authentication one
+    /// is not called for the rejected requests, browsers would report
+    /// the CORS error instead of the `HTTP 401` otherwise. Function is
+    /// called with the request head and applies to the extraction errors.
+    pub fn cors_headers_fn<F>(mut self, f: F) -> ErrorOptions
+    where
+        F: Fn(&RequestHead) -> Vec<(HeaderName, HeaderValue)>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.cors_headers = Some(Callback::new(Arc::new(f)));
+        self
+    }
+
+    /// Set the function observing the error responses of the extractor,
+    /// ex. to count them by the error code and the status code.
+    ///
+    /// See [`AuthenticationError::with_observer`] for the arguments.
+    ///
+    /// [`AuthenticationError::with_observer`]: ./struct.AuthenticationError.html#method.with_observer
+    pub fn error_observer<F>(mut self, f: F) -> ErrorOptions
+    where
+        F: Fn(&'static str, Option<&'static str>, StatusCode)
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.observer = Some(Callback::new(Arc::new(f)));
+        self
+    }
+
+    /// Set the function rewriting the challenge parameters right before
+    /// the error response is sent, ex. to add a legal notice parameter.
+    ///
+    /// See [`AuthenticationError::with_challenge_interceptor`] for details.
+    ///
+    /// [`AuthenticationError::with_challenge_interceptor`]: ./struct.AuthenticationError.html#method.with_challenge_interceptor
+    pub fn challenge_interceptor(
+        mut self,
+        interceptor: Arc<dyn Fn(&mut ChallengeParams) + Send + Sync>,
+    ) -> ErrorOptions {
+        self.interceptor = Some(Callback::new(interceptor));
+        self
+    }
+
+    /// Set the `Cache-Control` header value of the error responses,
+    /// `None` to omit the header. Defaults to `no-store`, since caching
+    /// the challenge or the error body is almost always wrong.
+    ///
+    /// ## Panics
+    ///
+    /// If `value` is not a valid header value.
+    pub fn cache_control(mut self, value: Option<&str>) -> ErrorOptions {
+        let value = value.map(|value| {
+            HeaderValue::from_str(value).unwrap_or_else(|_| {
+                panic!("Invalid Cache-Control value: {:?}", value)
+            })
+        });
+        self.cache_control = CacheControl(value);
+        self
+    }
+
+    /// Redirect the browser navigation requests to the `login_url`
+    /// instead of responding with the `HTTP 401` challenge.
+    ///
+    /// Requests with the `Sec-Fetch-Mode: navigate` header, sent by
+    /// the modern browsers for the top-level navigations, are receiving
+    /// the `HTTP 302` response, while `fetch` and the other API requests
+    /// are still receiving the `HTTP 401` one.
+    ///
+    /// ## Panics
+    ///
+    /// If `login_url` is not a valid header value.
+    pub fn redirect_navigation(mut self, login_url: &str) -> ErrorOptions {
+        let login_url = HeaderValue::from_str(login_url)
+            .unwrap_or_else(|_| panic!("Invalid login URL: {:?}", login_url));
+        self.login_redirect = Some(login_url);
+        self
+    }
+
+    /// Set the maximum length in bytes of the headers carrying
+    /// the credentials.
+    ///
+    /// Oversized headers are rejected before being decoded
+    /// with the `HTTP 431 Request Header Fields Too Large` error.
+    /// There is no limit by default.
+    pub fn max_header_bytes(mut self, value: usize) -> ErrorOptions {
+        self.max_header_bytes = Some(value);
+        self
+    }
+
+    /// Set the limits for the number and the combined length of the
+    /// headers from the extractor's `header_priority` list, checked before
+    /// any of them is decoded, like the [`max_header_bytes`] one.
+    ///
+    /// [`max_header_bytes`]: #method.max_header_bytes
+    pub fn limits(mut self, value: AuthLimits) -> ErrorOptions {
+        self.limits = Some(value);
+        self
+    }
+
+    /// Returns the `HTTP 431` error with the `challenge`, if the headers
+    /// carrying the credentials are exceeding the configured limits.
+    pub(crate) fn check_size<C: Challenge>(
+        &self,
+        lookup: &HeaderLookup,
+        headers: &HeaderMap,
+        challenge: &C,
+    ) -> Result<(), AuthenticationError<C>> {
+        if !lookup.is_oversized(headers, self.max_header_bytes, self.limits) {
+            return Ok(());
+        }
+
+        let mut error = AuthenticationError::new(challenge.clone());
+        *error.status_code_mut() = StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE;
+        Err(error)
+    }
+
+    /// Applies the options to the `error` of the request with the `head`.
+    pub(crate) fn apply<C: Challenge>(
+        &self,
+        error: AuthenticationError<C>,
+        head: &RequestHead,
+    ) -> AuthenticationError<C> {
+        let error = error
+            .with_cors_headers(head, &self.cors_headers)
+            .with_navigation_redirect(head, &self.login_redirect);
+
+        self.apply_headless(error)
+    }
+
+    /// Applies the options, which do not depend on the request,
+    /// to the `error`.
+    pub(crate) fn apply_headless<C: Challenge>(
+        &self,
+        error: AuthenticationError<C>,
+    ) -> AuthenticationError<C> {
+        error
+            .cache_control(self.cache_control.0.clone())
+            .with_error_observer(&self.observer)
+            .with_interceptor(&self.interceptor)
+    }
+}
@@ -1,11 +1,48 @@
+use std::borrow::Cow;
 use std::error::Error;
 use std::fmt;
+use std::sync::Arc;
 
+use actix_web::dev::RequestHead;
+use actix_web::http::header::{
+    HeaderMap, HeaderName, HeaderValue, CACHE_CONTROL, LOCATION, VARY,
+    WWW_AUTHENTICATE,
+};
 use actix_web::http::StatusCode;
 use actix_web::{HttpResponse, ResponseError};
 
-use crate::headers::www_authenticate::Challenge;
-use crate::headers::www_authenticate::WwwAuthenticate;
+use super::AuthExtractorConfig;
+use crate::headers::authorization::ParseError;
+use crate::headers::www_authenticate::{
+    basic, bearer, bearer_or_basic, digest, signature, timestamp_key, token,
+    Challenge,
+};
+use crate::headers::www_authenticate::{ChallengeParams, WwwAuthenticate};
+use crate::utils::{self, Callback};
+
+/// Function producing the CORS headers for the error response.
+pub(crate) type CorsHeaders =
+    dyn Fn(&RequestHead) -> Vec<(HeaderName, HeaderValue)> + Send + Sync;
+
+/// Function observing the error responses, called with the scheme name,
+/// the challenge error code and the response status code.
+pub(crate) type ErrorObserver =
+    dyn Fn(&'static str, Option<&'static str>, StatusCode) + Send + Sync;
+
+/// Function rewriting the challenges right before they are sent.
+pub(crate) type ChallengeInterceptor =
+    dyn Fn(&mut ChallengeParams) + Send + Sync;
+
+/// `Cache-Control` header value of the error responses,
+/// `no-store` unless configured otherwise.
+#[derive(Debug, Clone)]
+pub(crate) struct CacheControl(pub(crate) Option<HeaderValue>);
+
+impl Default for CacheControl {
+    fn default() -> Self {
+        CacheControl(Some(HeaderValue::from_static("no-store")))
+    }
+}
 
 /// Authentication error returned by authentication extractors.
 ///
@@ -14,7 +51,52 @@ use crate::headers::www_authenticate::WwwAuthenticate;
 #[derive(Debug)]
 pub struct AuthenticationError<C: Challenge> {
     challenge: C,
+    scheme: &'static str,
     status_code: StatusCode,
+    challenge_mode: ChallengeMode,
+    cache_control: Option<HeaderValue>,
+    extras: Option<Box<Extras<C>>>,
+}
+
+/// Describes when the `WWW-Authenticate` header is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChallengeMode {
+    Always,
+    /// Omitted from the `HTTP 401` responses only.
+    OmitOn401,
+    /// Omitted regardless of the status code.
+    Never,
+}
+
+/// Rarely used state of the error, kept out of line, so the error
+/// is cheap to move around when none of it is set.
+#[derive(Debug)]
+struct Extras<C> {
+    challenges: Vec<C>,
+    foreign_challenges: Vec<HeaderValue>,
+    headers: HeaderMap,
+    unauthorized_headers: HeaderMap,
+    reason: Option<Cow<'static, str>>,
+    location: Option<HeaderValue>,
+    parse_error: Option<ParseError>,
+    observer: Option<Callback<ErrorObserver>>,
+    interceptor: Option<Callback<ChallengeInterceptor>>,
+}
+
+impl<C> Default for Extras<C> {
+    fn default() -> Self {
+        Extras {
+            challenges: Vec::new(),
+            foreign_challenges: Vec::new(),
+            headers: HeaderMap::new(),
+            unauthorized_headers: HeaderMap::new(),
+            reason: None,
+            location: None,
+            parse_error: None,
+            observer: None,
+            interceptor: None,
+        }
+    }
 }
 
 impl<C: Challenge> AuthenticationError<C> {
@@ -23,12 +105,157 @@ impl<C: Challenge> AuthenticationError<C> {
     /// By default returned error will resolve into the `HTTP 401` status code.
     pub fn new(challenge: C) -> AuthenticationError<C> {
         AuthenticationError {
+            scheme: challenge.scheme(),
             challenge,
             status_code: StatusCode::UNAUTHORIZED,
+            challenge_mode: ChallengeMode::Always,
+            cache_control: CacheControl::default().0,
+            extras: None,
         }
     }
 
+    fn extras_mut(&mut self) -> &mut Extras<C> {
+        self.extras.get_or_insert_with(Box::default)
+    }
+
+    /// Creates new authentication error advertising all of `challenges`,
+    /// ex. the bearer ones pointing to the different identity providers
+    /// in the federated setup.
+    ///
+    /// Each challenge is sent in the separate `WWW-Authenticate` header,
+    /// in the provided order.
+    ///
+    /// ## Panics
+    ///
+    /// If `challenges` is empty.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web_httpauth::extractors::AuthenticationError;
+    /// # use actix_web_httpauth::headers::www_authenticate::bearer::Bearer;
+    /// let error = AuthenticationError::federated(vec![
+    ///     Bearer::build()
+    ///         .param("authorization_uri", "https://idp1.example.com/authorize")
+    ///         .finish(),
+    ///     Bearer::build()
+    ///         .param("authorization_uri", "https://idp2.example.com/authorize")
+    ///         .finish(),
+    /// ]);
+    /// ```
+    pub fn federated(challenges: Vec<C>) -> AuthenticationError<C> {
+        let mut challenges = challenges.into_iter();
+        let first = challenges
+            .next()
+            .expect("At least one challenge is required");
+        let mut error = AuthenticationError::new(first);
+        let challenges: Vec<C> = challenges.collect();
+        if !challenges.is_empty() {
+            error.extras_mut().challenges = challenges;
+        }
+
+        error
+    }
+
+    /// Creates new error for the valid credentials of the disabled
+    /// or locked account, resolving into the `HTTP 403` status code.
+    ///
+    /// Unlike the other errors it does not send the `WWW-Authenticate`
+    /// header, since entering the credentials again won't help;
+    /// use [`with_reason`] to explain the client what happened.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web::ResponseError;
+    /// # use actix_web_httpauth::extractors::basic::Config;
+    /// # use actix_web_httpauth::extractors::AuthenticationError;
+    /// let error = AuthenticationError::account_disabled(Config::default())
+    ///     .with_reason("Account is locked, contact the support");
+    /// let response = error.error_response();
+    ///
+    /// assert_eq!(response.status(), 403);
+    /// assert!(response.headers().get("WWW-Authenticate").is_none());
+    /// ```
+    ///
+    /// [`with_reason`]: #method.with_reason
+    pub fn account_disabled<T>(config: T) -> AuthenticationError<C>
+    where
+        T: AuthExtractorConfig<Inner = C>,
+    {
+        let mut error = config.into_error();
+        error.status_code = StatusCode::FORBIDDEN;
+        error.challenge_mode = ChallengeMode::Never;
+
+        error
+    }
+
+    /// Creates new error for the server misconfiguration, ex. the missing
+    /// verification key, resolving into the `HTTP 500` status code
+    /// without the challenge, since the client can't do anything about it.
+    #[cfg(feature = "paseto")]
+    pub(crate) fn misconfigured<T>(config: T) -> AuthenticationError<C>
+    where
+        T: AuthExtractorConfig<Inner = C>,
+    {
+        let mut error = config.into_error();
+        error.status_code = StatusCode::INTERNAL_SERVER_ERROR;
+        error.challenge_mode = ChallengeMode::Never;
+
+        error
+    }
+
+    /// Attach the human-readable `reason`,
+    /// sent as the plain text body of the response.
+    pub fn with_reason<T>(mut self, reason: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.extras_mut().reason = Some(reason.into());
+        self
+    }
+
+    /// Returns the name of scheme enforced by the originating extractor,
+    /// ex. `"Basic"` or `"Bearer"`.
+    ///
+    /// Useful for the centralized logging and metrics.
+    pub fn scheme_name(&self) -> &'static str {
+        self.scheme
+    }
+
+    /// Returns the reason why the credentials could not be parsed,
+    /// if this error was caused by the parse failure.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web::test::TestRequest;
+    /// # use actix_web_httpauth::extractors::basic::BasicAuth;
+    /// # use actix_web_httpauth::extractors::AuthExtractor;
+    /// # use actix_web_httpauth::headers::authorization::ParseError;
+    /// let req = TestRequest::with_header("Authorization", "Basic %%%")
+    ///     .to_srv_request();
+    /// let error = BasicAuth::from_service_request(&req).unwrap_err();
+    ///
+    /// match error.parse_error() {
+    ///     Some(ParseError::Base64DecodeError(_)) => (),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn parse_error(&self) -> Option<&ParseError> {
+        self.extras.as_ref()?.parse_error.as_ref()
+    }
+
+    /// Set the reason why the credentials could not be parsed.
+    pub fn with_parse_error(mut self, error: ParseError) -> Self {
+        self.extras_mut().parse_error = Some(error);
+        self
+    }
+
     /// Returns mutable reference to the inner challenge instance.
+    ///
+    /// For the [`federated`](#method.federated) errors
+    /// it is the first challenge.
     pub fn challenge_mut(&mut self) -> &mut C {
         &mut self.challenge
     }
@@ -40,21 +267,472 @@ impl<C: Challenge> AuthenticationError<C> {
     pub fn status_code_mut(&mut self) -> &mut StatusCode {
         &mut self.status_code
     }
+
+    /// Returns the status code the error resolves into.
+    pub(crate) fn status_code(&self) -> StatusCode {
+        self.status_code
+    }
+
+    /// Attach an additional header to the error response.
+    ///
+    /// Can be called multiple times, all headers are appended
+    /// to the response along with the `WWW-Authenticate` one,
+    /// ex. `Access-Control-Expose-Headers` or `Link`.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.extras_mut().headers.append(name, value);
+        self
+    }
+
+    /// Attach an additional header to the `HTTP 401` error response only,
+    /// ex. the `Link` to the token endpoint, which is pointless
+    /// for the `HTTP 403` one.
+    pub(crate) fn with_unauthorized_header(
+        mut self,
+        name: HeaderName,
+        value: HeaderValue,
+    ) -> Self {
+        self.extras_mut().unauthorized_headers.append(name, value);
+        self
+    }
+
+    /// Advertise the challenge of some other scheme, sent as is after
+    /// the own ones, ex. the `Basic` one next to the `Bearer` challenge.
+    pub(crate) fn with_foreign_challenge(mut self, value: HeaderValue) -> Self {
+        self.extras_mut().foreign_challenges.push(value);
+        self
+    }
+
+    /// Set the `Cache-Control` header value of the error response,
+    /// `None` to omit the header.
+    ///
+    /// By default it is `no-store`, so the caching proxies are not storing
+    /// the challenges. Header attached with [`with_header`] takes precedence.
+    ///
+    /// [`with_header`]: #method.with_header
+    pub fn cache_control(mut self, value: Option<HeaderValue>) -> Self {
+        self.cache_control = value;
+        self
+    }
+
+    /// Call the `observer` each time the error response is built,
+    /// ex. to count the errors for the operational dashboards.
+    ///
+    /// Observer is called with the scheme name, the error code of the
+    /// challenge, ex. `"invalid_token"`, and the response status code,
+    /// all of them available without any allocation.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use std::sync::atomic::{AtomicUsize, Ordering};
+    /// # use std::sync::Arc;
+    /// # use actix_web_httpauth::extractors::AuthenticationError;
+    /// # use actix_web_httpauth::headers::www_authenticate::bearer::Bearer;
+    /// let invalid_tokens = Arc::new(AtomicUsize::new(0));
+    /// let counter = invalid_tokens.clone();
+    /// let error = AuthenticationError::new(Bearer::default())
+    ///     .with_observer(move |_scheme, error, _status| {
+    ///         if error == Some("invalid_token") {
+    ///             counter.fetch_add(1, Ordering::Relaxed);
+    ///         }
+    ///     });
+    /// ```
+    pub fn with_observer<F>(mut self, observer: F) -> Self
+    where
+        F: Fn(&'static str, Option<&'static str>, StatusCode)
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.extras_mut().observer = Some(Callback::new(Arc::new(observer)));
+        self
+    }
+
+    /// Attach the observer configured for the extractor, if any.
+    pub(crate) fn with_error_observer(
+        mut self,
+        observer: &Option<Callback<ErrorObserver>>,
+    ) -> Self {
+        if observer.is_some() {
+            self.extras_mut().observer = observer.clone();
+        }
+
+        self
+    }
+
+    /// Call the `interceptor` for each challenge right before it is
+    /// serialized into the `WWW-Authenticate` header, so its parameters
+    /// can be added, removed or rewritten, ex. to strip the internal
+    /// realm names.
+    ///
+    /// Challenges which can't be rewritten, ex. the ones with the
+    /// rewritten parameters not valid in the header value, are omitted
+    /// rather than sent as is.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web::ResponseError;
+    /// # use actix_web_httpauth::extractors::AuthenticationError;
+    /// # use actix_web_httpauth::headers::www_authenticate::bearer::Bearer;
+    /// let error = AuthenticationError::new(Bearer::build().realm("internal-7").finish())
+    ///     .with_challenge_interceptor(|params| {
+    ///         params.remove("realm");
+    ///         params.set("notice", "Authorized use only");
+    ///     });
+    /// let response = error.error_response();
+    ///
+    /// assert_eq!(
+    ///     response.headers().get("WWW-Authenticate").unwrap(),
+    ///     "Bearer notice=\"Authorized use only\""
+    /// );
+    /// ```
+    pub fn with_challenge_interceptor<F>(mut self, interceptor: F) -> Self
+    where
+        F: Fn(&mut ChallengeParams) + Send + Sync + 'static,
+    {
+        self.extras_mut().interceptor =
+            Some(Callback::new(Arc::new(interceptor)));
+        self
+    }
+
+    /// Attach the challenge interceptor configured for the extractor, if any.
+    pub(crate) fn with_interceptor(
+        mut self,
+        interceptor: &Option<Callback<ChallengeInterceptor>>,
+    ) -> Self {
+        if interceptor.is_some() {
+            self.extras_mut().interceptor = interceptor.clone();
+        }
+
+        self
+    }
+
+    /// Omit the `WWW-Authenticate` header from the `HTTP 401` response,
+    /// so it can't trigger the browser authentication dialog.
+    ///
+    /// Responses with the other status codes are not affected.
+    pub fn omit_challenge_on_401(mut self, value: bool) -> Self {
+        if self.challenge_mode != ChallengeMode::Never {
+            self.challenge_mode = if value {
+                ChallengeMode::OmitOn401
+            } else {
+                ChallengeMode::Always
+            };
+        }
+        self
+    }
+
+    /// Attach the headers produced by the configured CORS headers function.
+    pub(crate) fn with_cors_headers(
+        mut self,
+        head: &RequestHead,
+        cors_headers: &Option<Callback<CorsHeaders>>,
+    ) -> Self {
+        if let Some(ref cors_headers) = cors_headers {
+            for (name, value) in cors_headers(head) {
+                self = self.with_header(name, value);
+            }
+        }
+
+        self
+    }
+
+    /// Turn the `HTTP 401` error into the `HTTP 302` redirect to the
+    /// `login_url` if the request is the browser navigation one,
+    /// as reported by the `Sec-Fetch-Mode: navigate` header.
+    ///
+    /// Responses are marked with `Vary: Sec-Fetch-Mode` once the
+    /// `login_url` is configured.
+    pub(crate) fn with_navigation_redirect(
+        mut self,
+        head: &RequestHead,
+        login_url: &Option<HeaderValue>,
+    ) -> Self {
+        let login_url = match login_url {
+            Some(login_url) => login_url,
+            None => return self,
+        };
+
+        let navigate = head
+            .headers()
+            .get(SEC_FETCH_MODE)
+            .is_some_and(|mode| mode == "navigate");
+        if navigate && self.status_code == StatusCode::UNAUTHORIZED {
+            self.status_code = StatusCode::FOUND;
+            self.extras_mut().location = Some(login_url.clone());
+        }
+
+        self.with_header(VARY, HeaderValue::from_static("Sec-Fetch-Mode"))
+    }
 }
 
+/// Fetch metadata request header, sent by the modern browsers.
+const SEC_FETCH_MODE: &str = "sec-fetch-mode";
+
 impl<C: Challenge> fmt::Display for AuthenticationError<C> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt::Display::fmt(&self.status_code, f)
     }
 }
 
-impl<C: 'static + Challenge> Error for AuthenticationError<C> {}
+impl<C: 'static + Challenge> Error for AuthenticationError<C> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.parse_error()
+            .map(|error| error as &(dyn Error + 'static))
+    }
+}
+
+/// Creates the error with the default challenge,
+/// keeping the parse `error` as the reason.
+impl<C: Challenge + Default> From<ParseError> for AuthenticationError<C> {
+    fn from(error: ParseError) -> Self {
+        AuthenticationError::new(C::default()).with_parse_error(error)
+    }
+}
 
 impl<C: 'static + Challenge> ResponseError for AuthenticationError<C> {
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::build(self.status_code)
-            // TODO: Get rid of the `.clone()`
-            .set(WwwAuthenticate(self.challenge.clone()))
-            .finish()
+        let default_extras;
+        let extras = match self.extras {
+            Some(ref extras) => extras,
+            None => {
+                default_extras = Extras::default();
+                &default_extras
+            }
+        };
+
+        if let Some(ref observer) = extras.observer {
+            observer(
+                self.scheme,
+                self.challenge.error_code(),
+                self.status_code,
+            );
+        }
+
+        if let Some(ref location) = extras.location {
+            let mut response = HttpResponse::build(self.status_code);
+            response.header(LOCATION, location.clone());
+            for (name, value) in extras.headers.iter() {
+                response.header(name.clone(), value.clone());
+            }
+
+            return self.with_cache_control(response.finish());
+        }
+
+        let unauthorized = self.status_code == StatusCode::UNAUTHORIZED;
+        let omit_challenge = match self.challenge_mode {
+            ChallengeMode::Always => false,
+            ChallengeMode::OmitOn401 => unauthorized,
+            ChallengeMode::Never => true,
+        };
+        let mut response = HttpResponse::build(self.status_code);
+        if !omit_challenge {
+            match extras.interceptor {
+                Some(ref interceptor) => {
+                    let value = intercept(interceptor, &self.challenge);
+                    if let Some(value) = value {
+                        response.header(WWW_AUTHENTICATE, value);
+                    }
+                }
+                // TODO: Get rid of the `.clone()`
+                None => {
+                    response.set(WwwAuthenticate(self.challenge.clone()));
+                }
+            }
+        }
+        for (name, value) in extras.headers.iter() {
+            response.header(name.clone(), value.clone());
+        }
+        if unauthorized {
+            for (name, value) in extras.unauthorized_headers.iter() {
+                response.header(name.clone(), value.clone());
+            }
+        }
+
+        let mut response = match extras.reason {
+            Some(ref reason) => response
+                .content_type("text/plain; charset=utf-8")
+                .body(reason.clone().into_owned()),
+            None => response.finish(),
+        };
+        let more_challenges = !extras.challenges.is_empty()
+            || !extras.foreign_challenges.is_empty();
+        if !omit_challenge && more_challenges {
+            let challenges = extras.challenges.iter().filter_map(|challenge| {
+                match extras.interceptor {
+                    Some(ref interceptor) => intercept(interceptor, challenge),
+                    None => challenge.clone().try_into().ok(),
+                }
+            });
+            utils::append_header_values(
+                response.headers_mut(),
+                WWW_AUTHENTICATE,
+                challenges.chain(extras.foreign_challenges.iter().cloned()),
+            );
+        }
+
+        self.with_cache_control(response)
+    }
+}
+
+/// Returns the status code of the `error`, if it is the authentication
+/// error of any built-in scheme, without rendering its response,
+/// so the error observer is not called.
+pub(crate) fn status_of(error: &actix_web::Error) -> Option<StatusCode> {
+    fn status<C: 'static + Challenge>(
+        error: &actix_web::Error,
+    ) -> Option<StatusCode> {
+        error
+            .as_error::<AuthenticationError<C>>()
+            .map(AuthenticationError::status_code)
+    }
+
+    status::<basic::Basic>(error)
+        .or_else(|| status::<bearer::Bearer>(error))
+        .or_else(|| status::<bearer_or_basic::BearerOrBasic>(error))
+        .or_else(|| status::<digest::Digest>(error))
+        .or_else(|| status::<signature::Signature>(error))
+        .or_else(|| status::<timestamp_key::TimestampKey>(error))
+        .or_else(|| status::<token::Token>(error))
+}
+
+/// Returns the `challenge` rewritten by the `interceptor`,
+/// or `None` if it can't be rewritten.
+fn intercept<C: Challenge>(
+    interceptor: &Callback<ChallengeInterceptor>,
+    challenge: &C,
+) -> Option<HeaderValue> {
+    let mut params = ChallengeParams::from_challenge(challenge)?;
+    interceptor(&mut params);
+
+    params.to_header_value().ok()
+}
+
+impl<C: Challenge> AuthenticationError<C> {
+    fn with_cache_control(&self, mut response: HttpResponse) -> HttpResponse {
+        if let Some(ref value) = self.cache_control {
+            if !response.headers().contains_key(CACHE_CONTROL) {
+                response.headers_mut().insert(CACHE_CONTROL, value.clone());
+            }
+        }
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::dev::Body;
+    use actix_web::http::header::{
+        HeaderValue, ACCESS_CONTROL_EXPOSE_HEADERS, LINK, WWW_AUTHENTICATE,
+    };
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+
+    use super::AuthenticationError;
+    use crate::extractors::bearer::Config;
+    use crate::headers::www_authenticate::basic::Basic;
+    use crate::headers::www_authenticate::bearer::Bearer;
+
+    #[test]
+    fn test_scheme_name() {
+        assert_eq!(
+            AuthenticationError::new(Basic::new()).scheme_name(),
+            "Basic"
+        );
+        assert_eq!(
+            AuthenticationError::new(Bearer::default()).scheme_name(),
+            "Bearer"
+        );
+    }
+
+    #[test]
+    fn test_federated() {
+        let error = AuthenticationError::federated(vec![
+            Bearer::build()
+                .realm("idp1")
+                .param(
+                    "authorization_uri",
+                    "https://idp1.example.com/authorize",
+                )
+                .finish(),
+            Bearer::build()
+                .realm("idp2")
+                .param(
+                    "authorization_uri",
+                    "https://idp2.example.com/authorize",
+                )
+                .finish(),
+        ]);
+        let response = error.error_response();
+        let challenges = response
+            .headers()
+            .get_all(WWW_AUTHENTICATE)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            challenges,
+            vec![
+                "Bearer realm=\"idp1\", authorization_uri=\"https://idp1.example.com/authorize\"",
+                "Bearer realm=\"idp2\", authorization_uri=\"https://idp2.example.com/authorize\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_interceptor() {
+        let error = AuthenticationError::federated(vec![
+            Bearer::build().realm("internal-1").finish(),
+            Bearer::build().realm("internal-2").finish(),
+        ])
+        .with_challenge_interceptor(|params| {
+            params.set("realm", "line\nbreak");
+        });
+        let response = error.error_response();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().get(WWW_AUTHENTICATE).is_none());
+    }
+
+    #[test]
+    fn test_account_disabled() {
+        let config = Config::default().realm("example");
+        let response = AuthenticationError::account_disabled(config.clone())
+            .error_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(response.headers().get(WWW_AUTHENTICATE).is_none());
+
+        let response = AuthenticationError::account_disabled(config)
+            .with_reason("Account is disabled")
+            .omit_challenge_on_401(false)
+            .error_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(response.headers().get(WWW_AUTHENTICATE).is_none());
+        assert_eq!(
+            response.body().as_ref(),
+            Some(&Body::from("Account is disabled"))
+        );
+    }
+
+    #[test]
+    fn test_with_header() {
+        let error = AuthenticationError::new(Basic::new())
+            .with_header(
+                ACCESS_CONTROL_EXPOSE_HEADERS,
+                HeaderValue::from_static("WWW-Authenticate"),
+            )
+            .with_header(LINK, HeaderValue::from_static("</login>"))
+            .with_header(LINK, HeaderValue::from_static("</help>"));
+        let response = error.error_response();
+        let headers = response.headers();
+
+        assert_eq!(headers.get(WWW_AUTHENTICATE).unwrap(), "Basic");
+        assert_eq!(
+            headers.get(ACCESS_CONTROL_EXPOSE_HEADERS).unwrap(),
+            "WWW-Authenticate"
+        );
+        let links = headers.get_all(LINK).collect::<Vec<_>>();
+        assert_eq!(links, vec!["</login>", "</help>"]);
     }
 }
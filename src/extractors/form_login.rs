@@ -0,0 +1,361 @@
+//! Form body fallbacks for the `Basic` credentials and the `Bearer` token
+
+use actix_web::dev::{Payload, ServiceRequest};
+use actix_web::http::header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE};
+use actix_web::http::Method;
+use actix_web::{error, Error, HttpMessage};
+use bytes::BytesMut;
+use futures::future::{self, Either};
+use futures::{stream, Future, Stream};
+
+use super::basic::Config;
+use super::{bearer, AuthExtractor};
+use crate::headers::authorization::Basic;
+
+/// Maximum length in bytes of the form body carrying the bearer token.
+pub(crate) const MAX_FORM_TOKEN_BYTES: usize = 16 * 1024;
+
+/// Form login fallback settings, see [`Config::form_login`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web_httpauth::extractors::basic::{Config, FormLoginConfig};
+/// let config = Config::default().form_login(FormLoginConfig {
+///     path: "/signin",
+///     ..FormLoginConfig::default()
+/// });
+/// ```
+///
+/// [`Config::form_login`]: ./struct.Config.html#method.form_login
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormLoginConfig {
+    /// Path of the login form target, `"/login"` by default.
+    pub path: &'static str,
+    /// Name of the user-ID form field, `"username"` by default.
+    pub user_field: &'static str,
+    /// Name of the password form field, `"password"` by default.
+    pub pass_field: &'static str,
+    /// Maximum form body length in bytes, 16 KiB by default.
+    pub max_body_bytes: usize,
+}
+
+impl Default for FormLoginConfig {
+    fn default() -> Self {
+        FormLoginConfig {
+            path: "/login",
+            user_field: "username",
+            pass_field: "password",
+            max_body_bytes: 16 * 1024,
+        }
+    }
+}
+
+impl FormLoginConfig {
+    /// Returns `true` if the request body should be buffered:
+    /// it is the urlencoded `POST` to the login path without the
+    /// `Authorization` header, and its `Content-Length` is within the limit.
+    fn matches(&self, req: &ServiceRequest) -> bool {
+        req.method() == Method::POST
+            && req.path() == self.path
+            && is_form(req, self.max_body_bytes)
+    }
+
+    fn credentials(&self, body: &[u8]) -> Option<Basic> {
+        let mut user_id = None;
+        let mut password = None;
+        for (name, value) in url::form_urlencoded::parse(body) {
+            if name == self.user_field {
+                user_id = Some(value.into_owned());
+            } else if name == self.pass_field {
+                password = Some(value.into_owned());
+            }
+        }
+
+        Some(Basic::new(user_id?, Some(password?)))
+    }
+}
+
+/// Returns `true` if the request is the urlencoded form without the
+/// `Authorization` header, and its `Content-Length` is within `max_bytes`.
+fn is_form(req: &ServiceRequest, max_bytes: usize) -> bool {
+    let headers = req.headers();
+    let content_length = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+
+    !headers.contains_key(AUTHORIZATION)
+        && headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| {
+                value.starts_with("application/x-www-form-urlencoded")
+            })
+        && content_length.is_some_and(|length| length <= max_bytes)
+}
+
+/// Returns `true` if the bearer token should be looked for in the form
+/// body, as allowed by [RFC 6750](https://tools.ietf.org/html/rfc6750#section-2.2)
+/// for the methods with the request body semantics.
+fn matches_form_token(req: &ServiceRequest) -> bool {
+    req.app_data::<bearer::Config>()
+        .is_some_and(|config| config.form_body)
+        && req.method() != Method::GET
+        && req.method() != Method::HEAD
+        && is_form(req, MAX_FORM_TOKEN_BYTES)
+}
+
+/// Form body fallbacks of the middleware extractors,
+/// see [`AuthExtractor::uses_form_login`].
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct FormBody {
+    pub(crate) login: bool,
+    pub(crate) token: bool,
+}
+
+impl FormBody {
+    /// Returns the fallbacks used by the `T` extractor.
+    pub(crate) fn of<T: AuthExtractor>() -> FormBody {
+        FormBody {
+            login: T::uses_form_login(),
+            token: T::uses_form_token(),
+        }
+    }
+
+    /// Returns the fallbacks used by any of the extractors.
+    pub(crate) fn union(self, other: FormBody) -> FormBody {
+        FormBody {
+            login: self.login || other.login,
+            token: self.token || other.token,
+        }
+    }
+}
+
+/// Credentials extracted from the login form body.
+#[derive(Debug, Clone)]
+pub(crate) struct FormCredentials(pub(crate) Basic);
+
+/// Bearer token extracted from the `access_token` form body field.
+#[derive(Debug, Clone)]
+pub(crate) struct FormToken(pub(crate) String);
+
+/// Buffers the form body if [`FormLoginConfig`] is configured
+/// and the request matches it, so the `BasicAuth` extractor can pick
+/// the [`FormCredentials`] up; same goes for the `BearerAuth` extractor
+/// and the [`FormToken`], if the form body token is allowed.
+///
+/// Only the fallbacks of the `form_body` are considered, so the body
+/// is not read for the extractors which are not looking into it.
+/// Body is put back into the request for the handler.
+pub(crate) fn buffer(
+    mut req: ServiceRequest,
+    form_body: FormBody,
+) -> impl Future<Item = ServiceRequest, Error = Error> {
+    let form_login = req
+        .app_data::<Config>()
+        .filter(|_| form_body.login)
+        .and_then(|config| config.form_login.clone())
+        .filter(|form_login| form_login.matches(&req));
+    let form_token = form_body.token && matches_form_token(&req);
+    let max_body_bytes = match (&form_login, form_token) {
+        (Some(form_login), true) => {
+            form_login.max_body_bytes.max(MAX_FORM_TOKEN_BYTES)
+        }
+        (Some(form_login), false) => form_login.max_body_bytes,
+        (None, true) => MAX_FORM_TOKEN_BYTES,
+        (None, false) => return Either::A(future::ok(req)),
+    };
+
+    let f = req
+        .take_payload()
+        .map_err(Error::from)
+        .fold(BytesMut::new(), move |mut body, chunk| {
+            // `Content-Length` might lie
+            if body.len() + chunk.len() > max_body_bytes {
+                return Err(error::ErrorPayloadTooLarge(
+                    "Form body is too large",
+                ));
+            }
+            body.extend_from_slice(&chunk);
+            Ok(body)
+        })
+        .map(move |body| {
+            let body = body.freeze();
+            let credentials =
+                form_login.and_then(|form_login| form_login.credentials(&body));
+            if let Some(credentials) = credentials {
+                req.extensions_mut().insert(FormCredentials(credentials));
+            }
+            if form_token {
+                let token = url::form_urlencoded::parse(&body)
+                    .find(|(name, _)| name == "access_token")
+                    .map(|(_, value)| value.into_owned());
+                if let Some(token) = token {
+                    req.extensions_mut().insert(FormToken(token));
+                }
+            }
+            req.set_payload(Payload::Stream(Box::new(stream::once(Ok(body)))));
+
+            req
+        });
+
+    Either::B(f)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::CONTENT_TYPE;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+    use futures::future;
+
+    use super::FormLoginConfig;
+    use crate::extractors::basic::Config;
+    use crate::extractors::bearer;
+    use crate::middleware::tests::status;
+    use crate::middleware::HttpAuthentication;
+
+    fn login(path: &str, body: &'static str) -> TestRequest {
+        TestRequest::post()
+            .uri(path)
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header("Content-Length", body.len().to_string())
+            .set_payload(body)
+    }
+
+    #[test]
+    fn test_form_login() {
+        let auth = HttpAuthentication::basic(|req, credentials| {
+            if credentials.user_id() == "Aladdin"
+                && credentials.password().unwrap() == "open sesame"
+            {
+                future::ok(req)
+            } else {
+                future::err(actix_web::error::ErrorForbidden("invalid"))
+            }
+        });
+        let mut app = test::init_service(
+            App::new()
+                .data(Config::default().form_login(FormLoginConfig {
+                    max_body_bytes: 64,
+                    ..FormLoginConfig::default()
+                }))
+                .wrap(auth)
+                .route("/login", web::post().to(|body: String| body))
+                .route("/other", web::post().to(HttpResponse::Ok)),
+        );
+
+        let body = "username=Aladdin&password=open+sesame&remember=1";
+        let res =
+            test::call_service(&mut app, login("/login", body).to_request());
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(test::read_body(res), body);
+
+        let req =
+            login("/login", "username=Aladdin&password=guess").to_request();
+        assert_eq!(status(&mut app, req), StatusCode::FORBIDDEN);
+
+        let req = login("/other", body).to_request();
+        assert_eq!(status(&mut app, req), StatusCode::UNAUTHORIZED);
+
+        // Over the limit, body is not buffered
+        let req = login(
+            "/login",
+            "username=Aladdin&password=open+sesame&padding=xxxxxxxxxxxxxxxxxxxxxxxx",
+        )
+        .to_request();
+        assert_eq!(status(&mut app, req), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_form_body_token() {
+        let auth = HttpAuthentication::bearer(|req, credentials| {
+            if credentials.token() == "mF_9.B5f-4.1JqM" {
+                future::ok(req)
+            } else {
+                future::err(actix_web::error::ErrorForbidden("invalid"))
+            }
+        });
+        let mut app = test::init_service(
+            App::new()
+                .data(bearer::Config::default().allow_form_body(true))
+                .wrap(auth)
+                .route("/resource", web::post().to(|body: String| body))
+                .route("/resource", web::get().to(HttpResponse::Ok)),
+        );
+
+        let body = "access_token=mF_9.B5f-4.1JqM&item=1";
+        let res =
+            test::call_service(&mut app, login("/resource", body).to_request());
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(test::read_body(res), body);
+
+        let req = login("/resource", "access_token=guess").to_request();
+        assert_eq!(status(&mut app, req), StatusCode::FORBIDDEN);
+
+        let req = login("/resource", "item=1").to_request();
+        assert_eq!(status(&mut app, req), StatusCode::UNAUTHORIZED);
+
+        let req = login("/resource", body)
+            .method(actix_web::http::Method::GET)
+            .to_request();
+        assert_eq!(status(&mut app, req), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_form_body_token_disabled() {
+        let mut app = test::init_service(
+            App::new()
+                .wrap(HttpAuthentication::bearer(|req, _credentials| {
+                    future::ok(req)
+                }))
+                .route("/resource", web::post().to(HttpResponse::Ok)),
+        );
+
+        let req =
+            login("/resource", "access_token=mF_9.B5f-4.1JqM").to_request();
+        assert_eq!(status(&mut app, req), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_buffer_for_form_extractors_only() {
+        let config = Config::default().form_login(FormLoginConfig {
+            max_body_bytes: 64,
+            ..FormLoginConfig::default()
+        });
+        // `Content-Length` lies, so the buffered body is too large
+        let oversized = || {
+            TestRequest::post()
+                .uri("/login")
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .header("Content-Length", "10")
+                .set_payload(vec![b'x'; 128])
+                .to_request()
+        };
+
+        let mut app = test::init_service(
+            App::new()
+                .data(config.clone())
+                .wrap(HttpAuthentication::basic(|req, _credentials| {
+                    future::ok(req)
+                }))
+                .route("/login", web::post().to(HttpResponse::Ok)),
+        );
+        assert_eq!(
+            status(&mut app, oversized()),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+
+        let mut app = test::init_service(
+            App::new()
+                .data(config)
+                .wrap(HttpAuthentication::bearer(|req, _credentials| {
+                    future::ok(req)
+                }))
+                .route("/login", web::post().to(HttpResponse::Ok)),
+        );
+        assert_eq!(status(&mut app, oversized()), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,572 @@
+//! Extractor and validator for the HMAC-signed requests,
+//! "Signature" HTTP Authentication Scheme
+
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use actix_web::dev::{Payload, ServiceRequest};
+use actix_web::http::header::Header;
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+use futures::future::{self, FutureResult};
+use hmac::{Hmac, Mac};
+use sha2::{Sha256, Sha512};
+
+use super::config::AuthExtractorConfig;
+use super::errors::AuthenticationError;
+use super::{AssuranceLevel, AuthExtractor};
+use crate::clock::{Clock, SystemClock};
+use crate::headers::authorization::{self, Authorization};
+use crate::headers::www_authenticate::signature::Signature;
+
+type SecretLookup = dyn Fn(&str) -> Option<Vec<u8>> + Send + Sync;
+
+/// [`HmacAuth`](./struct.HmacAuth.html) extractor configuration,
+/// used for [`HmacValidator`](./struct.HmacValidator.html) errors too.
+#[derive(Debug, Clone, Default)]
+pub struct Config(Signature);
+
+impl Config {
+    /// Set challenge `realm` attribute.
+    pub fn realm<T: Into<Cow<'static, str>>>(mut self, value: T) -> Config {
+        self.0 = self.0.realm(value);
+        self
+    }
+
+    /// Set challenge `headers` attribute, the space-delimited list
+    /// of headers which the client is expected to sign.
+    pub fn headers<T: Into<Cow<'static, str>>>(mut self, value: T) -> Config {
+        self.0 = self.0.headers(value);
+        self
+    }
+}
+
+impl AsRef<Signature> for Config {
+    fn as_ref(&self) -> &Signature {
+        &self.0
+    }
+}
+
+impl AuthExtractorConfig for Config {
+    type Inner = Signature;
+
+    fn into_inner(self) -> Self::Inner {
+        self.0
+    }
+}
+
+/// Extractor for the `Signature` credentials.
+///
+/// Extractor only parses the `Authorization` header, signature is
+/// verified by the [`HmacValidator`](./struct.HmacValidator.html).
+#[derive(Debug, Clone)]
+pub struct HmacAuth(authorization::Signature);
+
+impl HmacAuth {
+    /// Returns the key ID provided by client.
+    pub fn key_id(&self) -> &str {
+        self.0.key_id()
+    }
+
+    /// Returns the parsed credentials.
+    pub fn credentials(&self) -> &authorization::Signature {
+        &self.0
+    }
+
+    fn extract<T: HttpMessage>(
+        msg: &T,
+        config: Option<&Config>,
+    ) -> Result<Self, AuthenticationError<Signature>> {
+        Authorization::<authorization::Signature>::parse(msg)
+            .map(|auth| HmacAuth(auth.into_scheme()))
+            .map_err(|_| {
+                AuthenticationError::from(config.cloned().unwrap_or_default())
+            })
+    }
+}
+
+impl FromRequest for HmacAuth {
+    type Config = Config;
+    type Future = Result<Self, Self::Error>;
+    type Error = AuthenticationError<Signature>;
+
+    fn from_request(
+        req: &HttpRequest,
+        _payload: &mut Payload,
+    ) -> <Self as FromRequest>::Future {
+        HmacAuth::extract(req, req.app_data::<Self::Config>())
+    }
+}
+
+impl AuthExtractor for HmacAuth {
+    type Future = Result<Self, Self::Error>;
+    type Error = AuthenticationError<Signature>;
+
+    fn from_service_request(req: &ServiceRequest) -> Self::Future {
+        let config = req.app_data::<Config>();
+
+        HmacAuth::extract(req, config.as_ref().map(|config| config.get_ref()))
+    }
+
+    fn subject(&self) -> Option<&str> {
+        Some(self.key_id())
+    }
+}
+
+impl AssuranceLevel for HmacAuth {
+    fn assurance_level(&self) -> u8 {
+        2
+    }
+}
+
+/// HMAC algorithm used by the [`HmacValidator`].
+///
+/// [`HmacValidator`]: ./struct.HmacValidator.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HmacAlgorithm {
+    /// `hmac-sha256`
+    HmacSha256,
+    /// `hmac-sha512`
+    HmacSha512,
+}
+
+impl HmacAlgorithm {
+    /// Returns the `algorithm` parameter value.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HmacAlgorithm::HmacSha256 => "hmac-sha256",
+            HmacAlgorithm::HmacSha512 => "hmac-sha512",
+        }
+    }
+
+    fn verify(self, secret: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        // Comparison is done in constant time by `verify_slice`
+        match self {
+            HmacAlgorithm::HmacSha256 => Hmac::<Sha256>::new_from_slice(secret)
+                .map(|mac| mac.chain_update(message).verify_slice(signature))
+                .is_ok_and(|result| result.is_ok()),
+            HmacAlgorithm::HmacSha512 => Hmac::<Sha512>::new_from_slice(secret)
+                .map(|mac| mac.chain_update(message).verify_slice(signature))
+                .is_ok_and(|result| result.is_ok()),
+        }
+    }
+}
+
+/// Ready-made validator for the `HttpAuthentication::with_fn` middleware,
+/// which verifies the HMAC signature of the [`HmacAuth`] credentials.
+///
+/// Signing string is reconstructed from the headers listed in the
+/// `headers` parameter, including the `(request-target)`, `(created)`
+/// and `(expires)` pseudo-headers, and signed with the secret returned
+/// by the lookup function for the `keyId` parameter. Signature is
+/// rejected with the `HTTP 401` error if it does not match, if the key
+/// is unknown, or if it does not cover all of the
+/// [required headers](#method.required_headers) and the ones listed
+/// by the [`Config::headers`]. Either `created` or `expires` timestamp
+/// is required: signatures created more than the allowed clock skew ago,
+/// or the expired ones, are rejected too, so they can't be replayed later.
+/// Challenge is formed from the [`Config`] instance fetched from
+/// the app data.
+///
+/// ## Example
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use actix_web::App;
+/// # use actix_web_httpauth::extractors::hmac::{HmacAlgorithm, HmacValidator};
+/// # use actix_web_httpauth::middleware::HttpAuthentication;
+/// let validator = HmacValidator::new(|key_id| match key_id {
+///     "hmac-key-1" => Some(b"secret".to_vec()),
+///     _ => None,
+/// })
+/// .algorithm(HmacAlgorithm::HmacSha512)
+/// .max_skew(Duration::from_secs(30));
+///
+/// let app = App::new().wrap(HttpAuthentication::with_fn(move |req, credentials| {
+///     validator.validate(req, credentials)
+/// }));
+/// ```
+///
+/// [`HmacAuth`]: ./struct.HmacAuth.html
+/// [`Config`]: ./struct.Config.html
+/// [`Config::headers`]: ./struct.Config.html#method.headers
+#[derive(Clone)]
+pub struct HmacValidator {
+    secrets: Arc<SecretLookup>,
+    algorithm: HmacAlgorithm,
+    max_skew: Duration,
+    required_headers: Vec<String>,
+    clock: Arc<dyn Clock>,
+}
+
+impl HmacValidator {
+    /// Creates new validator, which gets the secrets by the key ID
+    /// from the `secrets` function.
+    ///
+    /// By default `hmac-sha256` algorithm is used, the allowed clock skew
+    /// is 5 minutes and `(request-target) (created)` have to be signed.
+    pub fn new<F>(secrets: F) -> HmacValidator
+    where
+        F: Fn(&str) -> Option<Vec<u8>> + Send + Sync + 'static,
+    {
+        HmacValidator {
+            secrets: Arc::new(secrets),
+            algorithm: HmacAlgorithm::HmacSha256,
+            max_skew: Duration::from_secs(300),
+            required_headers: vec![
+                "(request-target)".to_string(),
+                "(created)".to_string(),
+            ],
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Set the HMAC algorithm.
+    ///
+    /// Credentials with the other `algorithm` parameter are rejected,
+    /// except for the `hs2019` one, which defers the choice to the server.
+    pub fn algorithm(mut self, algorithm: HmacAlgorithm) -> HmacValidator {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Set the allowed clock skew for the `created` and `expires`
+    /// timestamps, it also limits the age of the signatures.
+    pub fn max_skew(mut self, value: Duration) -> HmacValidator {
+        self.max_skew = value;
+        self
+    }
+
+    /// Set the headers, including the pseudo-headers, which each
+    /// signature has to cover, ex. `&["(request-target)", "(created)",
+    /// "digest"]`; names are case-insensitive.
+    pub fn required_headers<I>(mut self, names: I) -> HmacValidator
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.required_headers = names
+            .into_iter()
+            .map(|name| name.as_ref().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Set the time source, ex. the [`ManualClock`] for tests.
+    ///
+    /// [`ManualClock`]: ../../clock/struct.ManualClock.html
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> HmacValidator {
+        self.clock = clock;
+        self
+    }
+
+    /// Validates the `credentials`, suitable to be called
+    /// from the `HttpAuthentication::with_fn` validation callback.
+    pub fn validate(
+        &self,
+        req: ServiceRequest,
+        credentials: HmacAuth,
+    ) -> FutureResult<ServiceRequest, Error> {
+        let config = req
+            .app_data::<Config>()
+            .map(|config| config.get_ref().clone())
+            .unwrap_or_default();
+        if self.verify(&req, credentials.credentials(), &config) {
+            future::ok(req)
+        } else {
+            future::err(AuthenticationError::from(config).into())
+        }
+    }
+
+    fn verify(
+        &self,
+        req: &ServiceRequest,
+        credentials: &authorization::Signature,
+        config: &Config,
+    ) -> bool {
+        let signed = credentials.signed_headers();
+        let configured = config.0.headers.as_deref().unwrap_or("");
+        if !self
+            .required_headers
+            .iter()
+            .map(String::as_str)
+            .chain(configured.split_whitespace())
+            .all(|name| signed.iter().any(|s| s.eq_ignore_ascii_case(name)))
+        {
+            return false;
+        }
+
+        match credentials.algorithm_name() {
+            Some(name)
+                if name != self.algorithm.as_str() && name != "hs2019" =>
+            {
+                return false;
+            }
+            _ => (),
+        }
+
+        let now = self
+            .clock
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_secs());
+        let max_skew = self.max_skew.as_secs();
+        let (created, expires) =
+            (credentials.created_at(), credentials.expires_at());
+        if created.is_none() && expires.is_none()
+            || created.is_some_and(|created| {
+                created > now.saturating_add(max_skew)
+                    || now.saturating_sub(created) > max_skew
+            })
+            || expires
+                .is_some_and(|expires| expires.saturating_add(max_skew) < now)
+        {
+            return false;
+        }
+
+        let secret = match (self.secrets)(credentials.key_id()) {
+            Some(secret) => secret,
+            None => return false,
+        };
+
+        match signing_string(req, credentials) {
+            Some(message) => self.algorithm.verify(
+                &secret,
+                message.as_bytes(),
+                credentials.signature(),
+            ),
+            None => false,
+        }
+    }
+}
+
+/// Reconstructs the signing string, `None` is returned
+/// if any of the signed headers is missing.
+fn signing_string(
+    req: &ServiceRequest,
+    credentials: &authorization::Signature,
+) -> Option<String> {
+    let mut lines = Vec::with_capacity(credentials.signed_headers().len());
+    for name in credentials.signed_headers() {
+        let value = match name.as_str() {
+            "(request-target)" => format!(
+                "{} {}",
+                req.method().as_str().to_lowercase(),
+                req.uri()
+                    .path_and_query()
+                    .map_or_else(|| req.path(), |target| target.as_str())
+            ),
+            "(created)" => credentials.created_at()?.to_string(),
+            "(expires)" => credentials.expires_at()?.to_string(),
+            name => {
+                let values = req
+                    .headers()
+                    .get_all(name)
+                    .map(|value| value.to_str())
+                    .collect::<Result<Vec<_>, _>>()
+                    .ok()?;
+                if values.is_empty() {
+                    return None;
+                }
+                values.join(", ")
+            }
+        };
+        lines.push(format!("{}: {}", name, value));
+    }
+
+    Some(lines.join("\n"))
+}
+
+impl fmt::Debug for HmacValidator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HmacValidator")
+            .field("algorithm", &self.algorithm)
+            .field("max_skew", &self.max_skew)
+            .field("required_headers", &self.required_headers)
+            .field("clock", &self.clock)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use actix_web::http::header::{IntoHeaderValue, AUTHORIZATION};
+    use actix_web::test::TestRequest;
+    use actix_web::ResponseError;
+    use futures::Future;
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    use super::{Config, HmacAlgorithm, HmacAuth, HmacValidator};
+    use crate::clock::ManualClock;
+    use crate::extractors::AuthExtractor;
+    use crate::headers::authorization::Signature;
+
+    const SIGNING_STRING: &str = "(request-target): post /foo?param=value\n\
+                                  (created): 1000\n\
+                                  host: example.com";
+
+    fn sign(message: &str) -> Vec<u8> {
+        let mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.chain_update(message).finalize().into_bytes().to_vec()
+    }
+
+    fn validator(now: u64) -> HmacValidator {
+        let clock = ManualClock::new(UNIX_EPOCH + Duration::from_secs(now));
+
+        HmacValidator::new(|key_id| match key_id {
+            "key-1" => Some(b"secret".to_vec()),
+            _ => None,
+        })
+        .max_skew(Duration::from_secs(30))
+        .clock(Arc::new(clock))
+    }
+
+    fn validate(
+        validator: &HmacValidator,
+        credentials: Signature,
+    ) -> Result<(), u16> {
+        let req = TestRequest::post()
+            .uri("/foo?param=value")
+            .header("Host", "example.com")
+            .header(AUTHORIZATION, credentials.try_into().unwrap())
+            .data(Config::default().realm("Example"))
+            .to_srv_request();
+        let credentials = HmacAuth::from_service_request(&req).unwrap();
+
+        validator
+            .validate(req, credentials)
+            .wait()
+            .map(|_| ())
+            .map_err(|e| e.as_response_error().error_response().status().into())
+    }
+
+    fn credentials(key_id: &str, signature: Vec<u8>) -> Signature {
+        Signature::new(key_id, signature)
+            .algorithm("hmac-sha256")
+            .headers(&["(request-target)", "(created)", "host"])
+            .created(1000)
+    }
+
+    #[test]
+    fn test_valid_signature() {
+        let signature = sign(SIGNING_STRING);
+
+        assert!(
+            validate(&validator(1000), credentials("key-1", signature)).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_invalid_signature() {
+        let validator = validator(1000);
+        let mut other = sign(SIGNING_STRING);
+        other[0] ^= 1;
+
+        assert_eq!(validate(&validator, credentials("key-1", other)), Err(401));
+        assert_eq!(
+            validate(&validator, credentials("key-2", sign(SIGNING_STRING))),
+            Err(401)
+        );
+        assert_eq!(
+            validate(
+                &validator.clone().algorithm(HmacAlgorithm::HmacSha512),
+                credentials("key-1", sign(SIGNING_STRING))
+            ),
+            Err(401)
+        );
+    }
+
+    #[test]
+    fn test_clock_skew() {
+        let signature = sign(SIGNING_STRING);
+
+        for now in &[970, 1030] {
+            assert!(validate(
+                &validator(*now),
+                credentials("key-1", signature.clone())
+            )
+            .is_ok());
+        }
+        for now in &[969, 1031, 100_000] {
+            assert_eq!(
+                validate(
+                    &validator(*now),
+                    credentials("key-1", signature.clone())
+                ),
+                Err(401)
+            );
+        }
+        assert_eq!(
+            validate(
+                &validator(1100),
+                credentials("key-1", signature).expires(1050)
+            ),
+            Err(401)
+        );
+    }
+
+    #[test]
+    fn test_missing_timestamps() {
+        let message = "(request-target): post /foo?param=value\n\
+                       host: example.com";
+        let credentials = Signature::new("key-1", sign(message))
+            .headers(&["(request-target)", "host"]);
+        let validator = validator(1000).required_headers(&["(request-target)"]);
+
+        assert_eq!(validate(&validator, credentials), Err(401));
+    }
+
+    #[test]
+    fn test_required_headers() {
+        let created = "(created): 1000\nhost: example.com";
+        let without_target = Signature::new("key-1", sign(created))
+            .headers(&["(created)", "host"])
+            .created(1000);
+        assert_eq!(
+            validate(&validator(1000), without_target.clone()),
+            Err(401)
+        );
+        assert!(validate(
+            &validator(1000).required_headers(&["(Created)"]),
+            without_target
+        )
+        .is_ok());
+
+        // Headers listed in the challenge are required too
+        let req = TestRequest::post()
+            .uri("/foo?param=value")
+            .header("Host", "example.com")
+            .header(
+                AUTHORIZATION,
+                credentials("key-1", sign(SIGNING_STRING))
+                    .try_into()
+                    .unwrap(),
+            )
+            .data(Config::default().headers("(request-target) digest"))
+            .to_srv_request();
+        let credentials = HmacAuth::from_service_request(&req).unwrap();
+        assert!(validator(1000).validate(req, credentials).wait().is_err());
+    }
+
+    #[test]
+    fn test_missing_credentials() {
+        let req = TestRequest::default()
+            .data(Config::default().headers("(created) host"))
+            .to_srv_request();
+        let error = HmacAuth::from_service_request(&req).unwrap_err();
+
+        assert_eq!(
+            error
+                .error_response()
+                .headers()
+                .get("WWW-Authenticate")
+                .unwrap(),
+            "Signature headers=\"(created) host\""
+        );
+    }
+}
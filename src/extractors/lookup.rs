@@ -0,0 +1,275 @@
+use actix_web::http::header::{
+    HeaderMap, HeaderName, HeaderValue, AUTHORIZATION,
+};
+
+use crate::headers::authorization::{split_scheme, ParseError};
+
+/// Limits for all of the headers, which may carry the credentials,
+/// see [`ErrorOptions::limits`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web_httpauth::extractors::{AuthLimits, ErrorOptions};
+/// # use actix_web_httpauth::extractors::bearer::Config;
+/// let config = Config::default()
+///     .header_priority(vec!["X-Forwarded-Authorization", "Authorization"])
+///     .error_options(ErrorOptions::default().limits(AuthLimits {
+///         max_headers: 2,
+///         max_total_bytes: 8192,
+///     }));
+/// ```
+///
+/// [`ErrorOptions::limits`]: ./struct.ErrorOptions.html#method.limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthLimits {
+    /// Maximum number of the present headers, counting the repeated ones.
+    pub max_headers: usize,
+    /// Maximum combined length in bytes of the present headers values.
+    pub max_total_bytes: usize,
+}
+
+/// Describes which request headers are carrying the credentials.
+///
+/// Headers are tried in the configured order, the first present one
+/// is used.
+#[derive(Debug, Clone)]
+pub(crate) struct HeaderLookup {
+    names: Vec<HeaderName>,
+    fallthrough: bool,
+}
+
+impl HeaderLookup {
+    /// Replaces the headers list with the provided `names`.
+    ///
+    /// ## Panics
+    ///
+    /// If any of `names` is not a valid header name.
+    pub fn set_names<I>(&mut self, names: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.names = names
+            .into_iter()
+            .map(|name| {
+                let name = name.as_ref();
+                HeaderName::from_bytes(name.as_bytes()).unwrap_or_else(|_| {
+                    panic!("Invalid header name: {:?}", name)
+                })
+            })
+            .collect();
+    }
+
+    /// Allows to proceed to the next header in the list
+    /// if the present one can't be parsed.
+    pub fn set_fallthrough(&mut self, value: bool) {
+        self.fallthrough = value;
+    }
+
+    /// Returns `true` if any of the present headers exceeds the `max_bytes`
+    /// length, or all of them are exceeding the total `limits`.
+    pub fn is_oversized(
+        &self,
+        headers: &HeaderMap,
+        max_bytes: Option<usize>,
+        limits: Option<AuthLimits>,
+    ) -> bool {
+        let present = || {
+            self.names
+                .iter()
+                .flat_map(move |name| headers.get_all(name))
+        };
+
+        if let Some(max_bytes) = max_bytes {
+            if present().any(|header| header.len() > max_bytes) {
+                return true;
+            }
+        }
+
+        match limits {
+            Some(limits) => {
+                present().count() > limits.max_headers
+                    || present().map(HeaderValue::len).sum::<usize>()
+                        > limits.max_total_bytes
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the first present header carries
+    /// the credentials for some other scheme than the `expected` one.
+    pub fn has_foreign_scheme(
+        &self,
+        headers: &HeaderMap,
+        expected: &str,
+    ) -> bool {
+        let header = match self.names.iter().find_map(|name| headers.get(name))
+        {
+            Some(header) => header,
+            None => return false,
+        };
+
+        match header.to_str() {
+            Ok(value) => {
+                let (scheme, _credentials) = split_scheme(value);
+                !scheme.is_empty() && !scheme.eq_ignore_ascii_case(expected)
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Finds and parses the first present header.
+    ///
+    /// Returns [`ParseError::MissingHeader`] if none of the headers
+    /// are present, or the parse error of the found one if it is malformed
+    /// and fallthrough is not allowed. If all present headers are malformed,
+    /// the last error is returned.
+    pub fn find<T, F>(
+        &self,
+        headers: &HeaderMap,
+        parse: F,
+    ) -> Result<T, ParseError>
+    where
+        F: Fn(&HeaderValue) -> Result<T, ParseError>,
+    {
+        let mut error = ParseError::MissingHeader;
+        for name in &self.names {
+            let header = match headers.get(name) {
+                Some(header) => header,
+                None => continue,
+            };
+
+            match parse(header) {
+                Ok(value) => return Ok(value),
+                Err(err) if self.fallthrough => error = err,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(error)
+    }
+}
+
+impl Default for HeaderLookup {
+    fn default() -> Self {
+        HeaderLookup {
+            names: vec![AUTHORIZATION],
+            fallthrough: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::{
+        HeaderMap, HeaderName, HeaderValue, AUTHORIZATION,
+    };
+
+    use super::HeaderLookup;
+    use crate::headers::authorization::{Bearer, ParseError, Scheme};
+
+    fn lookup(fallthrough: bool) -> HeaderLookup {
+        let mut lookup = HeaderLookup::default();
+        lookup.set_names(vec![
+            "Authorization",
+            "X-Forwarded-Authorization",
+            "X-Original-Authorization",
+        ]);
+        lookup.set_fallthrough(fallthrough);
+        lookup
+    }
+
+    #[test]
+    fn test_priority() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("x-original-authorization"),
+            HeaderValue::from_static("Bearer original"),
+        );
+        headers.insert(
+            HeaderName::from_static("x-forwarded-authorization"),
+            HeaderValue::from_static("Bearer forwarded"),
+        );
+
+        let bearer = lookup(false).find(&headers, Bearer::parse).unwrap();
+        assert_eq!(bearer.token(), "forwarded");
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer main"));
+        let bearer = lookup(false).find(&headers, Bearer::parse).unwrap();
+        assert_eq!(bearer.token(), "main");
+    }
+
+    #[test]
+    fn test_all_absent() {
+        let headers = HeaderMap::new();
+
+        match lookup(false).find(&headers, Bearer::parse) {
+            Err(ParseError::MissingHeader) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_malformed_does_not_fall_through() {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Basic"));
+        headers.insert(
+            HeaderName::from_static("x-forwarded-authorization"),
+            HeaderValue::from_static("Bearer forwarded"),
+        );
+
+        match lookup(false).find(&headers, Bearer::parse) {
+            Err(ParseError::Invalid) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_malformed_falls_through() {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Basic"));
+        headers.insert(
+            HeaderName::from_static("x-forwarded-authorization"),
+            HeaderValue::from_static("Bearer forwarded"),
+        );
+
+        let bearer = lookup(true).find(&headers, Bearer::parse).unwrap();
+        assert_eq!(bearer.token(), "forwarded");
+    }
+
+    #[test]
+    fn test_oversized() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("x-forwarded-authorization"),
+            HeaderValue::from_static("Bearer forwarded"),
+        );
+
+        let lookup = lookup(false);
+        assert!(!lookup.is_oversized(&headers, None, None));
+        assert!(!lookup.is_oversized(&headers, Some(16), None));
+        assert!(lookup.is_oversized(&headers, Some(15), None));
+    }
+
+    #[test]
+    fn test_foreign_scheme() {
+        let mut headers = HeaderMap::new();
+        assert!(!lookup(false).has_foreign_scheme(&headers, "Bearer"));
+
+        headers.insert(
+            HeaderName::from_static("x-forwarded-authorization"),
+            HeaderValue::from_static("Weird xyz"),
+        );
+        assert!(lookup(false).has_foreign_scheme(&headers, "Bearer"));
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("bearer xyz"));
+        assert!(!lookup(false).has_foreign_scheme(&headers, "Bearer"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_header_name() {
+        HeaderLookup::default().set_names(vec!["Invalid header"]);
+    }
+}
@@ -1,16 +1,33 @@
 //! Type-safe authentication information extractors
 
-use actix_web::dev::ServiceRequest;
-use actix_web::Error;
-use futures::IntoFuture;
+use actix_web::dev::{RequestHead, ServiceRequest};
+use actix_web::http::header::WWW_AUTHENTICATE;
+use actix_web::{Error, HttpRequest, HttpResponse, ResponseError};
+use futures::future::{self, FutureResult, Then};
+use futures::{Future, IntoFuture};
 
 pub mod basic;
 pub mod bearer;
 mod config;
-mod errors;
+pub mod credentials;
+pub mod dispatch;
+mod error_options;
+pub(crate) mod errors;
+pub(crate) mod form_login;
+pub mod hmac;
+mod lookup;
+mod openapi;
+pub mod skip;
+pub mod timestamp_key;
+pub mod token;
 
 pub use self::config::AuthExtractorConfig;
+pub use self::credentials::Credentials;
+pub use self::error_options::ErrorOptions;
 pub use self::errors::AuthenticationError;
+pub use self::lookup::AuthLimits;
+pub use self::openapi::SecuritySchemeDescriptor;
+use crate::utils;
 
 /// Trait implemented by types that can extract
 /// HTTP authentication scheme credentials from the request.
@@ -30,4 +47,281 @@ pub trait AuthExtractor: Sized {
 
     /// Parse the authentication credentials from the actix' `ServiceRequest`.
     fn from_service_request(req: &ServiceRequest) -> Self::Future;
+
+    /// Returns the identity claimed by the credentials, ex. the user ID,
+    /// as reported in the [`AuditEvent`]s.
+    ///
+    /// Credentials which are secrets on their own, ex. the bearer tokens,
+    /// are not reporting any subject.
+    ///
+    /// [`AuditEvent`]: ../middleware/struct.AuditEvent.html
+    fn subject(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns `true` if the credentials may come in the login form body,
+    /// see [`basic::Config::form_login`].
+    ///
+    /// Middleware buffers the form body only for such extractors.
+    ///
+    /// [`basic::Config::form_login`]: ./basic/struct.Config.html#method.form_login
+    fn uses_form_login() -> bool {
+        false
+    }
+
+    /// Returns `true` if the bearer token may come in the form body,
+    /// see [`bearer::Config::allow_form_body`].
+    ///
+    /// Middleware buffers the form body only for such extractors.
+    ///
+    /// [`bearer::Config::allow_form_body`]: ./bearer/struct.Config.html#method.allow_form_body
+    fn uses_form_token() -> bool {
+        false
+    }
+}
+
+/// Trait implemented by the credentials types which are borrowing
+/// from the request instead of allocating the owned strings,
+/// ex. [`basic::BasicAuthRef`] and [`bearer::BearerAuthRef`].
+///
+/// Borrowed credentials can't outlive the request, so they are usable
+/// with the synchronous validators only, see
+/// [`HttpAuthentication::basic_ref`] and
+/// [`HttpAuthentication::bearer_ref`]. Owned [`AuthExtractor`] types
+/// should be used by the validators which are storing the credentials.
+///
+/// [`basic::BasicAuthRef`]: ./basic/struct.BasicAuthRef.html
+/// [`bearer::BearerAuthRef`]: ./bearer/struct.BearerAuthRef.html
+/// [`HttpAuthentication::basic_ref`]: ../middleware/struct.HttpAuthentication.html#method.basic_ref
+/// [`HttpAuthentication::bearer_ref`]: ../middleware/struct.HttpAuthentication.html#method.bearer_ref
+/// [`AuthExtractor`]: ./trait.AuthExtractor.html
+pub trait AuthExtractorRef<'a>: Sized {
+    /// The associated error which can be returned.
+    type Error: Into<Error>;
+
+    /// Parse the authentication credentials from the `req`.
+    ///
+    /// Credentials which have to be decoded first, ex. the "Basic" ones,
+    /// are decoded into the `scratch` buffer, so it can be reused
+    /// between the calls.
+    fn from_service_request_ref(
+        req: &'a ServiceRequest,
+        scratch: &'a mut Vec<u8>,
+    ) -> Result<Self, Self::Error>;
+}
+
+/// Trait implemented by the credentials types which are reporting
+/// their authentication assurance level, see
+/// [`HttpAuthentication::require_level`].
+///
+/// Higher level means the stronger authentication method; built-in
+/// extractors are reporting `1` for the API keys, ex. "Basic" and "Token"
+/// schemes, and `2` for the "Bearer" tokens and the signed requests.
+/// Implement it for the own credentials type to express the stronger
+/// methods, ex. the bearer token bound to the client certificate.
+///
+/// [`HttpAuthentication::require_level`]: ../middleware/struct.HttpAuthentication.html#method.require_level
+pub trait AssuranceLevel {
+    /// Returns the assurance level of the credentials,
+    /// `0` means no authentication at all.
+    fn assurance_level(&self) -> u8;
+}
+
+/// No credentials, extraction always succeeds.
+///
+/// Useful for the validators which are extracting
+/// the credentials on their own.
+impl AuthExtractor for () {
+    type Error = Error;
+    type Future = FutureResult<(), Error>;
+
+    fn from_service_request(_req: &ServiceRequest) -> Self::Future {
+        future::ok(())
+    }
+}
+
+impl AssuranceLevel for () {
+    fn assurance_level(&self) -> u8 {
+        0
+    }
+}
+
+/// Optional credentials, extraction never fails:
+/// `None` is returned instead of the `T` extractor error.
+impl<T> AuthExtractor for Option<T>
+where
+    T: AuthExtractor,
+{
+    type Error = T::Error;
+    type Future = Then<
+        <T::Future as IntoFuture>::Future,
+        FutureResult<Self, Self::Error>,
+        fn(Result<T, T::Error>) -> FutureResult<Self, Self::Error>,
+    >;
+
+    fn from_service_request(req: &ServiceRequest) -> Self::Future {
+        T::from_service_request(req)
+            .into_future()
+            .then(|result| future::ok(result.ok()))
+    }
+
+    fn subject(&self) -> Option<&str> {
+        self.as_ref().and_then(AuthExtractor::subject)
+    }
+
+    fn uses_form_login() -> bool {
+        T::uses_form_login()
+    }
+
+    fn uses_form_token() -> bool {
+        T::uses_form_token()
+    }
+}
+
+impl<T: AssuranceLevel> AssuranceLevel for Option<T> {
+    fn assurance_level(&self) -> u8 {
+        self.as_ref().map_or(0, AssuranceLevel::assurance_level)
+    }
+}
+
+/// Extracts the credentials `T` from the borrowed request,
+/// without running any validator.
+///
+/// Request is neither consumed nor altered, which is useful
+/// for the diagnostic endpoints, ex. `/auth/check`, and for testing.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::test::{self, TestRequest};
+/// # use actix_web_httpauth::extractors::bearer::BearerAuth;
+/// # use actix_web_httpauth::extractors::try_extract;
+/// let req = TestRequest::with_header("Authorization", "Bearer mF_9.B5f-4.1JqM")
+///     .to_srv_request();
+/// let credentials = test::block_on(try_extract::<BearerAuth>(&req)).unwrap();
+///
+/// assert_eq!(credentials.token(), "mF_9.B5f-4.1JqM");
+/// ```
+pub fn try_extract<T>(req: &ServiceRequest) -> <T::Future as IntoFuture>::Future
+where
+    T: AuthExtractor,
+{
+    T::from_service_request(req).into_future()
+}
+
+/// Builds the standard challenge response, ex. when the handler decides
+/// that the resource requires the step-up authentication.
+///
+/// Response has the `HTTP 401` status code and the challenges
+/// formed from the [`basic::Config`] and [`bearer::Config`] instances
+/// fetched from the app data, in this order. If none is configured,
+/// the default `Bearer` challenge is used.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::{HttpRequest, HttpResponse};
+/// # use actix_web_httpauth::extractors::respond_unauthorized;
+/// fn report(req: HttpRequest) -> HttpResponse {
+///     if req.headers().contains_key("X-Step-Up") {
+///         HttpResponse::Ok().finish()
+///     } else {
+///         respond_unauthorized(&req)
+///     }
+/// }
+/// ```
+///
+/// [`basic::Config`]: ./basic/struct.Config.html
+/// [`bearer::Config`]: ./bearer/struct.Config.html
+pub fn respond_unauthorized(req: &HttpRequest) -> HttpResponse {
+    challenge_response(
+        req.head(),
+        req.app_data::<basic::Config>(),
+        req.app_data::<bearer::Config>(),
+    )
+}
+
+/// Builds the [`respond_unauthorized`] response from the provided configs.
+///
+/// [`respond_unauthorized`]: ./fn.respond_unauthorized.html
+pub(crate) fn challenge_response(
+    head: &RequestHead,
+    basic: Option<&basic::Config>,
+    bearer: Option<&bearer::Config>,
+) -> HttpResponse {
+    let basic =
+        basic.map(|config| config.challenge_error(head).error_response());
+    let bearer =
+        bearer.map(|config| config.challenge_error(head).error_response());
+
+    match (basic, bearer) {
+        (Some(mut response), Some(bearer)) => {
+            utils::append_header_values(
+                response.headers_mut(),
+                WWW_AUTHENTICATE,
+                bearer.headers().get_all(WWW_AUTHENTICATE).cloned(),
+            );
+            response
+        }
+        (Some(response), None) | (None, Some(response)) => response,
+        (None, None) => bearer::Config::default()
+            .challenge_error(head)
+            .error_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+
+    use super::basic::{self, BasicAuth};
+    use super::{bearer, respond_unauthorized, try_extract};
+
+    #[test]
+    fn test_try_extract() {
+        let req = TestRequest::with_header(
+            AUTHORIZATION,
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+        )
+        .to_srv_request();
+
+        let auth = test::block_on(try_extract::<BasicAuth>(&req)).unwrap();
+        assert_eq!(auth.user_id(), "Aladdin");
+        assert!(req.headers().contains_key(AUTHORIZATION));
+
+        let req = TestRequest::default().to_srv_request();
+        assert!(test::block_on(try_extract::<BasicAuth>(&req)).is_err());
+    }
+
+    #[test]
+    fn test_optional_credentials() {
+        let req = TestRequest::default().to_srv_request();
+        let auth = test::block_on(try_extract::<Option<BasicAuth>>(&req));
+
+        assert!(auth.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_respond_unauthorized() {
+        let req = TestRequest::default().to_http_request();
+        let response = respond_unauthorized(&req);
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers().get(WWW_AUTHENTICATE).unwrap(), "Bearer");
+
+        let req = TestRequest::default()
+            .data(basic::Config::default().realm("Restricted"))
+            .data(bearer::Config::default().scope("admin"))
+            .to_http_request();
+        let response = respond_unauthorized(&req);
+        let challenges = response
+            .headers()
+            .get_all(WWW_AUTHENTICATE)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            challenges,
+            vec!["Basic realm=\"Restricted\"", "Bearer scope=\"admin\""]
+        );
+    }
 }
@@ -0,0 +1,51 @@
+use std::borrow::Cow;
+
+/// Machine-readable description of the authentication scheme
+/// enforced by the extractor, suitable for the OpenAPI
+/// [Security Scheme Object] generation.
+///
+/// Describes the scheme of the `http` type.
+///
+/// [Security Scheme Object]: https://swagger.io/specification/#securitySchemeObject
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SecuritySchemeDescriptor {
+    scheme: &'static str,
+    bearer_format: Option<Cow<'static, str>>,
+    scopes: Vec<String>,
+}
+
+impl SecuritySchemeDescriptor {
+    pub(crate) fn new(
+        scheme: &'static str,
+        bearer_format: Option<Cow<'static, str>>,
+        scopes: Option<&str>,
+    ) -> SecuritySchemeDescriptor {
+        SecuritySchemeDescriptor {
+            scheme,
+            bearer_format,
+            scopes: scopes
+                .map(|scopes| {
+                    scopes.split_whitespace().map(str::to_string).collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the HTTP authentication scheme name,
+    /// as registered in the [IANA registry], ex. `"basic"` or `"bearer"`.
+    ///
+    /// [IANA registry]: https://www.iana.org/assignments/http-authschemes/http-authschemes.xhtml
+    pub fn scheme(&self) -> &'static str {
+        self.scheme
+    }
+
+    /// Returns the hint about the bearer token format, ex. `"JWT"`.
+    pub fn bearer_format(&self) -> Option<&str> {
+        self.bearer_format.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Returns the scopes required by the challenge.
+    pub fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+}
@@ -0,0 +1,175 @@
+//! Extractor which skips authentication for some request methods
+
+use actix_web::dev::ServiceRequest;
+use actix_web::http::Method;
+use futures::future::{self, Either, FutureResult, Map};
+use futures::{Future, IntoFuture};
+
+use super::{AssuranceLevel, AuthExtractor};
+
+/// [`SkipMethods`] extractor configuration.
+///
+/// By default only the CORS preflight `OPTIONS` requests
+/// are not authenticated.
+///
+/// [`SkipMethods`]: ./enum.SkipMethods.html
+#[derive(Debug, Clone)]
+pub struct Config {
+    methods: Vec<Method>,
+}
+
+impl Config {
+    /// Set the request methods which should not be authenticated.
+    pub fn methods<I>(mut self, methods: I) -> Config
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        self.methods = methods.into_iter().collect();
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            methods: vec![Method::OPTIONS],
+        }
+    }
+}
+
+/// Wrapper for the `T` extractor, which does not require credentials
+/// for the requests with methods listed in the [`Config`].
+///
+/// Since CORS preflight requests never carry the credentials,
+/// this allows to protect the routes with middleware
+/// without breaking the CORS.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::{App, Error};
+/// # use actix_web::dev::ServiceRequest;
+/// # use futures::future::{self, FutureResult};
+/// # use actix_web_httpauth::middleware::HttpAuthentication;
+/// # use actix_web_httpauth::extractors::bearer::BearerAuth;
+/// # use actix_web_httpauth::extractors::skip::{Config, SkipMethods};
+/// fn validator(
+///     req: ServiceRequest,
+///     credentials: SkipMethods<BearerAuth>,
+/// ) -> FutureResult<ServiceRequest, Error> {
+///     match credentials {
+///         SkipMethods::Anonymous => future::ok(req),
+///         SkipMethods::Authenticated(_bearer) => {
+///             // Validate the bearer token here
+///             future::ok(req)
+///         }
+///     }
+/// }
+///
+/// let app = App::new()
+///     .data(Config::default())
+///     .wrap(HttpAuthentication::with_fn(validator));
+/// ```
+///
+/// [`Config`]: ./struct.Config.html
+#[derive(Debug, Clone)]
+pub enum SkipMethods<T> {
+    /// Request method is one of the skipped ones,
+    /// the credentials were not extracted.
+    Anonymous,
+    /// Credentials extracted by the inner extractor.
+    Authenticated(T),
+}
+
+impl<T> AuthExtractor for SkipMethods<T>
+where
+    T: AuthExtractor,
+{
+    type Error = T::Error;
+    type Future = Either<
+        FutureResult<Self, Self::Error>,
+        Map<<T::Future as IntoFuture>::Future, fn(T) -> Self>,
+    >;
+
+    fn from_service_request(req: &ServiceRequest) -> Self::Future {
+        let skip = match req.app_data::<Config>() {
+            Some(config) => config.methods.contains(req.method()),
+            None => Config::default().methods.contains(req.method()),
+        };
+
+        if skip {
+            Either::A(future::ok(SkipMethods::Anonymous))
+        } else {
+            let f = T::from_service_request(req).into_future();
+            Either::B(f.map(SkipMethods::Authenticated as fn(T) -> Self))
+        }
+    }
+
+    fn subject(&self) -> Option<&str> {
+        match self {
+            SkipMethods::Anonymous => None,
+            SkipMethods::Authenticated(credentials) => credentials.subject(),
+        }
+    }
+
+    fn uses_form_login() -> bool {
+        T::uses_form_login()
+    }
+
+    fn uses_form_token() -> bool {
+        T::uses_form_token()
+    }
+}
+
+impl<T: AssuranceLevel> AssuranceLevel for SkipMethods<T> {
+    fn assurance_level(&self) -> u8 {
+        match self {
+            SkipMethods::Anonymous => 0,
+            SkipMethods::Authenticated(credentials) => {
+                credentials.assurance_level()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::AUTHORIZATION;
+    use actix_web::http::Method;
+    use actix_web::test::TestRequest;
+    use futures::Future;
+
+    use super::{Config, SkipMethods};
+    use crate::extractors::bearer::BearerAuth;
+    use crate::extractors::AuthExtractor;
+
+    #[test]
+    fn test_skip_options_by_default() {
+        let req = TestRequest::default()
+            .method(Method::OPTIONS)
+            .to_srv_request();
+
+        let result =
+            SkipMethods::<BearerAuth>::from_service_request(&req).wait();
+        assert!(matches!(result, Ok(SkipMethods::Anonymous)));
+    }
+
+    #[test]
+    fn test_delegates_other_methods() {
+        let req = TestRequest::default().to_srv_request();
+        let result =
+            SkipMethods::<BearerAuth>::from_service_request(&req).wait();
+        assert!(result.is_err());
+
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer token")
+            .method(Method::OPTIONS)
+            .data(Config::default().methods(vec![Method::HEAD]))
+            .to_srv_request();
+        let result =
+            SkipMethods::<BearerAuth>::from_service_request(&req).wait();
+        assert!(matches!(
+            result,
+            Ok(SkipMethods::Authenticated(ref bearer)) if bearer.token() == "token"
+        ));
+    }
+}
@@ -0,0 +1,345 @@
+//! Extractor and validator for the "TSKey" HTTP Authentication Scheme,
+//! the pre-shared key HMAC of the request timestamp
+
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use actix_web::dev::{Payload, ServiceRequest};
+use actix_web::http::header::Header;
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+use futures::future::{self, FutureResult};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::config::AuthExtractorConfig;
+use super::errors::AuthenticationError;
+use super::{AssuranceLevel, AuthExtractor};
+use crate::clock::{Clock, SystemClock};
+use crate::headers::authorization::{self, Authorization};
+use crate::headers::www_authenticate::timestamp_key::{self, TimestampKey};
+
+type SecretLookup = dyn Fn(&str) -> Option<Vec<u8>> + Send + Sync;
+
+/// [`TimestampKeyAuth`](./struct.TimestampKeyAuth.html) extractor
+/// configuration, used for
+/// [`TimestampKeyValidator`](./struct.TimestampKeyValidator.html)
+/// errors too.
+#[derive(Debug, Clone, Default)]
+pub struct Config(TimestampKey);
+
+impl Config {
+    /// Set challenge `realm` attribute.
+    pub fn realm<T: Into<Cow<'static, str>>>(mut self, value: T) -> Config {
+        self.0 = self.0.realm(value);
+        self
+    }
+}
+
+impl AsRef<TimestampKey> for Config {
+    fn as_ref(&self) -> &TimestampKey {
+        &self.0
+    }
+}
+
+impl AuthExtractorConfig for Config {
+    type Inner = TimestampKey;
+
+    fn into_inner(self) -> Self::Inner {
+        self.0
+    }
+}
+
+/// Extractor for the `TSKey` credentials.
+///
+/// Extractor only parses the `Authorization` header, timestamp and
+/// signature are verified by the
+/// [`TimestampKeyValidator`](./struct.TimestampKeyValidator.html).
+#[derive(Debug, Clone)]
+pub struct TimestampKeyAuth(authorization::TimestampKey);
+
+impl TimestampKeyAuth {
+    /// Returns the key ID provided by client.
+    pub fn key_id(&self) -> &str {
+        self.0.key_id()
+    }
+
+    /// Returns the parsed credentials.
+    pub fn credentials(&self) -> &authorization::TimestampKey {
+        &self.0
+    }
+
+    fn extract<T: HttpMessage>(
+        msg: &T,
+        config: Option<&Config>,
+    ) -> Result<Self, AuthenticationError<TimestampKey>> {
+        Authorization::<authorization::TimestampKey>::parse(msg)
+            .map(|auth| TimestampKeyAuth(auth.into_scheme()))
+            .map_err(|_| {
+                AuthenticationError::from(config.cloned().unwrap_or_default())
+            })
+    }
+}
+
+impl FromRequest for TimestampKeyAuth {
+    type Config = Config;
+    type Future = Result<Self, Self::Error>;
+    type Error = AuthenticationError<TimestampKey>;
+
+    fn from_request(
+        req: &HttpRequest,
+        _payload: &mut Payload,
+    ) -> <Self as FromRequest>::Future {
+        TimestampKeyAuth::extract(req, req.app_data::<Self::Config>())
+    }
+}
+
+impl AuthExtractor for TimestampKeyAuth {
+    type Future = Result<Self, Self::Error>;
+    type Error = AuthenticationError<TimestampKey>;
+
+    fn from_service_request(req: &ServiceRequest) -> Self::Future {
+        let config = req.app_data::<Config>();
+
+        TimestampKeyAuth::extract(
+            req,
+            config.as_ref().map(|config| config.get_ref()),
+        )
+    }
+
+    fn subject(&self) -> Option<&str> {
+        Some(self.key_id())
+    }
+}
+
+impl AssuranceLevel for TimestampKeyAuth {
+    fn assurance_level(&self) -> u8 {
+        2
+    }
+}
+
+/// Ready-made validator for the `HttpAuthentication::with_fn` middleware,
+/// which verifies the [`TimestampKeyAuth`] credentials.
+///
+/// Timestamp has to be within the allowed window around the current
+/// time, otherwise the request is rejected with the `HTTP 401` error
+/// and the `error="stale"` challenge attribute. Signature is the
+/// HMAC-SHA256 of the decimal timestamp, signed with the secret returned
+/// by the lookup function for the `keyId` parameter; if it does not match
+/// or the key is unknown, `error="invalid"` is used instead. Challenge is
+/// formed from the [`Config`] instance fetched from the app data.
+///
+/// ## Example
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use actix_web::App;
+/// # use actix_web_httpauth::extractors::timestamp_key::TimestampKeyValidator;
+/// # use actix_web_httpauth::middleware::HttpAuthentication;
+/// let validator = TimestampKeyValidator::new(|key_id| match key_id {
+///     "svc-1" => Some(b"secret".to_vec()),
+///     _ => None,
+/// })
+/// .window(Duration::from_secs(30));
+///
+/// let app = App::new().wrap(HttpAuthentication::with_fn(move |req, credentials| {
+///     validator.validate(req, credentials)
+/// }));
+/// ```
+///
+/// [`TimestampKeyAuth`]: ./struct.TimestampKeyAuth.html
+/// [`Config`]: ./struct.Config.html
+#[derive(Clone)]
+pub struct TimestampKeyValidator {
+    secrets: Arc<SecretLookup>,
+    window: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl TimestampKeyValidator {
+    /// Creates new validator, which gets the secrets by the key ID
+    /// from the `secrets` function.
+    ///
+    /// By default the allowed window is 5 minutes.
+    pub fn new<F>(secrets: F) -> TimestampKeyValidator
+    where
+        F: Fn(&str) -> Option<Vec<u8>> + Send + Sync + 'static,
+    {
+        TimestampKeyValidator {
+            secrets: Arc::new(secrets),
+            window: Duration::from_secs(300),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Set the allowed difference between the timestamp
+    /// and the current time, in both directions.
+    pub fn window(mut self, value: Duration) -> TimestampKeyValidator {
+        self.window = value;
+        self
+    }
+
+    /// Set the time source, ex. the [`ManualClock`] for tests.
+    ///
+    /// [`ManualClock`]: ../../clock/struct.ManualClock.html
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> TimestampKeyValidator {
+        self.clock = clock;
+        self
+    }
+
+    /// Validates the `credentials`, suitable to be called
+    /// from the `HttpAuthentication::with_fn` validation callback.
+    pub fn validate(
+        &self,
+        req: ServiceRequest,
+        credentials: TimestampKeyAuth,
+    ) -> FutureResult<ServiceRequest, Error> {
+        match self.verify(credentials.credentials()) {
+            Ok(()) => future::ok(req),
+            Err(error) => {
+                let config = req
+                    .app_data::<Config>()
+                    .map(|config| config.get_ref().clone())
+                    .unwrap_or_default();
+                let challenge = config.into_inner().error(error);
+
+                future::err(AuthenticationError::new(challenge).into())
+            }
+        }
+    }
+
+    fn verify(
+        &self,
+        credentials: &authorization::TimestampKey,
+    ) -> Result<(), timestamp_key::Error> {
+        let now = self
+            .clock
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_secs());
+        if now.abs_diff(credentials.ts()) > self.window.as_secs() {
+            return Err(timestamp_key::Error::Stale);
+        }
+
+        let secret = (self.secrets)(credentials.key_id())
+            .ok_or(timestamp_key::Error::Invalid)?;
+        // Comparison is done in constant time by `verify_slice`
+        Hmac::<Sha256>::new_from_slice(&secret)
+            .ok()
+            .and_then(|mac| {
+                mac.chain_update(credentials.ts().to_string())
+                    .verify_slice(credentials.sig())
+                    .ok()
+            })
+            .ok_or(timestamp_key::Error::Invalid)
+    }
+}
+
+impl fmt::Debug for TimestampKeyValidator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TimestampKeyValidator")
+            .field("window", &self.window)
+            .field("clock", &self.clock)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use actix_web::http::header::{
+        IntoHeaderValue, AUTHORIZATION, WWW_AUTHENTICATE,
+    };
+    use actix_web::test::TestRequest;
+    use actix_web::HttpResponse;
+    use futures::Future;
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    use super::{Config, TimestampKeyAuth, TimestampKeyValidator};
+    use crate::clock::ManualClock;
+    use crate::extractors::AuthExtractor;
+    use crate::headers::authorization::TimestampKey;
+
+    fn sign(ts: u64) -> Vec<u8> {
+        let mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.chain_update(ts.to_string())
+            .finalize()
+            .into_bytes()
+            .to_vec()
+    }
+
+    fn validate(
+        now: u64,
+        credentials: TimestampKey,
+    ) -> Result<(), HttpResponse> {
+        let clock = ManualClock::new(UNIX_EPOCH + Duration::from_secs(now));
+        let validator = TimestampKeyValidator::new(|key_id| match key_id {
+            "svc-1" => Some(b"secret".to_vec()),
+            _ => None,
+        })
+        .window(Duration::from_secs(30))
+        .clock(Arc::new(clock));
+
+        let req = TestRequest::with_header(
+            AUTHORIZATION,
+            credentials.try_into().unwrap(),
+        )
+        .data(Config::default().realm("internal"))
+        .to_srv_request();
+        let credentials = TimestampKeyAuth::from_service_request(&req).unwrap();
+
+        validator
+            .validate(req, credentials)
+            .wait()
+            .map(|_| ())
+            .map_err(|e| e.as_response_error().error_response())
+    }
+
+    fn challenge(response: HttpResponse) -> String {
+        assert_eq!(response.status(), 401);
+        let challenge = response.headers().get(WWW_AUTHENTICATE).unwrap();
+        challenge.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_valid_timestamp() {
+        assert!(validate(1000, TimestampKey::new("svc-1", 1000, sign(1000)))
+            .is_ok());
+        assert!(validate(1030, TimestampKey::new("svc-1", 1000, sign(1000)))
+            .is_ok());
+        assert!(
+            validate(970, TimestampKey::new("svc-1", 1000, sign(1000))).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_stale_timestamp() {
+        for now in &[969, 1031] {
+            let error =
+                validate(*now, TimestampKey::new("svc-1", 1000, sign(1000)))
+                    .unwrap_err();
+            assert_eq!(
+                challenge(error),
+                "TSKey realm=\"internal\", error=\"stale\""
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_signature() {
+        for credentials in &[
+            TimestampKey::new("svc-1", 1000, sign(1001)),
+            TimestampKey::new("svc-2", 1000, sign(1000)),
+        ] {
+            let error = validate(1000, credentials.clone()).unwrap_err();
+            assert_eq!(
+                challenge(error),
+                "TSKey realm=\"internal\", error=\"invalid\""
+            );
+        }
+    }
+}
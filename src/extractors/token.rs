@@ -0,0 +1,151 @@
+//! Extractor for the GitHub-style "Token" HTTP Authentication Scheme
+
+use std::borrow::Cow;
+
+use actix_web::dev::{Payload, ServiceRequest};
+use actix_web::http::header::Header;
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+
+use super::config::AuthExtractorConfig;
+use super::errors::AuthenticationError;
+use super::{AssuranceLevel, AuthExtractor};
+use crate::headers::authorization::{self, Authorization};
+use crate::headers::www_authenticate::token::Token;
+
+/// [`TokenAuth`](./struct.TokenAuth.html) extractor configuration,
+/// used for [`WWW-Authenticate`] header later.
+///
+/// [`WWW-Authenticate`]: ../../headers/www_authenticate/struct.WwwAuthenticate.html
+#[derive(Debug, Clone, Default)]
+pub struct Config(Token);
+
+impl Config {
+    /// Set challenge `realm` attribute.
+    ///
+    /// The "realm" attribute indicates the scope of protection in the manner
+    /// described in HTTP/1.1 [RFC2617](https://tools.ietf.org/html/rfc2617#section-1.2).
+    pub fn realm<T: Into<Cow<'static, str>>>(mut self, value: T) -> Config {
+        self.0.realm = Some(value.into());
+        self
+    }
+}
+
+impl AsRef<Token> for Config {
+    fn as_ref(&self) -> &Token {
+        &self.0
+    }
+}
+
+impl AuthExtractorConfig for Config {
+    type Inner = Token;
+
+    fn into_inner(self) -> Self::Inner {
+        self.0
+    }
+}
+
+/// Extractor for the `Authorization: token <key>` credentials,
+/// used by GitHub and similar APIs.
+///
+/// ## Example
+///
+/// ```rust
+/// use actix_web_httpauth::extractors::token::TokenAuth;
+///
+/// fn index(auth: TokenAuth) -> String {
+///     format!("Hello, {}!", auth.key())
+/// }
+/// ```
+///
+/// If authentication fails, this extractor fetches the [`Config`] instance
+/// from the [app data] in order to properly form the `WWW-Authenticate`
+/// response header.
+///
+/// [`Config`]: ./struct.Config.html
+/// [app data]: https://docs.rs/actix-web/1.0.0/actix_web/struct.App.html#method.data
+#[derive(Debug, Clone)]
+pub struct TokenAuth(authorization::Token);
+
+impl TokenAuth {
+    /// Returns the token key.
+    pub fn key(&self) -> &str {
+        self.0.key()
+    }
+
+    pub(crate) fn extract<T: HttpMessage>(
+        msg: &T,
+        config: Option<&Config>,
+    ) -> Result<Self, AuthenticationError<Token>> {
+        Authorization::<authorization::Token>::parse(msg)
+            .map(|auth| TokenAuth(auth.into_scheme()))
+            .map_err(|_| {
+                AuthenticationError::from(config.cloned().unwrap_or_default())
+            })
+    }
+}
+
+impl FromRequest for TokenAuth {
+    type Config = Config;
+    type Future = Result<Self, Self::Error>;
+    type Error = AuthenticationError<Token>;
+
+    fn from_request(
+        req: &HttpRequest,
+        _payload: &mut Payload,
+    ) -> <Self as FromRequest>::Future {
+        TokenAuth::extract(req, req.app_data::<Self::Config>())
+    }
+}
+
+impl AuthExtractor for TokenAuth {
+    type Future = Result<Self, Self::Error>;
+    type Error = AuthenticationError<Token>;
+
+    fn from_service_request(req: &ServiceRequest) -> Self::Future {
+        let config = req.app_data::<Config>();
+
+        TokenAuth::extract(req, config.as_ref().map(|config| config.get_ref()))
+    }
+}
+
+impl AssuranceLevel for TokenAuth {
+    fn assurance_level(&self) -> u8 {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+    use actix_web::ResponseError;
+
+    use super::{Config, TokenAuth};
+    use crate::extractors::AuthExtractor;
+
+    #[test]
+    fn test_extract() {
+        let req = TestRequest::with_header(AUTHORIZATION, "token abc123")
+            .to_srv_request();
+        let auth = TokenAuth::from_service_request(&req).unwrap();
+
+        assert_eq!(auth.key(), "abc123");
+    }
+
+    #[test]
+    fn test_challenge() {
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer abc123")
+            .data(Config::default().realm("api"))
+            .to_srv_request();
+        let response = TokenAuth::from_service_request(&req)
+            .unwrap_err()
+            .error_response();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Token realm=\"api\""
+        );
+    }
+}
@@ -7,10 +7,15 @@ use actix_web::http::header;
 
 /// Possible errors while parsing `Authorization` header.
 ///
-/// Should not be used directly unless you are implementing
-/// your own [authentication scheme](./trait.Scheme.html).
+/// Extractors are keeping the original parse error in the
+/// [`AuthenticationError`], so the failure reason can be matched on,
+/// ex. to respond distinctly to the malformed credentials.
+///
+/// [`AuthenticationError`]: ../../extractors/struct.AuthenticationError.html
 #[derive(Debug)]
 pub enum ParseError {
+    /// None of the headers carrying the credentials is present
+    MissingHeader,
     /// Header value is malformed
     Invalid,
     /// Authentication scheme is missing
@@ -27,24 +32,28 @@ pub enum ParseError {
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(self.description())
-    }
-}
-
-impl Error for ParseError {
-    fn description(&self) -> &str {
         match self {
-            ParseError::Invalid => "Invalid header value",
-            ParseError::MissingScheme => "Missing authorization scheme",
-            ParseError::MissingField(_) => "Missing header field",
-            ParseError::ToStrError(e) => e.description(),
-            ParseError::Base64DecodeError(e) => e.description(),
-            ParseError::Utf8Error(e) => e.description(),
+            ParseError::MissingHeader => {
+                f.write_str("Missing authorization header")
+            }
+            ParseError::Invalid => f.write_str("Invalid header value"),
+            ParseError::MissingScheme => {
+                f.write_str("Missing authorization scheme")
+            }
+            ParseError::MissingField(field) => {
+                write!(f, "Missing header field: {}", field)
+            }
+            ParseError::ToStrError(e) => fmt::Display::fmt(e, f),
+            ParseError::Base64DecodeError(e) => fmt::Display::fmt(e, f),
+            ParseError::Utf8Error(e) => fmt::Display::fmt(e, f),
         }
     }
+}
 
+impl Error for ParseError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
+            ParseError::MissingHeader => None,
             ParseError::Invalid => None,
             ParseError::MissingScheme => None,
             ParseError::MissingField(_) => None,
@@ -6,6 +6,12 @@ mod scheme;
 
 pub use self::errors::ParseError;
 pub use self::header::Authorization;
-pub use self::scheme::basic::Basic;
+pub(crate) use self::scheme::basic::check_canonical;
+pub use self::scheme::basic::{Base64Alphabet, Basic};
 pub use self::scheme::bearer::Bearer;
+pub(crate) use self::scheme::signature::parse_params as parse_auth_params;
+pub use self::scheme::signature::Signature;
+pub(crate) use self::scheme::split_scheme;
+pub use self::scheme::timestamp_key::TimestampKey;
+pub use self::scheme::token::Token;
 pub use self::scheme::Scheme;
@@ -3,13 +3,49 @@ use std::fmt;
 use std::str;
 
 use actix_web::http::header::{
-    HeaderValue, IntoHeaderValue, InvalidHeaderValueBytes,
+    HeaderName, HeaderValue, IntoHeaderValue, InvalidHeaderValueBytes,
+    AUTHORIZATION,
 };
 use base64;
 use bytes::{BufMut, BytesMut};
 
 use crate::headers::authorization::errors::ParseError;
-use crate::headers::authorization::Scheme;
+use crate::headers::authorization::scheme::{split_scheme, Scheme};
+
+/// Alphabet used to decode the base64-encoded `Basic` credentials.
+///
+/// [RFC 7617](https://tools.ietf.org/html/rfc7617#section-2) requires the
+/// standard alphabet, `UrlSafe` exists only as a compatibility shim for the
+/// non-conforming clients which are encoding credentials with the URL-safe
+/// alphabet (`-` and `_` instead of `+` and `/`).
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub enum Base64Alphabet {
+    /// Standard base64 alphabet, as required by RFC 7617.
+    #[default]
+    Standard,
+    /// URL-safe base64 alphabet, as defined in
+    /// [RFC 4648](https://tools.ietf.org/html/rfc4648#section-5).
+    UrlSafe,
+}
+
+impl Base64Alphabet {
+    pub(crate) fn config(self) -> base64::Config {
+        match self {
+            Base64Alphabet::Standard => base64::STANDARD,
+            Base64Alphabet::UrlSafe => base64::URL_SAFE,
+        }
+    }
+}
+
+/// Rejects the non-canonical `encoded` credentials, which are lacking
+/// the padding; non-zero trailing bits are rejected by the decoder itself.
+pub(crate) fn check_canonical(encoded: &str) -> Result<(), ParseError> {
+    if encoded.len().is_multiple_of(4) {
+        Ok(())
+    } else {
+        Err(ParseError::Invalid)
+    }
+}
 
 /// Credentials for `Basic` authentication scheme, defined in [RFC 7617](https://tools.ietf.org/html/rfc7617)
 #[derive(Clone, Eq, Ord, PartialEq, PartialOrd)]
@@ -48,22 +84,63 @@ impl Basic {
     pub fn password(&self) -> Option<&Cow<'static, str>> {
         self.password.as_ref()
     }
-}
 
-impl Scheme for Basic {
-    fn parse(header: &HeaderValue) -> Result<Self, ParseError> {
+    /// Builds the outbound `Authorization` header from the credentials,
+    /// ex. for the HTTP client requests.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web::http::header::AUTHORIZATION;
+    /// # use actix_web_httpauth::headers::authorization::Basic;
+    /// let credentials = Basic::new("Aladdin", Some("open sesame"));
+    /// let (name, value) = credentials.to_header().unwrap();
+    ///
+    /// assert_eq!(name, AUTHORIZATION);
+    /// assert_eq!(value, "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==");
+    /// ```
+    pub fn to_header(
+        &self,
+    ) -> Result<(HeaderName, HeaderValue), InvalidHeaderValueBytes> {
+        self.clone().try_into().map(|value| (AUTHORIZATION, value))
+    }
+
+    /// Try to parse the `Basic` credentials from the `Authorization` header,
+    /// decoding them with the provided base64 `alphabet`.
+    ///
+    /// [`Scheme::parse`] is using the [`Base64Alphabet::Standard`] alphabet.
+    ///
+    /// [`Scheme::parse`]: ./trait.Scheme.html#tymethod.parse
+    /// [`Base64Alphabet::Standard`]: ./enum.Base64Alphabet.html
+    pub fn parse_with_alphabet(
+        header: &HeaderValue,
+        alphabet: Base64Alphabet,
+    ) -> Result<Self, ParseError> {
+        Basic::parse_with(header, alphabet, false)
+    }
+
+    /// Parses the credentials, rejecting the non-canonical base64
+    /// encodings if `strict` is set.
+    pub(crate) fn parse_with(
+        header: &HeaderValue,
+        alphabet: Base64Alphabet,
+        strict: bool,
+    ) -> Result<Self, ParseError> {
         // "Basic *" length
         if header.len() < 7 {
             return Err(ParseError::Invalid);
         }
 
-        let mut parts = header.to_str()?.splitn(2, ' ');
-        match parts.next() {
-            Some(scheme) if scheme == "Basic" => (),
-            _ => return Err(ParseError::MissingScheme),
+        let (scheme, encoded) = split_scheme(header.to_str()?);
+        if scheme != "Basic" {
+            return Err(ParseError::MissingScheme);
         }
 
-        let decoded = base64::decode(parts.next().ok_or(ParseError::Invalid)?)?;
+        let encoded = encoded.ok_or(ParseError::Invalid)?;
+        if strict {
+            check_canonical(encoded)?;
+        }
+        let decoded = base64::decode_config(encoded, alphabet.config())?;
         let mut credentials = str::from_utf8(&decoded)?.splitn(2, ':');
 
         let user_id = credentials
@@ -88,6 +165,12 @@ impl Scheme for Basic {
     }
 }
 
+impl Scheme for Basic {
+    fn parse(header: &HeaderValue) -> Result<Self, ParseError> {
+        Basic::parse_with_alphabet(header, Base64Alphabet::Standard)
+    }
+}
+
 impl fmt::Debug for Basic {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_fmt(format_args!("Basic {}:******", self.user_id))
@@ -129,7 +212,7 @@ impl IntoHeaderValue for Basic {
 
 #[cfg(test)]
 mod tests {
-    use super::{Basic, Scheme};
+    use super::{Base64Alphabet, Basic, Scheme};
     use actix_web::http::header::{HeaderValue, IntoHeaderValue};
 
     #[test]
@@ -179,6 +262,23 @@ mod tests {
         assert!(scheme.is_err());
     }
 
+    #[test]
+    fn test_whitespace_after_scheme() {
+        for value in &[
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+            "Basic    QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+            "Basic\t \tQWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+        ] {
+            let scheme = Basic::parse(&HeaderValue::from_static(value));
+            assert_eq!(scheme.unwrap().user_id, "Aladdin", "{:?}", value);
+        }
+
+        // Credentials themselves are not trimmed
+        let value =
+            HeaderValue::from_static("Basic  QWxhZGRpbjpvcGVuIHNlc2FtZQ== ");
+        assert!(Basic::parse(&value).is_err());
+    }
+
     #[test]
     fn test_missing_credentials_colon() {
         let value = HeaderValue::from_static("Basic QWxsYWRpbg==");
@@ -187,6 +287,36 @@ mod tests {
         assert!(scheme.is_err());
     }
 
+    #[test]
+    fn test_url_safe_alphabet() {
+        // "Aladdin:>>>???" is "QWxhZGRpbjo+Pj4/Pz8=" in the standard alphabet
+        let value = HeaderValue::from_static("Basic QWxhZGRpbjo-Pj4_Pz8=");
+        let scheme =
+            Basic::parse_with_alphabet(&value, Base64Alphabet::UrlSafe);
+
+        assert!(scheme.is_ok());
+        let scheme = scheme.unwrap();
+        assert_eq!(scheme.user_id, "Aladdin");
+        assert_eq!(scheme.password, Some(">>>???".into()));
+    }
+
+    #[test]
+    fn test_url_safe_alphabet_rejected_by_default() {
+        let value = HeaderValue::from_static("Basic QWxhZGRpbjo-Pj4_Pz8=");
+        let scheme = Basic::parse(&value);
+
+        assert!(scheme.is_err());
+    }
+
+    #[test]
+    fn test_standard_alphabet_rejected_by_url_safe() {
+        let value = HeaderValue::from_static("Basic QWxhZGRpbjo+Pj4/Pz8=");
+        let scheme =
+            Basic::parse_with_alphabet(&value, Base64Alphabet::UrlSafe);
+
+        assert!(scheme.is_err());
+    }
+
     #[test]
     fn test_into_header_value() {
         let basic = Basic {
@@ -2,12 +2,13 @@ use std::borrow::Cow;
 use std::fmt;
 
 use actix_web::http::header::{
-    HeaderValue, IntoHeaderValue, InvalidHeaderValueBytes,
+    HeaderName, HeaderValue, IntoHeaderValue, InvalidHeaderValueBytes,
+    AUTHORIZATION,
 };
 use bytes::{BufMut, BytesMut};
 
 use crate::headers::authorization::errors::ParseError;
-use crate::headers::authorization::scheme::Scheme;
+use crate::headers::authorization::scheme::{split_scheme, Scheme};
 
 /// Credentials for `Bearer` authentication scheme, defined in [RFC6750](https://tools.ietf.org/html/rfc6750)
 ///
@@ -40,6 +41,28 @@ impl Bearer {
     pub fn token(&self) -> &Cow<'static, str> {
         &self.token
     }
+
+    /// Builds the outbound `Authorization` header from the credentials,
+    /// ex. for the HTTP client requests.
+    ///
+    /// Fails if the token contains characters not allowed
+    /// in the header value.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web::http::header::AUTHORIZATION;
+    /// # use actix_web_httpauth::headers::authorization::Bearer;
+    /// let (name, value) = Bearer::new("mF_9.B5f-4.1JqM").to_header().unwrap();
+    ///
+    /// assert_eq!(name, AUTHORIZATION);
+    /// assert_eq!(value, "Bearer mF_9.B5f-4.1JqM");
+    /// ```
+    pub fn to_header(
+        &self,
+    ) -> Result<(HeaderName, HeaderValue), InvalidHeaderValueBytes> {
+        self.clone().try_into().map(|value| (AUTHORIZATION, value))
+    }
 }
 
 impl Scheme for Bearer {
@@ -49,13 +72,12 @@ impl Scheme for Bearer {
             return Err(ParseError::Invalid);
         }
 
-        let mut parts = header.to_str()?.splitn(2, ' ');
-        match parts.next() {
-            Some(scheme) if scheme == "Bearer" => (),
-            _ => return Err(ParseError::MissingScheme),
+        let (scheme, token) = split_scheme(header.to_str()?);
+        if scheme != "Bearer" {
+            return Err(ParseError::MissingScheme);
         }
 
-        let token = parts.next().ok_or(ParseError::Invalid)?;
+        let token = token.ok_or(ParseError::Invalid)?;
 
         Ok(Bearer {
             token: token.to_string().into(),
@@ -126,6 +148,28 @@ mod tests {
         assert!(scheme.is_err());
     }
 
+    #[test]
+    fn test_whitespace_after_scheme() {
+        for value in &["Bearer token ", "Bearer    token ", "Bearer\t \ttoken "]
+        {
+            let scheme = Bearer::parse(&HeaderValue::from_static(value));
+            assert_eq!(scheme.unwrap().token, "token ", "{:?}", value);
+        }
+
+        let scheme = Bearer::parse(&HeaderValue::from_static("Bearer \t  "));
+        assert!(scheme.is_err());
+    }
+
+    #[test]
+    fn test_to_header_round_trip() {
+        let (_name, value) =
+            Bearer::new("mF_9.B5f-4.1JqM").to_header().unwrap();
+        let scheme = Bearer::parse(&value).unwrap();
+        assert_eq!(scheme.token, "mF_9.B5f-4.1JqM");
+
+        assert!(Bearer::new("line\nbreak").to_header().is_err());
+    }
+
     #[test]
     fn test_into_header_value() {
         let bearer = Bearer::new("mF_9.B5f-4.1JqM");
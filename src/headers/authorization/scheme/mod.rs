@@ -4,9 +4,31 @@ use actix_web::http::header::{HeaderValue, IntoHeaderValue};
 
 pub mod basic;
 pub mod bearer;
+pub mod signature;
+pub mod timestamp_key;
+pub mod token;
 
 use crate::headers::authorization::errors::ParseError;
 
+/// Splits the `Authorization` header value into the scheme name
+/// and the credentials, skipping any run of the `SP` and `HTAB`
+/// characters between them.
+///
+/// Credentials are `None` if there are none, the rest of the value
+/// is returned as is, without trimming.
+pub(crate) fn split_scheme(value: &str) -> (&str, Option<&str>) {
+    let is_whitespace = |c: char| c == ' ' || c == '\t';
+    match value.find(is_whitespace) {
+        Some(index) => {
+            let credentials = value[index..].trim_start_matches(is_whitespace);
+            let credentials = Some(credentials).filter(|c| !c.is_empty());
+
+            (&value[..index], credentials)
+        }
+        None => (value, None),
+    }
+}
+
 /// Authentication scheme for [`Authorization`](./struct.Authorization.html)
 /// header.
 pub trait Scheme:
@@ -0,0 +1,296 @@
+use std::fmt;
+
+use actix_web::http::header::{
+    HeaderValue, IntoHeaderValue, InvalidHeaderValueBytes,
+};
+
+use crate::headers::authorization::errors::ParseError;
+use crate::headers::authorization::scheme::{split_scheme, Scheme};
+
+/// Credentials for `Signature` authentication scheme, defined in
+/// [HTTP Signatures draft](https://tools.ietf.org/html/draft-cavage-http-signatures-12)
+///
+/// Should be used in combination with
+/// [`Authorization`](./struct.Authorization.html) header.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Signature {
+    key_id: String,
+    algorithm: Option<String>,
+    headers: Vec<String>,
+    signature: Vec<u8>,
+    created: Option<u64>,
+    expires: Option<u64>,
+}
+
+impl Signature {
+    /// Creates new `Signature` credentials with the key ID and the
+    /// signature bytes provided, signing string is formed from the
+    /// `(created)` pseudo-header by default.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web_httpauth::headers::authorization::Signature;
+    /// let credentials = Signature::new("hmac-key-1", vec![0xde, 0xad])
+    ///     .headers(&["(request-target)", "(created)", "host"])
+    ///     .created(1_402_170_695);
+    /// ```
+    pub fn new<T: Into<String>>(key_id: T, signature: Vec<u8>) -> Signature {
+        Signature {
+            key_id: key_id.into(),
+            algorithm: None,
+            headers: vec!["(created)".to_string()],
+            signature,
+            created: None,
+            expires: None,
+        }
+    }
+
+    /// Set the `algorithm` parameter.
+    pub fn algorithm<T: Into<String>>(mut self, value: T) -> Signature {
+        self.algorithm = Some(value.into());
+        self
+    }
+
+    /// Set the list of the signed headers, `headers` parameter.
+    pub fn headers<I>(mut self, names: I) -> Signature
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.headers = names
+            .into_iter()
+            .map(|name| name.as_ref().to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    /// Set the `created` parameter, UNIX timestamp.
+    pub fn created(mut self, value: u64) -> Signature {
+        self.created = Some(value);
+        self
+    }
+
+    /// Set the `expires` parameter, UNIX timestamp.
+    pub fn expires(mut self, value: u64) -> Signature {
+        self.expires = Some(value);
+        self
+    }
+
+    /// Gets the `keyId` parameter.
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Gets the `algorithm` parameter, if present.
+    pub fn algorithm_name(&self) -> Option<&str> {
+        self.algorithm.as_deref()
+    }
+
+    /// Gets the lowercase names of the signed headers,
+    /// including the `(request-target)`-like pseudo-headers.
+    pub fn signed_headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// Gets the decoded `signature` parameter.
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Gets the `created` parameter, UNIX timestamp.
+    pub fn created_at(&self) -> Option<u64> {
+        self.created
+    }
+
+    /// Gets the `expires` parameter, UNIX timestamp.
+    pub fn expires_at(&self) -> Option<u64> {
+        self.expires
+    }
+}
+
+/// Splits the `name="value"` comma-separated parameters list.
+pub(crate) fn parse_params(
+    input: &str,
+) -> Result<Vec<(&str, String)>, ParseError> {
+    let mut params = Vec::new();
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        let eq = rest.find('=').ok_or(ParseError::Invalid)?;
+        let name = rest[..eq].trim();
+        if name.is_empty() {
+            return Err(ParseError::Invalid);
+        }
+        rest = rest[eq + 1..].trim_start();
+
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, c)) => value.push(c),
+                        None => return Err(ParseError::Invalid),
+                    },
+                    Some((i, '"')) => {
+                        rest = &quoted[i + 1..];
+                        break;
+                    }
+                    Some((_, c)) => value.push(c),
+                    None => return Err(ParseError::Invalid),
+                }
+            }
+            value
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let value = rest[..end].trim().to_string();
+            rest = &rest[end..];
+            value
+        };
+        params.push((name, value));
+
+        rest = rest.trim_start();
+        if let Some(next) = rest.strip_prefix(',') {
+            rest = next.trim_start();
+        } else if !rest.is_empty() {
+            return Err(ParseError::Invalid);
+        }
+    }
+
+    Ok(params)
+}
+
+fn parse_timestamp(value: &str) -> Result<u64, ParseError> {
+    value.parse().map_err(|_| ParseError::Invalid)
+}
+
+impl Scheme for Signature {
+    fn parse(header: &HeaderValue) -> Result<Self, ParseError> {
+        let (scheme, params) = split_scheme(header.to_str()?);
+        if scheme != "Signature" {
+            return Err(ParseError::MissingScheme);
+        }
+
+        let mut key_id = None;
+        let mut signature = None;
+        let mut credentials = Signature::new("", Vec::new());
+        for (name, value) in parse_params(params.unwrap_or(""))? {
+            match name {
+                "keyId" => key_id = Some(value),
+                "signature" => signature = Some(base64::decode(&value)?),
+                "algorithm" => credentials.algorithm = Some(value),
+                "headers" => {
+                    credentials = credentials.headers(value.split_whitespace())
+                }
+                "created" => {
+                    credentials.created = Some(parse_timestamp(&value)?)
+                }
+                "expires" => {
+                    credentials.expires = Some(parse_timestamp(&value)?)
+                }
+                // Unknown parameters are ignored
+                _ => (),
+            }
+        }
+
+        credentials.key_id = key_id.ok_or(ParseError::MissingField("keyId"))?;
+        credentials.signature =
+            signature.ok_or(ParseError::MissingField("signature"))?;
+
+        Ok(credentials)
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Signature keyId=\"{}\"", self.key_id)?;
+        if let Some(ref algorithm) = self.algorithm {
+            write!(f, ",algorithm=\"{}\"", algorithm)?;
+        }
+        if let Some(created) = self.created {
+            write!(f, ",created={}", created)?;
+        }
+        if let Some(expires) = self.expires {
+            write!(f, ",expires={}", expires)?;
+        }
+        write!(
+            f,
+            ",headers=\"{}\",signature=\"{}\"",
+            self.headers.join(" "),
+            base64::encode(&self.signature)
+        )
+    }
+}
+
+impl IntoHeaderValue for Signature {
+    type Error = InvalidHeaderValueBytes;
+
+    fn try_into(self) -> Result<HeaderValue, <Self as IntoHeaderValue>::Error> {
+        HeaderValue::from_shared(self.to_string().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::{HeaderValue, IntoHeaderValue};
+
+    use super::{Scheme, Signature};
+
+    #[test]
+    fn test_parse_header() {
+        let value = HeaderValue::from_static(
+            "Signature keyId=\"hmac-key-1\",algorithm=\"hmac-sha256\",\
+             created=1402170695, headers=\"(request-target) (created) Host\",\
+             signature=\"3q2+7w==\"",
+        );
+        let credentials = Signature::parse(&value).unwrap();
+
+        assert_eq!(credentials.key_id(), "hmac-key-1");
+        assert_eq!(credentials.algorithm_name(), Some("hmac-sha256"));
+        assert_eq!(
+            credentials.signed_headers(),
+            ["(request-target)", "(created)", "host"]
+        );
+        assert_eq!(credentials.signature(), [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(credentials.created_at(), Some(1_402_170_695));
+        assert_eq!(credentials.expires_at(), None);
+    }
+
+    #[test]
+    fn test_default_headers() {
+        let value =
+            HeaderValue::from_static("Signature keyId=\"k\",signature=\"\"");
+        let credentials = Signature::parse(&value).unwrap();
+
+        assert_eq!(credentials.signed_headers(), ["(created)"]);
+    }
+
+    #[test]
+    fn test_malformed_header() {
+        for value in &[
+            "Bearer keyId=\"k\",signature=\"\"",
+            "Signature signature=\"\"",
+            "Signature keyId=\"k\"",
+            "Signature keyId=\"k,signature=\"\"",
+            "Signature keyId=\"k\",signature=\"\",created=soon",
+        ] {
+            let value = HeaderValue::from_static(value);
+            assert!(Signature::parse(&value).is_err(), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn test_into_header_value() {
+        let credentials = Signature::new("k", vec![0xde, 0xad, 0xbe, 0xef])
+            .headers(&["(created)", "Date"])
+            .created(1);
+        let value = credentials.clone().try_into().unwrap();
+
+        assert_eq!(
+            value,
+            "Signature keyId=\"k\",created=1,headers=\"(created) date\",\
+             signature=\"3q2+7w==\""
+        );
+        assert_eq!(Signature::parse(&value).unwrap(), credentials);
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt;
+
+use actix_web::http::header::{
+    HeaderValue, IntoHeaderValue, InvalidHeaderValueBytes,
+};
+
+use super::signature::parse_params;
+use crate::headers::authorization::errors::ParseError;
+use crate::headers::authorization::scheme::{split_scheme, Scheme};
+
+/// Credentials for the `TSKey` authentication scheme, the pre-shared key
+/// HMAC of the request timestamp, ex.
+/// `Authorization: TSKey keyId="svc-1", ts="1618884473", sig="<base64>"`.
+///
+/// Should be used in combination with
+/// [`Authorization`](./struct.Authorization.html) header.
+#[derive(Clone, Eq, PartialEq)]
+pub struct TimestampKey {
+    key_id: String,
+    ts: u64,
+    sig: Vec<u8>,
+}
+
+impl TimestampKey {
+    /// Creates new `TSKey` credentials with the key ID, the UNIX timestamp
+    /// and the HMAC of the timestamp provided.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use actix_web_httpauth::headers::authorization::TimestampKey;
+    /// let credentials = TimestampKey::new("svc-1", 1_618_884_473, vec![0xde, 0xad]);
+    /// ```
+    pub fn new<T: Into<String>>(key_id: T, ts: u64, sig: Vec<u8>) -> Self {
+        TimestampKey {
+            key_id: key_id.into(),
+            ts,
+            sig,
+        }
+    }
+
+    /// Gets the `keyId` parameter.
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Gets the `ts` parameter, UNIX timestamp.
+    pub fn ts(&self) -> u64 {
+        self.ts
+    }
+
+    /// Gets the decoded `sig` parameter.
+    pub fn sig(&self) -> &[u8] {
+        &self.sig
+    }
+}
+
+impl Scheme for TimestampKey {
+    fn parse(header: &HeaderValue) -> Result<Self, ParseError> {
+        let (scheme, params) = split_scheme(header.to_str()?);
+        if scheme != "TSKey" {
+            return Err(ParseError::MissingScheme);
+        }
+
+        let mut key_id = None;
+        let mut ts = None;
+        let mut sig = None;
+        for (name, value) in parse_params(params.unwrap_or(""))? {
+            match name {
+                "keyId" => key_id = Some(value),
+                "ts" => {
+                    ts = Some(value.parse().map_err(|_| ParseError::Invalid)?)
+                }
+                "sig" => sig = Some(base64::decode(&value)?),
+                // Unknown parameters are ignored
+                _ => (),
+            }
+        }
+
+        Ok(TimestampKey {
+            key_id: key_id.ok_or(ParseError::MissingField("keyId"))?,
+            ts: ts.ok_or(ParseError::MissingField("ts"))?,
+            sig: sig.ok_or(ParseError::MissingField("sig"))?,
+        })
+    }
+}
+
+impl fmt::Debug for TimestampKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TimestampKey")
+            .field("key_id", &self.key_id)
+            .field("ts", &self.ts)
+            .field("sig", &"******")
+            .finish()
+    }
+}
+
+impl fmt::Display for TimestampKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TSKey keyId=\"{}\", ts=\"{}\", sig=\"{}\"",
+            self.key_id,
+            self.ts,
+            base64::encode(&self.sig)
+        )
+    }
+}
+
+impl IntoHeaderValue for TimestampKey {
+    type Error = InvalidHeaderValueBytes;
+
+    fn try_into(self) -> Result<HeaderValue, <Self as IntoHeaderValue>::Error> {
+        HeaderValue::from_shared(self.to_string().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::{HeaderValue, IntoHeaderValue};
+
+    use super::{Scheme, TimestampKey};
+
+    #[test]
+    fn test_parse_header() {
+        let value = HeaderValue::from_static(
+            "TSKey keyId=\"svc-1\", ts=\"1618884473\", sig=\"3q2+7w==\"",
+        );
+        let credentials = TimestampKey::parse(&value).unwrap();
+
+        assert_eq!(credentials.key_id(), "svc-1");
+        assert_eq!(credentials.ts(), 1_618_884_473);
+        assert_eq!(credentials.sig(), [0xde, 0xad, 0xbe, 0xef]);
+    }
+
+    #[test]
+    fn test_malformed_header() {
+        for value in &[
+            "Signature keyId=\"k\", ts=\"1\", sig=\"\"",
+            "TSKey ts=\"1\", sig=\"\"",
+            "TSKey keyId=\"k\", sig=\"\"",
+            "TSKey keyId=\"k\", ts=\"1\"",
+            "TSKey keyId=\"k\", ts=\"soon\", sig=\"\"",
+            "TSKey keyId=\"k\", ts=\"1\", sig=\"%%\"",
+        ] {
+            let value = HeaderValue::from_static(value);
+            assert!(TimestampKey::parse(&value).is_err(), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn test_into_header_value() {
+        let credentials =
+            TimestampKey::new("k", 1, vec![0xde, 0xad, 0xbe, 0xef]);
+        let value = credentials.clone().try_into().unwrap();
+
+        assert_eq!(value, "TSKey keyId=\"k\", ts=\"1\", sig=\"3q2+7w==\"");
+        assert_eq!(TimestampKey::parse(&value).unwrap(), credentials);
+    }
+}
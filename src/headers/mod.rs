@@ -1,4 +1,5 @@
 //! Typed HTTP headers
 
 pub mod authorization;
+pub mod signature;
 pub mod www_authenticate;
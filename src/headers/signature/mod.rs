@@ -0,0 +1,319 @@
+//! `Signature-Input` and `Signature` headers, as defined in
+//! [RFC 9421](https://www.rfc-editor.org/rfc/rfc9421) HTTP Message Signatures
+
+use actix_web::http::header::{HeaderMap, HeaderName};
+
+use crate::headers::authorization::ParseError;
+
+mod structured;
+
+pub use self::structured::{BareItem, Parameters};
+use self::structured::{Member, Parser};
+
+/// `Signature-Input` header name.
+pub const SIGNATURE_INPUT: &str = "signature-input";
+
+/// `Signature` header name.
+pub const SIGNATURE: &str = "signature";
+
+/// Covered component identifier, ex. `"@method"` or `"content-digest";sf`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    name: String,
+    parameters: Parameters,
+}
+
+impl Component {
+    /// Returns the component name, ex. `"@target-uri"` or `"host"`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the component parameters, ex. `sf` or `key="a"`.
+    pub fn parameters(&self) -> &Parameters {
+        &self.parameters
+    }
+}
+
+/// Single named message signature, formed from the matching
+/// `Signature-Input` and `Signature` dictionaries members.
+///
+/// Signature itself is not verified; [`signature_params`] provides
+/// the serialized value required for the `@signature-params` line
+/// of the signature base.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web_httpauth::headers::signature::MessageSignature;
+/// let signatures = MessageSignature::parse(
+///     "sig1=(\"@method\" \"@authority\");created=1618884473;keyid=\"test-key\"",
+///     "sig1=:dGVzdA==:",
+/// )
+/// .unwrap();
+/// let signature = &signatures[0];
+///
+/// assert_eq!(signature.label(), "sig1");
+/// assert_eq!(signature.keyid(), Some("test-key"));
+/// assert_eq!(signature.created(), Some(1618884473));
+/// assert_eq!(signature.signature(), b"test");
+/// ```
+///
+/// [`signature_params`]: #method.signature_params
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageSignature {
+    label: String,
+    components: Vec<Component>,
+    parameters: Parameters,
+    signature_params: String,
+    signature: Vec<u8>,
+}
+
+impl MessageSignature {
+    /// Parses all signatures from the `Signature-Input`
+    /// and `Signature` header values.
+    ///
+    /// Signatures are returned in the `Signature-Input` order;
+    /// each of them is required to have the `Signature` counterpart.
+    pub fn parse(
+        signature_input: &str,
+        signature: &str,
+    ) -> Result<Vec<MessageSignature>, ParseError> {
+        let signatures = Parser::new(signature).parse_dictionary()?;
+
+        Parser::new(signature_input)
+            .parse_dictionary()?
+            .into_iter()
+            .map(|(label, member, raw)| {
+                let (items, parameters) = match member {
+                    Member::InnerList(items, parameters) => (items, parameters),
+                    Member::Item(..) => return Err(ParseError::Invalid),
+                };
+                let components = items
+                    .into_iter()
+                    .map(|(item, parameters)| match item {
+                        BareItem::String(name) => Ok(Component {
+                            name,
+                            parameters,
+                        }),
+                        _ => Err(ParseError::Invalid),
+                    })
+                    .collect::<Result<_, _>>()?;
+                let signature = signatures
+                    .iter()
+                    .find(|(key, _, _)| *key == label)
+                    .ok_or(ParseError::MissingField("signature"))?;
+                let signature = match signature.1 {
+                    Member::Item(BareItem::ByteSequence(ref bytes), _) => {
+                        bytes.clone()
+                    }
+                    _ => return Err(ParseError::Invalid),
+                };
+
+                Ok(MessageSignature {
+                    label,
+                    components,
+                    parameters,
+                    signature_params: raw.to_string(),
+                    signature,
+                })
+            })
+            .collect()
+    }
+
+    /// Parses all signatures from the request headers,
+    /// multiple header fields are combined as the single dictionary.
+    ///
+    /// Returns an empty vector if there is no `Signature-Input` header.
+    pub fn from_headers(
+        headers: &HeaderMap,
+    ) -> Result<Vec<MessageSignature>, ParseError> {
+        let combine = |name: &'static str| -> Result<String, ParseError> {
+            let values = headers
+                .get_all(HeaderName::from_static(name))
+                .map(|value| value.to_str())
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(values.join(", "))
+        };
+
+        let signature_input = combine(SIGNATURE_INPUT)?;
+        if signature_input.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        MessageSignature::parse(&signature_input, &combine(SIGNATURE)?)
+    }
+
+    /// Returns the signature label, ex. `"sig1"`.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the covered components, in the signed order.
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    /// Returns all signature parameters, including the unknown ones.
+    pub fn parameters(&self) -> &Parameters {
+        &self.parameters
+    }
+
+    /// Returns the serialized signature parameters, ex.
+    /// `("@method" "@authority");created=1618884473;keyid="test-key"`,
+    /// as required for the `@signature-params` signature base line.
+    pub fn signature_params(&self) -> &str {
+        &self.signature_params
+    }
+
+    /// Returns the decoded signature bytes.
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn parameter(&self, name: &str) -> Option<&BareItem> {
+        self.parameters
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the `created` parameter, UNIX timestamp.
+    pub fn created(&self) -> Option<i64> {
+        self.parameter("created").and_then(BareItem::as_integer)
+    }
+
+    /// Returns the `expires` parameter, UNIX timestamp.
+    pub fn expires(&self) -> Option<i64> {
+        self.parameter("expires").and_then(BareItem::as_integer)
+    }
+
+    /// Returns the `keyid` parameter.
+    pub fn keyid(&self) -> Option<&str> {
+        self.parameter("keyid").and_then(BareItem::as_str)
+    }
+
+    /// Returns the `alg` parameter.
+    pub fn alg(&self) -> Option<&str> {
+        self.parameter("alg").and_then(BareItem::as_str)
+    }
+
+    /// Returns the `nonce` parameter.
+    pub fn nonce(&self) -> Option<&str> {
+        self.parameter("nonce").and_then(BareItem::as_str)
+    }
+
+    /// Returns the `tag` parameter.
+    pub fn tag(&self) -> Option<&str> {
+        self.parameter("tag").and_then(BareItem::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+
+    use super::{BareItem, MessageSignature};
+
+    // RFC 9421, Appendix B.2.5 and B.2.6
+    const SIGNATURE_INPUT: &str = "sig-b25=(\"date\" \"@authority\" \
+        \"content-type\");created=1618884473;keyid=\"test-shared-secret\", \
+        sig-b26=(\"date\" \"@method\" \"@path\" \"@authority\" \
+        \"content-type\" \"content-length\");created=1618884473;\
+        keyid=\"test-key-ed25519\"";
+    const SIGNATURE: &str = "sig-b26=:wqcAqbmYJ2ji2glfAMaRy4gruYYnx2nEFN2HN6jrnDnQCK1u02Gb04v9EDgwUPiu4A0w6vuQv5lIp5WPpBKRCw==:, \
+        sig-b25=:pxcQw6G3AjtMBQjwo8XzkZf/bws5LelbaMk5rGIGtE8=:";
+
+    #[test]
+    fn test_parse_multiple() {
+        let signatures =
+            MessageSignature::parse(SIGNATURE_INPUT, SIGNATURE).unwrap();
+
+        assert_eq!(signatures.len(), 2);
+        assert_eq!(signatures[0].label(), "sig-b25");
+        assert_eq!(signatures[0].keyid(), Some("test-shared-secret"));
+        assert_eq!(signatures[0].signature().len(), 32);
+        assert_eq!(
+            signatures[0].signature_params(),
+            "(\"date\" \"@authority\" \"content-type\");created=1618884473;\
+             keyid=\"test-shared-secret\""
+        );
+
+        let names = signatures[1]
+            .components()
+            .iter()
+            .map(|component| component.name())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "date",
+                "@method",
+                "@path",
+                "@authority",
+                "content-type",
+                "content-length"
+            ]
+        );
+        assert_eq!(signatures[1].created(), Some(1_618_884_473));
+        assert_eq!(signatures[1].alg(), None);
+        assert_eq!(signatures[1].signature().len(), 64);
+    }
+
+    #[test]
+    fn test_component_parameters() {
+        let signatures = MessageSignature::parse(
+            "sig1=(\"example-dict\";sf \"@query-param\";name=\"id\");\
+             alg=\"hmac-sha256\";expires=1618884773",
+            "sig1=:AA==:",
+        )
+        .unwrap();
+        let components = signatures[0].components();
+
+        assert_eq!(
+            components[0].parameters(),
+            &vec![("sf".to_string(), BareItem::Boolean(true))]
+        );
+        assert_eq!(
+            components[1].parameters(),
+            &vec![("name".to_string(), BareItem::String("id".to_string()))]
+        );
+        assert_eq!(signatures[0].alg(), Some("hmac-sha256"));
+        assert_eq!(signatures[0].expires(), Some(1_618_884_773));
+    }
+
+    #[test]
+    fn test_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert!(MessageSignature::from_headers(&headers).unwrap().is_empty());
+
+        let input = HeaderName::from_static("signature-input");
+        let signature = HeaderName::from_static("signature");
+        headers
+            .append(input.clone(), HeaderValue::from_static("a=(\"@method\")"));
+        headers.append(input, HeaderValue::from_static("b=(\"@path\")"));
+        headers
+            .insert(signature, HeaderValue::from_static("a=:AA==:, b=:AQ==:"));
+
+        let signatures = MessageSignature::from_headers(&headers).unwrap();
+        assert_eq!(signatures.len(), 2);
+    }
+
+    #[test]
+    fn test_malformed() {
+        for (input, signature) in &[
+            ("sig1=(\"@method\")", "sig2=:AA==:"),
+            ("sig1=\"@method\"", "sig1=:AA==:"),
+            ("sig1=(@method)", "sig1=:AA==:"),
+            ("sig1=(\"@method\")", "sig1=\"AA==\""),
+        ] {
+            assert!(
+                MessageSignature::parse(input, signature).is_err(),
+                "{} / {}",
+                input,
+                signature
+            );
+        }
+    }
+}
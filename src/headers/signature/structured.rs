@@ -0,0 +1,343 @@
+//! Minimal [RFC 8941](https://tools.ietf.org/html/rfc8941) structured field
+//! values parser, sufficient for the `Signature-Input` and `Signature`
+//! dictionaries.
+
+use crate::headers::authorization::ParseError;
+
+/// Structured field bare item.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BareItem {
+    /// Integer, ex. `1618884473`.
+    Integer(i64),
+    /// Decimal, ex. `4.5`.
+    Decimal(f64),
+    /// String, ex. `"test-key-rsa-pss"`.
+    String(String),
+    /// Token, ex. `rsa-pss-sha512`.
+    Token(String),
+    /// Decoded byte sequence, ex.
+    /// `:cHJldGVuZCB0aGlzIGlzIGEgc2lnbmF0dXJlCg==:`.
+    ByteSequence(Vec<u8>),
+    /// Boolean, ex. `?1`.
+    Boolean(bool),
+}
+
+impl BareItem {
+    /// Returns the value of the string item.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            BareItem::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the integer item.
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            BareItem::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// Item or inner list parameters, in the order of appearance.
+pub type Parameters = Vec<(String, BareItem)>;
+
+/// Dictionary member value.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Member {
+    Item(BareItem, Parameters),
+    InnerList(Vec<(BareItem, Parameters)>, Parameters),
+}
+
+pub(crate) struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(input: &'a str) -> Parser<'a> {
+        Parser {
+            input,
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.position).cloned()
+    }
+
+    fn skip_while<F: Fn(u8) -> bool>(&mut self, f: F) {
+        while self.peek().is_some_and(&f) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ParseError> {
+        if self.peek() == Some(byte) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(ParseError::Invalid)
+        }
+    }
+
+    /// Parses the dictionary, each member is returned along with
+    /// its raw serialized value.
+    pub(crate) fn parse_dictionary(
+        mut self,
+    ) -> Result<Vec<(String, Member, &'a str)>, ParseError> {
+        let mut members = Vec::new();
+        self.skip_while(|byte| byte == b' ');
+        while self.peek().is_some() {
+            let key = self.parse_key()?;
+            let start = self.position;
+            let member = if self.peek() == Some(b'=') {
+                self.position += 1;
+                if self.peek() == Some(b'(') {
+                    self.parse_inner_list()?
+                } else {
+                    let item = self.parse_bare_item()?;
+                    Member::Item(item, self.parse_parameters()?)
+                }
+            } else {
+                Member::Item(BareItem::Boolean(true), self.parse_parameters()?)
+            };
+            let raw = self.input[start..self.position].trim_start_matches('=');
+            members.push((key, member, raw));
+
+            self.skip_while(|byte| byte == b' ' || byte == b'\t');
+            if self.peek().is_none() {
+                break;
+            }
+            self.expect(b',')?;
+            self.skip_while(|byte| byte == b' ' || byte == b'\t');
+            if self.peek().is_none() {
+                // Trailing comma
+                return Err(ParseError::Invalid);
+            }
+        }
+
+        Ok(members)
+    }
+
+    fn parse_inner_list(&mut self) -> Result<Member, ParseError> {
+        self.expect(b'(')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_while(|byte| byte == b' ');
+            if self.peek() == Some(b')') {
+                self.position += 1;
+                break;
+            }
+
+            let item = self.parse_bare_item()?;
+            items.push((item, self.parse_parameters()?));
+            match self.peek() {
+                Some(b' ') | Some(b')') => (),
+                _ => return Err(ParseError::Invalid),
+            }
+        }
+
+        Ok(Member::InnerList(items, self.parse_parameters()?))
+    }
+
+    fn parse_parameters(&mut self) -> Result<Parameters, ParseError> {
+        let mut parameters = Vec::new();
+        while self.peek() == Some(b';') {
+            self.position += 1;
+            self.skip_while(|byte| byte == b' ');
+            let key = self.parse_key()?;
+            let value = if self.peek() == Some(b'=') {
+                self.position += 1;
+                self.parse_bare_item()?
+            } else {
+                BareItem::Boolean(true)
+            };
+            parameters.push((key, value));
+        }
+
+        Ok(parameters)
+    }
+
+    fn parse_key(&mut self) -> Result<String, ParseError> {
+        match self.peek() {
+            Some(byte) if byte.is_ascii_lowercase() || byte == b'*' => (),
+            _ => return Err(ParseError::Invalid),
+        }
+
+        let start = self.position;
+        self.skip_while(|byte| {
+            byte.is_ascii_lowercase()
+                || byte.is_ascii_digit()
+                || b"_-.*".contains(&byte)
+        });
+
+        Ok(self.input[start..self.position].to_string())
+    }
+
+    fn parse_bare_item(&mut self) -> Result<BareItem, ParseError> {
+        match self.peek() {
+            Some(b'-') => self.parse_number(),
+            Some(byte) if byte.is_ascii_digit() => self.parse_number(),
+            Some(b'"') => self.parse_string(),
+            Some(b':') => self.parse_byte_sequence(),
+            Some(b'?') => self.parse_boolean(),
+            Some(byte) if byte.is_ascii_alphabetic() || byte == b'*' => {
+                Ok(self.parse_token())
+            }
+            _ => Err(ParseError::Invalid),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<BareItem, ParseError> {
+        let start = self.position;
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        self.skip_while(|byte| byte.is_ascii_digit() || byte == b'.');
+
+        let value = &self.input[start..self.position];
+        if value.contains('.') {
+            value
+                .parse()
+                .map(BareItem::Decimal)
+                .map_err(|_| ParseError::Invalid)
+        } else if value.trim_start_matches('-').len() > 15 {
+            Err(ParseError::Invalid)
+        } else {
+            value
+                .parse()
+                .map(BareItem::Integer)
+                .map_err(|_| ParseError::Invalid)
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<BareItem, ParseError> {
+        self.expect(b'"')?;
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                Some(b'\\') => {
+                    self.position += 1;
+                    match self.peek() {
+                        Some(byte @ b'"') | Some(byte @ b'\\') => {
+                            value.push(char::from(byte))
+                        }
+                        _ => return Err(ParseError::Invalid),
+                    }
+                }
+                Some(b'"') => {
+                    self.position += 1;
+                    return Ok(BareItem::String(value));
+                }
+                Some(byte) if (0x20..0x7f).contains(&byte) => {
+                    value.push(char::from(byte))
+                }
+                _ => return Err(ParseError::Invalid),
+            }
+            self.position += 1;
+        }
+    }
+
+    fn parse_token(&mut self) -> BareItem {
+        let start = self.position;
+        self.skip_while(|byte| {
+            byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~:/".contains(&byte)
+        });
+
+        BareItem::Token(self.input[start..self.position].to_string())
+    }
+
+    fn parse_byte_sequence(&mut self) -> Result<BareItem, ParseError> {
+        self.expect(b':')?;
+        let start = self.position;
+        self.skip_while(|byte| {
+            byte.is_ascii_alphanumeric() || b"+/=".contains(&byte)
+        });
+        let encoded = &self.input[start..self.position];
+        self.expect(b':')?;
+
+        Ok(BareItem::ByteSequence(base64::decode(encoded)?))
+    }
+
+    fn parse_boolean(&mut self) -> Result<BareItem, ParseError> {
+        self.expect(b'?')?;
+        let value = match self.peek() {
+            Some(b'1') => true,
+            Some(b'0') => false,
+            _ => return Err(ParseError::Invalid),
+        };
+        self.position += 1;
+
+        Ok(BareItem::Boolean(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BareItem, Member, Parser};
+
+    #[test]
+    fn test_dictionary() {
+        let members = Parser::new(
+            "a=1, b=?0;x, c, d=(\"x\" tok;p=-1.5);q=:AQI=:, e=\"\\\"q\\\"\"",
+        )
+        .parse_dictionary()
+        .unwrap();
+        let values = members
+            .iter()
+            .map(|(key, member, _)| (key.as_str(), member.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            values,
+            vec![
+                ("a", Member::Item(BareItem::Integer(1), vec![])),
+                (
+                    "b",
+                    Member::Item(
+                        BareItem::Boolean(false),
+                        vec![("x".to_string(), BareItem::Boolean(true))]
+                    )
+                ),
+                ("c", Member::Item(BareItem::Boolean(true), vec![])),
+                (
+                    "d",
+                    Member::InnerList(
+                        vec![
+                            (BareItem::String("x".to_string()), vec![]),
+                            (
+                                BareItem::Token("tok".to_string()),
+                                vec![(
+                                    "p".to_string(),
+                                    BareItem::Decimal(-1.5)
+                                )]
+                            ),
+                        ],
+                        vec![(
+                            "q".to_string(),
+                            BareItem::ByteSequence(vec![1, 2])
+                        )]
+                    )
+                ),
+                (
+                    "e",
+                    Member::Item(BareItem::String("\"q\"".to_string()), vec![])
+                ),
+            ]
+        );
+        assert_eq!(members[3].2, "(\"x\" tok;p=-1.5);q=:AQI=:");
+    }
+
+    #[test]
+    fn test_malformed() {
+        for value in &["A=1", "a=1,", "a=(1", "a=\"x", "a=:AQI=", "a=1 b=2"] {
+            assert!(
+                Parser::new(value).parse_dictionary().is_err(),
+                "{}",
+                value
+            );
+        }
+    }
+}
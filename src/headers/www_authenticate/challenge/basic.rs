@@ -8,10 +8,10 @@ use std::str;
 use actix_web::http::header::{
     HeaderValue, IntoHeaderValue, InvalidHeaderValueBytes,
 };
-use bytes::{BufMut, Bytes, BytesMut};
+use bytes::{Bytes, BytesMut};
 
 use super::Challenge;
-use crate::utils;
+use crate::headers::www_authenticate::ext_value;
 
 /// Challenge for [`WWW-Authenticate`] header with HTTP Basic auth scheme,
 /// described in [RFC 7617](https://tools.ietf.org/html/rfc7617)
@@ -78,15 +78,17 @@ impl Basic {
 
 #[doc(hidden)]
 impl Challenge for Basic {
+    fn scheme(&self) -> &'static str {
+        "Basic"
+    }
+
     fn to_bytes(&self) -> Bytes {
         // 5 is for `"Basic"`, 9 is for `"realm=\"\""`
         let length = 5 + self.realm.as_ref().map_or(0, |realm| realm.len() + 9);
         let mut buffer = BytesMut::with_capacity(length);
-        buffer.put("Basic");
+        buffer.extend_from_slice(b"Basic");
         if let Some(ref realm) = self.realm {
-            buffer.put(" realm=\"");
-            utils::put_quoted(&mut buffer, realm);
-            buffer.put("\"");
+            ext_value::put_param(&mut buffer, "realm", realm);
         }
 
         buffer.freeze()
@@ -141,4 +143,25 @@ mod tests {
         let value = value.unwrap();
         assert_eq!(value, "Basic realm=\"Restricted area\"");
     }
+
+    #[test]
+    fn test_extended_realm_into_header_value() {
+        let challenge = Basic {
+            realm: Some("Zone d'accès".into()),
+        };
+
+        let value = challenge.try_into();
+        assert!(value.is_ok());
+        let value = value.unwrap();
+        assert_eq!(value, "Basic realm*=UTF-8''Zone%20d%27acc%C3%A8s");
+    }
+
+    #[test]
+    fn test_empty_realm_into_header_value() {
+        let challenge = Basic {
+            realm: Some("".into()),
+        };
+
+        assert_eq!(challenge.try_into().unwrap(), "Basic");
+    }
 }
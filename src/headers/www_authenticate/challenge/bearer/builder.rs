@@ -7,9 +7,28 @@ use super::{Bearer, Error};
 /// It is up to implementor to fill all required fields,
 /// neither this `Builder` or [`Bearer`] does not provide any validation.
 ///
+/// Each attribute setter has the `_opt` counterpart, which accepts
+/// an `Option` and leaves the attribute untouched if it is `None`:
+///
+/// ```rust
+/// # use actix_web_httpauth::headers::www_authenticate::bearer::{Bearer, Error};
+/// let description = if cfg!(debug_assertions) {
+///     Some("Token has expired")
+/// } else {
+///     None
+/// };
+/// let challenge = Bearer::build()
+///     .error(Error::InvalidToken)
+///     .error_description_opt(description)
+///     .finish();
+/// ```
+///
 /// [`Bearer`]: struct.Bearer.html
 #[derive(Debug, Default)]
-pub struct BearerBuilder(Bearer);
+pub struct BearerBuilder {
+    challenge: Bearer,
+    normalize_scope: bool,
+}
 
 impl BearerBuilder {
     /// Provides the `scope` attribute, as defined in [RFC6749, Section 3.3](https://tools.ietf.org/html/rfc6749#section-3.3)
@@ -17,7 +36,17 @@ impl BearerBuilder {
     where
         T: Into<Cow<'static, str>>,
     {
-        self.0.scope = Some(value.into());
+        self.challenge.scope = Some(value.into());
+        self
+    }
+
+    /// Deduplicate and sort the scopes of the `scope` attribute,
+    /// so ex. `"write read read"` is serialized as `"read write"`.
+    ///
+    /// Useful when the scopes are assembled from the multiple sources;
+    /// disabled by default, so the provided order is preserved.
+    pub fn with_scope_normalization(mut self, value: bool) -> Self {
+        self.normalize_scope = value;
         self
     }
 
@@ -26,13 +55,27 @@ impl BearerBuilder {
     where
         T: Into<Cow<'static, str>>,
     {
-        self.0.realm = Some(value.into());
+        self.challenge.realm = Some(value.into());
+        self
+    }
+
+    /// Provides the `domain` attribute, the list of URIs which are sharing
+    /// the protection space, as defined in [RFC7616, Section 3.3](https://tools.ietf.org/html/rfc7616#section-3.3)
+    ///
+    /// It is a hint for the clients only, URIs are space-separated
+    /// in the serialized challenge, ex. `domain="/api /admin"`.
+    pub fn domain<I>(mut self, uris: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'static, str>>,
+    {
+        self.challenge.domain = uris.into_iter().map(Into::into).collect();
         self
     }
 
     /// Provides the `error` attribute, as defined in [RFC6750, Section 3.1](https://tools.ietf.org/html/rfc6750#section-3.1)
     pub fn error(mut self, value: Error) -> Self {
-        self.0.error = Some(value);
+        self.challenge.error = Some(value);
         self
     }
 
@@ -41,7 +84,7 @@ impl BearerBuilder {
     where
         T: Into<Cow<'static, str>>,
     {
-        self.0.error_description = Some(value.into());
+        self.challenge.error_description = Some(value.into());
         self
     }
 
@@ -52,12 +95,95 @@ impl BearerBuilder {
     where
         T: Into<Cow<'static, str>>,
     {
-        self.0.error_uri = Some(value.into());
+        self.challenge.error_uri = Some(value.into());
         self
     }
 
+    /// Provides the custom attribute, ex. the `authorization_uri` one
+    /// pointing to the identity provider.
+    ///
+    /// Custom attributes are serialized after the standard ones,
+    /// in the order they were provided, see [`Bearer`] for the
+    /// canonical order.
+    ///
+    /// [`Bearer`]: ./struct.Bearer.html
+    pub fn param<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+        V: Into<Cow<'static, str>>,
+    {
+        self.challenge.params.push((name.into(), value.into()));
+        self
+    }
+
+    /// Provides the `scope` attribute if `value` is `Some`,
+    /// see [`scope`](#method.scope).
+    pub fn scope_opt<T>(self, value: Option<T>) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        match value {
+            Some(value) => self.scope(value),
+            None => self,
+        }
+    }
+
+    /// Provides the `realm` attribute if `value` is `Some`,
+    /// see [`realm`](#method.realm).
+    pub fn realm_opt<T>(self, value: Option<T>) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        match value {
+            Some(value) => self.realm(value),
+            None => self,
+        }
+    }
+
+    /// Provides the `error` attribute if `value` is `Some`,
+    /// see [`error`](#method.error).
+    pub fn error_opt(self, value: Option<Error>) -> Self {
+        match value {
+            Some(value) => self.error(value),
+            None => self,
+        }
+    }
+
+    /// Provides the `error_description` attribute if `value` is `Some`,
+    /// see [`error_description`](#method.error_description).
+    pub fn error_description_opt<T>(self, value: Option<T>) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        match value {
+            Some(value) => self.error_description(value),
+            None => self,
+        }
+    }
+
+    /// Provides the `error_uri` attribute if `value` is `Some`,
+    /// see [`error_uri`](#method.error_uri).
+    pub fn error_uri_opt<T>(self, value: Option<T>) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        match value {
+            Some(value) => self.error_uri(value),
+            None => self,
+        }
+    }
+
     /// Consumes the builder and returns built `Bearer` instance.
-    pub fn finish(self) -> Bearer {
-        self.0
+    pub fn finish(mut self) -> Bearer {
+        if self.normalize_scope {
+            if let Some(ref mut scope) = self.challenge.scope {
+                let mut scopes = scope.split_whitespace().collect::<Vec<_>>();
+                scopes.sort_unstable();
+                scopes.dedup();
+                *scope = scopes.join(" ").into();
+            }
+        }
+
+        self.challenge
     }
 }
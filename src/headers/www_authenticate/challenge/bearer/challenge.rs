@@ -5,15 +5,21 @@ use std::str;
 use actix_web::http::header::{
     HeaderValue, IntoHeaderValue, InvalidHeaderValueBytes,
 };
-use bytes::{BufMut, Bytes, BytesMut};
+use bytes::{Bytes, BytesMut};
 
 use super::super::Challenge;
 use super::{BearerBuilder, Error};
-use crate::utils;
+use crate::headers::www_authenticate::ext_value;
+use crate::headers::www_authenticate::params::param_rank;
 
 /// Challenge for [`WWW-Authenticate`] header with HTTP Bearer auth scheme,
 /// described in [RFC 6750](https://tools.ietf.org/html/rfc6750#section-3)
 ///
+/// Parameters are always serialized in the same order: `realm`, `domain`,
+/// `scope`, `error`, `error_description` and `error_uri`, followed by the
+/// custom ones, in the order they were provided. Custom parameters named
+/// as the standard ones are moved to their canonical position.
+///
 /// ## Example
 ///
 /// ```rust
@@ -43,9 +49,11 @@ use crate::utils;
 pub struct Bearer {
     pub(crate) scope: Option<Cow<'static, str>>,
     pub(crate) realm: Option<Cow<'static, str>>,
+    pub(crate) domain: Vec<Cow<'static, str>>,
     pub(crate) error: Option<Error>,
     pub(crate) error_description: Option<Cow<'static, str>>,
     pub(crate) error_uri: Option<Cow<'static, str>>,
+    pub(crate) params: Vec<(Cow<'static, str>, Cow<'static, str>)>,
 }
 
 impl Bearer {
@@ -67,53 +75,44 @@ impl Bearer {
 
 #[doc(hidden)]
 impl Challenge for Bearer {
-    fn to_bytes(&self) -> Bytes {
-        let desc_uri_required = self
-            .error_description
-            .as_ref()
-            .map_or(0, |desc| desc.len() + 20)
-            + self.error_uri.as_ref().map_or(0, |url| url.len() + 12);
-        let capacity = 6
-            + self.realm.as_ref().map_or(0, |realm| realm.len() + 9)
-            + self.scope.as_ref().map_or(0, |scope| scope.len() + 9)
-            + desc_uri_required;
-        let mut buffer = BytesMut::with_capacity(capacity);
-        buffer.put("Bearer");
-
-        if let Some(ref realm) = self.realm {
-            buffer.put(" realm=\"");
-            utils::put_quoted(&mut buffer, realm);
-            buffer.put("\"");
-        }
-
-        if let Some(ref scope) = self.scope {
-            buffer.put(" scope=\"");
-            utils::put_quoted(&mut buffer, scope);
-            buffer.put("\"");
-        }
+    fn scheme(&self) -> &'static str {
+        "Bearer"
+    }
 
-        if let Some(ref error) = self.error {
-            let error_repr = error.as_str();
-            let remaining = buffer.remaining_mut();
-            let required = desc_uri_required + error_repr.len() + 9; // 9 is for `" error=\"\""`
-            if remaining < required {
-                buffer.reserve(required);
-            }
-            buffer.put(" error=\"");
-            utils::put_quoted(&mut buffer, error_repr);
-            buffer.put("\"")
-        }
+    fn error_code(&self) -> Option<&'static str> {
+        self.error.as_ref().map(Error::as_str)
+    }
 
-        if let Some(ref error_description) = self.error_description {
-            buffer.put(" error_description=\"");
-            utils::put_quoted(&mut buffer, error_description);
-            buffer.put("\"");
-        }
+    fn to_bytes(&self) -> Bytes {
+        let domain = self.domain.join(" ");
+        let params = [
+            ("realm", self.realm.as_deref()),
+            ("domain", Some(domain.as_str()).filter(|d| !d.is_empty())),
+            ("scope", self.scope.as_deref()),
+            ("error", self.error.as_ref().map(Error::as_str)),
+            ("error_description", self.error_description.as_deref()),
+            ("error_uri", self.error_uri.as_deref()),
+        ];
+        let mut params = params
+            .iter()
+            .filter_map(|&(name, value)| value.map(|value| (name, value)))
+            .chain(
+                self.params
+                    .iter()
+                    .map(|(name, value)| (name.as_ref(), value.as_ref())),
+            )
+            .collect::<Vec<_>>();
+        // Sort is stable, so the custom parameters keep their order
+        params.sort_by_key(|&(name, _)| param_rank(name));
 
-        if let Some(ref error_uri) = self.error_uri {
-            buffer.put(" error_uri=\"");
-            utils::put_quoted(&mut buffer, error_uri);
-            buffer.put("\"");
+        // 6 is for `"Bearer"` and for each of `", =\"\""` separators
+        let capacity = params
+            .iter()
+            .fold(6, |acc, (name, value)| acc + name.len() + value.len() + 6);
+        let mut buffer = BytesMut::with_capacity(capacity);
+        buffer.extend_from_slice(b"Bearer");
+        for (name, value) in params {
+            ext_value::put_param(&mut buffer, name, value);
         }
 
         buffer.freeze()
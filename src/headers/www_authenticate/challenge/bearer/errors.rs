@@ -18,6 +18,15 @@ pub enum Error {
     /// The request requires higher privileges than provided by the access
     /// token.
     InsufficientScope,
+
+    /// The DPoP proof is missing, malformed or invalid, described in
+    /// [RFC 9449](https://tools.ietf.org/html/rfc9449#section-7.1).
+    InvalidDpopProof,
+
+    /// The DPoP proof has to include the server-provided nonce, sent
+    /// in the `DPoP-Nonce` header, described in
+    /// [RFC 9449](https://tools.ietf.org/html/rfc9449#section-9).
+    UseDpopNonce,
 }
 
 impl Error {
@@ -30,16 +39,20 @@ impl Error {
             Error::InvalidRequest => StatusCode::BAD_REQUEST,
             Error::InvalidToken => StatusCode::UNAUTHORIZED,
             Error::InsufficientScope => StatusCode::FORBIDDEN,
+            Error::InvalidDpopProof => StatusCode::UNAUTHORIZED,
+            Error::UseDpopNonce => StatusCode::UNAUTHORIZED,
         }
     }
 
     #[doc(hidden)]
     #[allow(clippy::trivially_copy_pass_by_ref)]
-    pub fn as_str(&self) -> &str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             Error::InvalidRequest => "invalid_request",
             Error::InvalidToken => "invalid_token",
             Error::InsufficientScope => "insufficient_scope",
+            Error::InvalidDpopProof => "invalid_dpop_proof",
+            Error::UseDpopNonce => "use_dpop_nonce",
         }
     }
 }
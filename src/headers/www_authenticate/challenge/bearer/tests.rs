@@ -1,3 +1,5 @@
+use actix_web::http::header::IntoHeaderValue;
+
 use super::*;
 
 #[test]
@@ -10,7 +12,172 @@ fn to_bytes() {
         .finish();
 
     assert_eq!(
-        "Bearer error=\"invalid_token\" error_description=\"Subject 8740827c-2e0a-447b-9716-d73042e4039d not found\"",
+        "Bearer error=\"invalid_token\", error_description=\"Subject 8740827c-2e0a-447b-9716-d73042e4039d not found\"",
+        format!("{}", b)
+    );
+}
+
+#[test]
+fn opt_setters() {
+    let b = Bearer::build()
+        .realm_opt(Some("example"))
+        .scope_opt(None::<&str>)
+        .error_opt(Some(Error::InvalidToken))
+        .error_description_opt(None::<&str>)
+        .error_uri_opt(Some("https://example.com/errors"))
+        .finish();
+
+    assert_eq!(
+        "Bearer realm=\"example\", error=\"invalid_token\", error_uri=\"https://example.com/errors\"",
+        format!("{}", b)
+    );
+}
+
+#[test]
+fn description_without_error() {
+    let b = Bearer::build()
+        .error_description("Unsupported scheme")
+        .error_uri("https://example.com/errors")
+        .finish();
+
+    assert_eq!(
+        "Bearer error_description=\"Unsupported scheme\", error_uri=\"https://example.com/errors\"",
         format!("{}", b)
     );
 }
+
+#[test]
+fn extended_realm() {
+    let b = Bearer::build()
+        .realm("Überall")
+        .scope("openid")
+        .error(Error::InvalidToken)
+        .finish();
+
+    assert_eq!(
+        "Bearer realm*=UTF-8''%C3%9Cberall, scope=\"openid\", error=\"invalid_token\"",
+        format!("{}", b)
+    );
+}
+
+#[test]
+fn custom_params() {
+    let b = Bearer::build()
+        .realm("example")
+        .error(Error::InvalidToken)
+        .param("authorization_uri", "https://idp.example.com/authorize")
+        .param("tenant", "Müller")
+        .finish();
+
+    assert_eq!(
+        "Bearer realm=\"example\", error=\"invalid_token\", authorization_uri=\"https://idp.example.com/authorize\", tenant*=UTF-8''M%C3%BCller",
+        format!("{}", b)
+    );
+}
+
+#[test]
+fn all_fields_subsets() {
+    let fields = [
+        ("realm", "example"),
+        ("scope", "openid"),
+        ("error", "invalid_token"),
+        ("error_description", "Expired"),
+        ("error_uri", "https://example.com/errors"),
+    ];
+
+    for mask in 0..(1 << fields.len()) {
+        let set = |i: usize| mask & (1 << i) != 0;
+        let b = Bearer::build()
+            .realm_opt(if set(0) { Some(fields[0].1) } else { None })
+            .scope_opt(if set(1) { Some(fields[1].1) } else { None })
+            .error_opt(if set(2) {
+                Some(Error::InvalidToken)
+            } else {
+                None
+            })
+            .error_description_opt(if set(3) {
+                Some(fields[3].1)
+            } else {
+                None
+            })
+            .error_uri_opt(if set(4) { Some(fields[4].1) } else { None })
+            .finish();
+
+        let params = fields
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| set(i))
+            .map(|(_, (name, value))| format!("{}=\"{}\"", name, value))
+            .collect::<Vec<_>>();
+        let expected = if params.is_empty() {
+            "Bearer".to_string()
+        } else {
+            format!("Bearer {}", params.join(", "))
+        };
+
+        assert_eq!(expected, format!("{}", b), "mask {:05b}", mask);
+    }
+}
+
+#[test]
+fn empty_params_are_skipped() {
+    let b = Bearer::build()
+        .realm("")
+        .scope("")
+        .error(Error::InvalidToken)
+        .param("tenant", "")
+        .finish();
+
+    assert_eq!("Bearer error=\"invalid_token\"", format!("{}", b));
+}
+
+#[test]
+fn domain() {
+    let b = Bearer::build()
+        .realm("example")
+        .domain(vec!["/api", "/admin"])
+        .scope("openid")
+        .finish();
+    let value = b.clone().try_into().unwrap();
+
+    assert_eq!(
+        value,
+        "Bearer realm=\"example\", domain=\"/api /admin\", scope=\"openid\""
+    );
+    assert_eq!(value.to_str().unwrap(), b.to_string());
+
+    let b = Bearer::build().domain(Vec::<String>::new()).finish();
+    assert_eq!("Bearer", format!("{}", b));
+}
+
+#[test]
+fn scope_normalization() {
+    let challenge = Bearer::build()
+        .scope("write read  read")
+        .with_scope_normalization(true)
+        .finish();
+    assert_eq!(challenge.to_string(), "Bearer scope=\"read write\"");
+
+    let challenge = Bearer::build().scope("write read  read").finish();
+    assert_eq!(challenge.to_string(), "Bearer scope=\"write read  read\"");
+}
+
+#[test]
+fn canonical_order() {
+    let b = Bearer::build()
+        .param("tenant", "acme")
+        .error_uri("https://example.com/errors")
+        .error_description("Expired")
+        .error(Error::InvalidToken)
+        .param("scope", "openid")
+        .param("authorization_uri", "https://idp.example.com/authorize")
+        .realm("example")
+        .finish();
+    let value = b.clone().try_into().unwrap();
+
+    assert_eq!(
+        value.as_bytes(),
+        &b"Bearer realm=\"example\", scope=\"openid\", error=\"invalid_token\", error_description=\"Expired\", error_uri=\"https://example.com/errors\", tenant=\"acme\", authorization_uri=\"https://idp.example.com/authorize\""[..]
+    );
+    assert_eq!(b.to_string().as_bytes(), value.as_bytes());
+}
@@ -0,0 +1,80 @@
+//! Challenge advertising either the "Bearer" or the "Basic" scheme
+
+use std::fmt;
+
+use actix_web::http::header::{
+    HeaderValue, IntoHeaderValue, InvalidHeaderValueBytes,
+};
+use bytes::Bytes;
+
+use super::basic::Basic;
+use super::bearer::Bearer;
+use super::Challenge;
+
+/// Challenge for [`WWW-Authenticate`] header with either the `Bearer`
+/// or the `Basic` auth scheme, so both of them can be advertised
+/// by one error, see
+/// [`AuthenticationError::bearer_or_basic`].
+///
+/// [`WWW-Authenticate`]: ../struct.WwwAuthenticate.html
+/// [`AuthenticationError::bearer_or_basic`]: ../../../extractors/struct.AuthenticationError.html#method.bearer_or_basic
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Clone)]
+pub enum BearerOrBasic {
+    /// `Bearer` challenge.
+    Bearer(Bearer),
+    /// `Basic` challenge.
+    Basic(Basic),
+}
+
+impl From<Bearer> for BearerOrBasic {
+    fn from(challenge: Bearer) -> Self {
+        BearerOrBasic::Bearer(challenge)
+    }
+}
+
+impl From<Basic> for BearerOrBasic {
+    fn from(challenge: Basic) -> Self {
+        BearerOrBasic::Basic(challenge)
+    }
+}
+
+#[doc(hidden)]
+impl Challenge for BearerOrBasic {
+    fn scheme(&self) -> &'static str {
+        match self {
+            BearerOrBasic::Bearer(challenge) => challenge.scheme(),
+            BearerOrBasic::Basic(challenge) => challenge.scheme(),
+        }
+    }
+
+    fn error_code(&self) -> Option<&'static str> {
+        match self {
+            BearerOrBasic::Bearer(challenge) => challenge.error_code(),
+            BearerOrBasic::Basic(challenge) => challenge.error_code(),
+        }
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        match self {
+            BearerOrBasic::Bearer(challenge) => challenge.to_bytes(),
+            BearerOrBasic::Basic(challenge) => challenge.to_bytes(),
+        }
+    }
+}
+
+impl fmt::Display for BearerOrBasic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            BearerOrBasic::Bearer(challenge) => fmt::Display::fmt(challenge, f),
+            BearerOrBasic::Basic(challenge) => fmt::Display::fmt(challenge, f),
+        }
+    }
+}
+
+impl IntoHeaderValue for BearerOrBasic {
+    type Error = InvalidHeaderValueBytes;
+
+    fn try_into(self) -> Result<HeaderValue, <Self as IntoHeaderValue>::Error> {
+        HeaderValue::from_shared(self.to_bytes())
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt;
+use std::str::FromStr;
+
+use md5::Md5;
+use sha2::{Digest as _, Sha256, Sha512_256};
+
+use crate::headers::authorization::ParseError;
+
+/// Digest `algorithm` parameter, as defined in
+/// [RFC 7616](https://tools.ietf.org/html/rfc7616#section-3.3).
+///
+/// `session` flag is set for the `-sess` variants, which are hashing
+/// the client nonce into the `A1` value.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web_httpauth::headers::www_authenticate::digest::DigestAlgorithm;
+/// let algorithm: DigestAlgorithm = "SHA-256-sess".parse().unwrap();
+///
+/// assert_eq!(algorithm, DigestAlgorithm::Sha256 { session: true });
+/// assert!(algorithm.is_session());
+/// assert_eq!(algorithm.to_string(), "SHA-256-sess");
+/// assert!("SHA-1".parse::<DigestAlgorithm>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DigestAlgorithm {
+    /// `MD5` and `MD5-sess`
+    Md5 {
+        /// `-sess` variant
+        session: bool,
+    },
+    /// `SHA-256` and `SHA-256-sess`
+    Sha256 {
+        /// `-sess` variant
+        session: bool,
+    },
+    /// `SHA-512-256` and `SHA-512-256-sess`
+    Sha512_256 {
+        /// `-sess` variant
+        session: bool,
+    },
+}
+
+impl Default for DigestAlgorithm {
+    /// `MD5`, which is implied when the parameter is absent.
+    fn default() -> Self {
+        DigestAlgorithm::Md5 {
+            session: false,
+        }
+    }
+}
+
+impl DigestAlgorithm {
+    /// Returns `true` for the `-sess` variants.
+    pub fn is_session(self) -> bool {
+        match self {
+            DigestAlgorithm::Md5 {
+                session,
+            }
+            | DigestAlgorithm::Sha256 {
+                session,
+            }
+            | DigestAlgorithm::Sha512_256 {
+                session,
+            } => session,
+        }
+    }
+
+    /// Returns the parameter value, ex. `"SHA-256-sess"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DigestAlgorithm::Md5 {
+                session: false,
+            } => "MD5",
+            DigestAlgorithm::Md5 {
+                session: true,
+            } => "MD5-sess",
+            DigestAlgorithm::Sha256 {
+                session: false,
+            } => "SHA-256",
+            DigestAlgorithm::Sha256 {
+                session: true,
+            } => "SHA-256-sess",
+            DigestAlgorithm::Sha512_256 {
+                session: false,
+            } => "SHA-512-256",
+            DigestAlgorithm::Sha512_256 {
+                session: true,
+            } => "SHA-512-256-sess",
+        }
+    }
+
+    /// Returns the lowercase hex-encoded hash of the `data`.
+    pub fn hash(self, data: &[u8]) -> String {
+        let bytes = match self {
+            DigestAlgorithm::Md5 {
+                ..
+            } => Md5::digest(data).to_vec(),
+            DigestAlgorithm::Sha256 {
+                ..
+            } => Sha256::digest(data).to_vec(),
+            DigestAlgorithm::Sha512_256 {
+                ..
+            } => Sha512_256::digest(data).to_vec(),
+        };
+
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+impl FromStr for DigestAlgorithm {
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, session) = match value.len().checked_sub(5) {
+            Some(at)
+                if value.is_char_boundary(at)
+                    && value[at..].eq_ignore_ascii_case("-sess") =>
+            {
+                (&value[..at], true)
+            }
+            _ => (value, false),
+        };
+
+        if name.eq_ignore_ascii_case("MD5") {
+            Ok(DigestAlgorithm::Md5 {
+                session,
+            })
+        } else if name.eq_ignore_ascii_case("SHA-256") {
+            Ok(DigestAlgorithm::Sha256 {
+                session,
+            })
+        } else if name.eq_ignore_ascii_case("SHA-512-256") {
+            Ok(DigestAlgorithm::Sha512_256 {
+                session,
+            })
+        } else {
+            Err(ParseError::Invalid)
+        }
+    }
+}
+
+impl fmt::Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DigestAlgorithm;
+
+    #[test]
+    fn test_round_trip() {
+        for value in &[
+            "MD5",
+            "MD5-sess",
+            "SHA-256",
+            "SHA-256-sess",
+            "SHA-512-256",
+            "SHA-512-256-sess",
+        ] {
+            let algorithm = value.parse::<DigestAlgorithm>().unwrap();
+            assert_eq!(algorithm.as_str(), *value);
+            assert_eq!(algorithm.is_session(), value.ends_with("-sess"));
+        }
+
+        assert_eq!(
+            "md5-SESS".parse::<DigestAlgorithm>().unwrap(),
+            DigestAlgorithm::Md5 {
+                session: true
+            }
+        );
+    }
+
+    #[test]
+    fn test_unknown() {
+        for value in &["", "-sess", "SHA-1", "SHA-256-session", "MD5 "] {
+            assert!(value.parse::<DigestAlgorithm>().is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_hash() {
+        let algorithm = DigestAlgorithm::default();
+
+        assert_eq!(algorithm.hash(b""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(
+            DigestAlgorithm::Sha256 {
+                session: false
+            }
+            .hash(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+}
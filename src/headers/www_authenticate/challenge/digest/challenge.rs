@@ -0,0 +1,215 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::str;
+
+use actix_web::http::header::{
+    HeaderValue, IntoHeaderValue, InvalidHeaderValueBytes,
+};
+use bytes::{BufMut, Bytes, BytesMut};
+
+use super::super::Challenge;
+use super::{DigestAlgorithm, NonceStore};
+use crate::utils;
+
+/// Challenge for [`WWW-Authenticate`] header with HTTP Digest auth scheme,
+/// described in [RFC 7616](https://tools.ietf.org/html/rfc7616#section-3.3)
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web_httpauth::headers::www_authenticate::digest::{Digest, DigestAlgorithm};
+/// let challenge = Digest::new("http-auth@example.org", "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v")
+///     .algorithm(DigestAlgorithm::Sha256 { session: false })
+///     .qop("auth");
+///
+/// assert_eq!(
+///     challenge.to_string(),
+///     "Digest realm=\"http-auth@example.org\", \
+///      nonce=\"7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v\", \
+///      algorithm=SHA-256, qop=\"auth\""
+/// );
+/// ```
+///
+/// [`WWW-Authenticate`]: ../struct.WwwAuthenticate.html
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Clone)]
+pub struct Digest {
+    realm: Cow<'static, str>,
+    domain: Vec<Cow<'static, str>>,
+    nonce: Cow<'static, str>,
+    opaque: Option<Cow<'static, str>>,
+    algorithm: Option<DigestAlgorithm>,
+    qop: Option<Cow<'static, str>>,
+    stale: bool,
+}
+
+impl Digest {
+    /// Creates new `Digest` challenge with the `realm` and `nonce`
+    /// attributes, ex. the one generated by the [`NonceStore`].
+    ///
+    /// [`NonceStore`]: ./trait.NonceStore.html
+    pub fn new<R, N>(realm: R, nonce: N) -> Digest
+    where
+        R: Into<Cow<'static, str>>,
+        N: Into<Cow<'static, str>>,
+    {
+        Digest {
+            realm: realm.into(),
+            domain: Vec::new(),
+            nonce: nonce.into(),
+            opaque: None,
+            algorithm: None,
+            qop: None,
+            stale: false,
+        }
+    }
+
+    /// Creates new `Digest` challenge with the `realm` attribute
+    /// and the nonce generated by the `store`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use actix_web_httpauth::headers::www_authenticate::digest::{Digest, InMemoryNonceStore};
+    /// let store = InMemoryNonceStore::new(Duration::from_secs(300));
+    /// let challenge = Digest::issue("http-auth@example.org", &store).qop("auth");
+    /// ```
+    pub fn issue<R>(realm: R, store: &dyn NonceStore) -> Digest
+    where
+        R: Into<Cow<'static, str>>,
+    {
+        Digest::new(realm, store.generate())
+    }
+
+    /// Set the `domain` attribute, the list of URIs which are sharing
+    /// the protection space, ex. `&["/api", "/admin"]`.
+    pub fn domain<I>(mut self, uris: I) -> Digest
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'static, str>>,
+    {
+        self.domain = uris.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the `opaque` attribute.
+    pub fn opaque<T: Into<Cow<'static, str>>>(mut self, value: T) -> Digest {
+        self.opaque = Some(value.into());
+        self
+    }
+
+    /// Set the `algorithm` attribute, `MD5` is implied by clients otherwise.
+    pub fn algorithm(mut self, value: DigestAlgorithm) -> Digest {
+        self.algorithm = Some(value);
+        self
+    }
+
+    /// Set the `qop` attribute, ex. `"auth"`.
+    pub fn qop<T: Into<Cow<'static, str>>>(mut self, value: T) -> Digest {
+        self.qop = Some(value.into());
+        self
+    }
+
+    /// Set the `stale` attribute, ex. for the [`NonceStatus::Stale`] nonces.
+    ///
+    /// [`NonceStatus::Stale`]: ./enum.NonceStatus.html#variant.Stale
+    pub fn stale(mut self, value: bool) -> Digest {
+        self.stale = value;
+        self
+    }
+
+    fn put_quoted(buffer: &mut BytesMut, name: &str, value: &str) {
+        buffer.extend_from_slice(b", ");
+        buffer.extend_from_slice(name.as_bytes());
+        buffer.extend_from_slice(b"=\"");
+        utils::put_quoted(buffer, value);
+        buffer.extend_from_slice(b"\"");
+    }
+}
+
+#[doc(hidden)]
+impl Challenge for Digest {
+    fn scheme(&self) -> &'static str {
+        "Digest"
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        // 16 is for `"Digest realm=\"\""`, 10 is for `", nonce=\"\""`
+        let capacity = 26 + self.realm.len() + self.nonce.len();
+        let mut buffer = BytesMut::with_capacity(capacity);
+        buffer.put("Digest realm=\"");
+        utils::put_quoted(&mut buffer, &self.realm);
+        buffer.extend_from_slice(b"\"");
+        if !self.domain.is_empty() {
+            Digest::put_quoted(&mut buffer, "domain", &self.domain.join(" "));
+        }
+        Digest::put_quoted(&mut buffer, "nonce", &self.nonce);
+
+        if let Some(ref opaque) = self.opaque {
+            Digest::put_quoted(&mut buffer, "opaque", opaque);
+        }
+
+        if let Some(algorithm) = self.algorithm {
+            buffer.extend_from_slice(b", algorithm=");
+            buffer.extend_from_slice(algorithm.as_str().as_bytes());
+        }
+
+        if let Some(ref qop) = self.qop {
+            Digest::put_quoted(&mut buffer, "qop", qop);
+        }
+
+        if self.stale {
+            buffer.extend_from_slice(b", stale=true");
+        }
+
+        buffer.freeze()
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let bytes = self.to_bytes();
+        let repr = str::from_utf8(&bytes)
+            // Should not happen since challenges are crafted manually
+            // from `&'static str`'s and Strings
+            .map_err(|_| fmt::Error)?;
+
+        f.write_str(repr)
+    }
+}
+
+impl IntoHeaderValue for Digest {
+    type Error = InvalidHeaderValueBytes;
+
+    fn try_into(self) -> Result<HeaderValue, <Self as IntoHeaderValue>::Error> {
+        HeaderValue::from_shared(self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::IntoHeaderValue;
+
+    use super::Digest;
+
+    #[test]
+    fn test_into_header_value() {
+        let challenge = Digest::new("api", "abc").opaque("xyz").stale(true);
+
+        assert_eq!(
+            challenge.try_into().unwrap(),
+            "Digest realm=\"api\", nonce=\"abc\", opaque=\"xyz\", stale=true"
+        );
+    }
+
+    #[test]
+    fn test_domain() {
+        let challenge =
+            Digest::new("api", "abc").domain(vec!["/api", "/admin"]);
+
+        assert_eq!(
+            challenge.try_into().unwrap(),
+            "Digest realm=\"api\", domain=\"/api /admin\", nonce=\"abc\""
+        );
+    }
+}
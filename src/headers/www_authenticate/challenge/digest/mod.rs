@@ -0,0 +1,20 @@
+//! Challenge for the "Digest" HTTP Authentication Scheme
+//!
+//! Digest credentials parsing is not implemented yet; this module
+//! provides the [`Digest`] challenge, the [`DigestResponse`] verification
+//! helper and the server-side [`NonceStore`], which tracks issued nonces
+//! in order to prevent replays.
+//!
+//! [`Digest`]: ./struct.Digest.html
+//! [`DigestResponse`]: ./struct.DigestResponse.html
+//! [`NonceStore`]: ./trait.NonceStore.html
+
+mod algorithm;
+mod challenge;
+mod nonce;
+mod response;
+
+pub use self::algorithm::DigestAlgorithm;
+pub use self::challenge::Digest;
+pub use self::nonce::{InMemoryNonceStore, NonceStatus, NonceStore};
+pub use self::response::DigestResponse;
@@ -0,0 +1,312 @@
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::BuildHasher;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use crate::clock::{Clock, SystemClock};
+use crate::utils;
+
+/// Result of the nonce validation by the [`NonceStore`].
+///
+/// [`NonceStore`]: ./trait.NonceStore.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceStatus {
+    /// Nonce is fresh and the `nc` counter was not used before.
+    Valid,
+    /// Nonce has expired or its `nc` counter is exhausted;
+    /// client should retry with the new nonce,
+    /// so the challenge is expected to have the `stale=true` attribute.
+    Stale,
+    /// `nc` counter is not greater than the previously used one.
+    Replayed,
+    /// Nonce was never issued by this store or was already evicted.
+    Unknown,
+}
+
+/// Storage for the issued Digest nonces.
+///
+/// [`InMemoryNonceStore`] is suitable for the single-instance deployments,
+/// distributed ones can implement this trait against the shared storage,
+/// ex. Redis.
+///
+/// [`InMemoryNonceStore`]: ./struct.InMemoryNonceStore.html
+pub trait NonceStore: Send + Sync {
+    /// Generates and remembers the new nonce.
+    fn generate(&self) -> String;
+
+    /// Validates the `nonce` and the `nc` counter sent by client.
+    ///
+    /// Counter is required to increase with each request,
+    /// as described in [RFC 7616](https://tools.ietf.org/html/rfc7616#section-3.4);
+    /// it is remembered only if the result is [`NonceStatus::Valid`].
+    ///
+    /// [`NonceStatus::Valid`]: ./enum.NonceStatus.html#variant.Valid
+    fn validate(&self, nonce: &str, nc: u32) -> NonceStatus;
+}
+
+#[derive(Debug)]
+struct Entry {
+    issued: SystemTime,
+    last_nc: Option<u32>,
+}
+
+#[derive(Debug, Default)]
+struct Shard {
+    entries: HashMap<String, Entry>,
+    /// Nonces in the order they were issued, oldest first.
+    issued: VecDeque<(SystemTime, String)>,
+}
+
+/// In-memory [`NonceStore`] implementation.
+///
+/// Nonces are 128 bits read from the OS random number generator.
+/// They are valid for the configured `ttl` and are reported as
+/// [`NonceStatus::Stale`] during the same period after that;
+/// older ones are evicted. Store keeps at most
+/// [`capacity`](#method.capacity) nonces, evicting the oldest ones,
+/// so the unauthenticated requests can't grow it without limit;
+/// clients of the evicted nonces are challenged again.
+/// Storage is split into the shards to reduce the lock contention.
+///
+/// ## Example
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use actix_web_httpauth::headers::www_authenticate::digest::{
+/// #     InMemoryNonceStore, NonceStatus, NonceStore,
+/// # };
+/// let store = InMemoryNonceStore::new(Duration::from_secs(300));
+/// let nonce = store.generate();
+///
+/// assert_eq!(store.validate(&nonce, 1), NonceStatus::Valid);
+/// assert_eq!(store.validate(&nonce, 1), NonceStatus::Replayed);
+/// assert_eq!(store.validate(&nonce, 2), NonceStatus::Valid);
+/// ```
+///
+/// [`NonceStore`]: ./trait.NonceStore.html
+/// [`NonceStatus::Stale`]: ./enum.NonceStatus.html#variant.Stale
+pub struct InMemoryNonceStore {
+    shards: Vec<Mutex<Shard>>,
+    ttl: Duration,
+    shard_capacity: usize,
+    clock: Arc<dyn Clock>,
+    keys: RandomState,
+}
+
+impl InMemoryNonceStore {
+    const SHARDS: usize = 16;
+
+    /// Creates new store, which nonces are valid for `ttl`.
+    ///
+    /// By default at most 65536 nonces are kept.
+    pub fn new(ttl: Duration) -> InMemoryNonceStore {
+        InMemoryNonceStore {
+            shards: (0..Self::SHARDS).map(|_| Mutex::default()).collect(),
+            ttl,
+            shard_capacity: 65536 / Self::SHARDS,
+            clock: Arc::new(SystemClock),
+            keys: RandomState::new(),
+        }
+    }
+
+    /// Set the time source, ex. the [`ManualClock`] for tests.
+    ///
+    /// [`ManualClock`]: ../../../clock/struct.ManualClock.html
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> InMemoryNonceStore {
+        self.clock = clock;
+        self
+    }
+
+    /// Set the maximal number of the kept nonces,
+    /// rounded up to the multiple of the shards number.
+    pub fn capacity(mut self, value: usize) -> InMemoryNonceStore {
+        self.shard_capacity = value.div_ceil(Self::SHARDS).max(1);
+        self
+    }
+
+    fn shard(&self, nonce: &str) -> MutexGuard<'_, Shard> {
+        let hash = self.keys.hash_one(nonce);
+        let shard = &self.shards[hash as usize % Self::SHARDS];
+
+        match shard.lock() {
+            Ok(shard) => shard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Age of the entry, clock going backwards is treated as no age at all.
+    fn age(&self, entry: &Entry, now: SystemTime) -> Duration {
+        now.duration_since(entry.issued).unwrap_or_default()
+    }
+}
+
+impl NonceStore for InMemoryNonceStore {
+    fn generate(&self) -> String {
+        let now = self.clock.now();
+        let nonce = utils::random_token();
+
+        let mut shard = self.shard(&nonce);
+        // Each nonce is popped once, so eviction is amortized O(1)
+        while let Some(&(issued, _)) = shard.issued.front() {
+            let age = now.duration_since(issued).unwrap_or_default();
+            if age <= self.ttl * 2 && shard.issued.len() < self.shard_capacity {
+                break;
+            }
+            if let Some((_, evicted)) = shard.issued.pop_front() {
+                shard.entries.remove(&evicted);
+            }
+        }
+        shard.issued.push_back((now, nonce.clone()));
+        shard.entries.insert(
+            nonce.clone(),
+            Entry {
+                issued: now,
+                last_nc: None,
+            },
+        );
+
+        nonce
+    }
+
+    fn validate(&self, nonce: &str, nc: u32) -> NonceStatus {
+        let now = self.clock.now();
+        let mut shard = self.shard(nonce);
+        let entry = match shard.entries.get_mut(nonce) {
+            Some(entry) => entry,
+            None => return NonceStatus::Unknown,
+        };
+
+        let age = self.age(entry, now);
+        if age > self.ttl * 2 {
+            shard.entries.remove(nonce);
+            return NonceStatus::Unknown;
+        } else if age > self.ttl {
+            return NonceStatus::Stale;
+        }
+
+        match entry.last_nc {
+            // Counter can't grow anymore, new nonce is required
+            Some(u32::MAX) => NonceStatus::Stale,
+            Some(last_nc) if nc <= last_nc => NonceStatus::Replayed,
+            _ => {
+                entry.last_nc = Some(nc);
+                NonceStatus::Valid
+            }
+        }
+    }
+}
+
+impl fmt::Debug for InMemoryNonceStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InMemoryNonceStore")
+            .field("ttl", &self.ttl)
+            .field("capacity", &(self.shard_capacity * Self::SHARDS))
+            .field("clock", &self.clock)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{InMemoryNonceStore, NonceStatus, NonceStore};
+    use crate::clock::ManualClock;
+
+    fn store() -> (InMemoryNonceStore, ManualClock) {
+        let clock = ManualClock::new(UNIX_EPOCH);
+        let store = InMemoryNonceStore::new(Duration::from_secs(60))
+            .clock(Arc::new(clock.clone()));
+
+        (store, clock)
+    }
+
+    #[test]
+    fn test_unique_nonces() {
+        let (store, _) = store();
+
+        assert_ne!(store.generate(), store.generate());
+    }
+
+    #[test]
+    fn test_replay_rejected() {
+        let (store, _) = store();
+        let nonce = store.generate();
+
+        assert_eq!(store.validate(&nonce, 1), NonceStatus::Valid);
+        assert_eq!(store.validate(&nonce, 1), NonceStatus::Replayed);
+        assert_eq!(store.validate(&nonce, 3), NonceStatus::Valid);
+        assert_eq!(store.validate(&nonce, 2), NonceStatus::Replayed);
+    }
+
+    #[test]
+    fn test_unknown_nonce() {
+        let (store, _) = store();
+
+        assert_eq!(store.validate("deadbeef", 1), NonceStatus::Unknown);
+    }
+
+    #[test]
+    fn test_expiration() {
+        let (store, clock) = store();
+        let nonce = store.generate();
+
+        clock.advance(Duration::from_secs(61));
+        assert_eq!(store.validate(&nonce, 1), NonceStatus::Stale);
+
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(store.validate(&nonce, 1), NonceStatus::Unknown);
+    }
+
+    #[test]
+    fn test_eviction() {
+        let (store, clock) = store();
+        let nonces = (0..64).map(|_| store.generate()).collect::<Vec<_>>();
+
+        clock.advance(Duration::from_secs(121));
+        for _ in 0..64 {
+            store.generate();
+        }
+
+        let remembered = store
+            .shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().entries.len())
+            .sum::<usize>();
+        assert!(remembered < 128);
+        assert!(nonces
+            .iter()
+            .all(|nonce| store.validate(nonce, 1) == NonceStatus::Unknown));
+    }
+
+    #[test]
+    fn test_capacity() {
+        let (store, _) = store();
+        let store = store.capacity(32);
+        let nonces = (0..256).map(|_| store.generate()).collect::<Vec<_>>();
+
+        for shard in &store.shards {
+            let shard = shard.lock().unwrap();
+            assert!(shard.entries.len() <= 2);
+            assert!(shard.issued.len() <= 2);
+        }
+        let valid = nonces
+            .iter()
+            .filter(|nonce| store.validate(nonce, 1) == NonceStatus::Valid)
+            .count();
+        assert!(valid > 0 && valid <= 32);
+        assert_eq!(store.validate(&store.generate(), 1), NonceStatus::Valid);
+    }
+
+    #[test]
+    fn test_nc_rollover() {
+        let (store, _) = store();
+        let nonce = store.generate();
+
+        assert_eq!(store.validate(&nonce, u32::MAX), NonceStatus::Valid);
+        assert_eq!(store.validate(&nonce, 1), NonceStatus::Stale);
+    }
+}
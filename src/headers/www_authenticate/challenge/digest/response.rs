@@ -0,0 +1,224 @@
+use super::{DigestAlgorithm, NonceStatus, NonceStore};
+use crate::utils;
+
+/// Digest credentials parameters, required to verify the client `response`,
+/// as described in [RFC 7616](https://tools.ietf.org/html/rfc7616#section-3.4.1).
+///
+/// Parameters are expected to be taken from the `Authorization` header
+/// as is; `qop`, `nc` and `cnonce` are absent for the legacy
+/// [RFC 2069](https://tools.ietf.org/html/rfc2069) clients.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web_httpauth::headers::www_authenticate::digest::{DigestAlgorithm, DigestResponse};
+/// let response = DigestResponse {
+///     algorithm: DigestAlgorithm::default(),
+///     username: "Mufasa",
+///     realm: "testrealm@host.com",
+///     nonce: "dcd98b7102dd2f0e8b11d0f600bfb0c093",
+///     uri: "/dir/index.html",
+///     qop: Some("auth"),
+///     nc: Some("00000001"),
+///     cnonce: Some("0a4f113b"),
+///     response: "6629fae49393a05397450978507c4ef1",
+/// };
+///
+/// assert!(response.verify("GET", "Circle Of Life"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigestResponse<'a> {
+    /// `algorithm` parameter, `MD5` if absent.
+    pub algorithm: DigestAlgorithm,
+    /// `username` parameter.
+    pub username: &'a str,
+    /// `realm` parameter.
+    pub realm: &'a str,
+    /// `nonce` parameter.
+    pub nonce: &'a str,
+    /// `uri` parameter.
+    pub uri: &'a str,
+    /// `qop` parameter.
+    pub qop: Option<&'a str>,
+    /// `nc` parameter, hex-encoded nonce counter.
+    pub nc: Option<&'a str>,
+    /// `cnonce` parameter.
+    pub cnonce: Option<&'a str>,
+    /// `response` parameter.
+    pub response: &'a str,
+}
+
+impl<'a> DigestResponse<'a> {
+    /// Returns `true` if the client `response` matches the one
+    /// computed for the request `method` and the user `password`.
+    ///
+    /// Responses are compared in constant time.
+    pub fn verify(&self, method: &str, password: &str) -> bool {
+        let secret = format!("{}:{}:{}", self.username, self.realm, password);
+        let mut ha1 = self.algorithm.hash(secret.as_bytes());
+        if self.algorithm.is_session() {
+            let cnonce = match self.cnonce {
+                Some(cnonce) => cnonce,
+                None => return false,
+            };
+            let session = format!("{}:{}:{}", ha1, self.nonce, cnonce);
+            ha1 = self.algorithm.hash(session.as_bytes());
+        }
+
+        let ha2 = self
+            .algorithm
+            .hash(format!("{}:{}", method, self.uri).as_bytes());
+        let expected = match (self.qop, self.nc, self.cnonce) {
+            (Some(qop), Some(nc), Some(cnonce)) => format!(
+                "{}:{}:{}:{}:{}:{}",
+                ha1, self.nonce, nc, cnonce, qop, ha2
+            ),
+            (None, None, None) => format!("{}:{}:{}", ha1, self.nonce, ha2),
+            _ => return false,
+        };
+        let expected = self.algorithm.hash(expected.as_bytes());
+
+        utils::constant_time_eq(
+            expected.as_bytes(),
+            self.response.to_ascii_lowercase().as_bytes(),
+        )
+    }
+
+    /// Verifies the client `response` like [`verify`](#method.verify)
+    /// does, then checks the `nonce` and the `nc` counter with the `store`.
+    ///
+    /// Returns `None` if the response does not match, so the store is
+    /// never touched by the unauthenticated requests; a malformed `nc`
+    /// is reported as [`NonceStatus::Replayed`]. Legacy clients, which are
+    /// not sending the counter, can use each nonce only once.
+    ///
+    /// [`NonceStatus::Replayed`]: ./enum.NonceStatus.html#variant.Replayed
+    pub fn verify_with_store(
+        &self,
+        method: &str,
+        password: &str,
+        store: &dyn NonceStore,
+    ) -> Option<NonceStatus> {
+        if !self.verify(method, password) {
+            return None;
+        }
+
+        let nc = match self.nc {
+            Some(nc) => match u32::from_str_radix(nc, 16) {
+                Ok(nc) => nc,
+                Err(_) => return Some(NonceStatus::Replayed),
+            },
+            None => 1,
+        };
+
+        Some(store.validate(self.nonce, nc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{DigestAlgorithm, DigestResponse};
+    use crate::headers::www_authenticate::digest::{
+        InMemoryNonceStore, NonceStatus, NonceStore,
+    };
+
+    // RFC 7616, Section 3.9.1
+    fn sha256_response() -> DigestResponse<'static> {
+        DigestResponse {
+            algorithm: DigestAlgorithm::Sha256 {
+                session: false,
+            },
+            username: "Mufasa",
+            realm: "http-auth@example.org",
+            nonce: "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v",
+            uri: "/dir/index.html",
+            qop: Some("auth"),
+            nc: Some("00000001"),
+            cnonce: Some("f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ"),
+            response: "753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1",
+        }
+    }
+
+    #[test]
+    fn test_verify_sha256() {
+        let response = sha256_response();
+
+        assert!(response.verify("GET", "Circle of Life"));
+        assert!(!response.verify("GET", "Circle of Death"));
+        assert!(!response.verify("POST", "Circle of Life"));
+    }
+
+    #[test]
+    fn test_verify_md5() {
+        let response = DigestResponse {
+            algorithm: DigestAlgorithm::default(),
+            response: "8ca523f5e9506fed4657c9700eebdbec",
+            ..sha256_response()
+        };
+
+        assert!(response.verify("GET", "Circle of Life"));
+    }
+
+    #[test]
+    fn test_verify_with_store() {
+        let store = InMemoryNonceStore::new(Duration::from_secs(60));
+        assert_eq!(
+            sha256_response().verify_with_store(
+                "GET",
+                "Circle of Life",
+                &store
+            ),
+            Some(NonceStatus::Unknown)
+        );
+
+        let nonce = store.generate();
+        let verify = |nc: &str, password: &str| {
+            let algorithm = DigestAlgorithm::default();
+            let ha1 =
+                algorithm.hash(format!("Mufasa:realm:{}", password).as_bytes());
+            let ha2 = algorithm.hash(b"GET:/");
+            let expected =
+                format!("{}:{}:{}:cnonce:auth:{}", ha1, nonce, nc, ha2);
+            let response = algorithm.hash(expected.as_bytes());
+
+            DigestResponse {
+                algorithm,
+                username: "Mufasa",
+                realm: "realm",
+                nonce: &nonce,
+                uri: "/",
+                qop: Some("auth"),
+                nc: Some(nc),
+                cnonce: Some("cnonce"),
+                response: &response,
+            }
+            .verify_with_store("GET", "Circle of Life", &store)
+        };
+
+        assert_eq!(verify("00000001", "Circle of Death"), None);
+        assert_eq!(
+            verify("00000001", "Circle of Life"),
+            Some(NonceStatus::Valid)
+        );
+        assert_eq!(
+            verify("00000001", "Circle of Life"),
+            Some(NonceStatus::Replayed)
+        );
+        assert_eq!(
+            verify("00000002", "Circle of Life"),
+            Some(NonceStatus::Valid)
+        );
+    }
+
+    #[test]
+    fn test_incomplete_qop() {
+        let response = DigestResponse {
+            cnonce: None,
+            ..sha256_response()
+        };
+
+        assert!(!response.verify("GET", "Circle of Life"));
+    }
+}
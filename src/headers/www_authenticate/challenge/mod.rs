@@ -5,11 +5,25 @@ use bytes::Bytes;
 
 pub mod basic;
 pub mod bearer;
+pub mod bearer_or_basic;
+pub mod digest;
+pub mod signature;
+pub mod timestamp_key;
+pub mod token;
 
 /// Authentication challenge for `WWW-Authenticate` header.
 pub trait Challenge:
     IntoHeaderValue + Debug + Display + Clone + Send + Sync
 {
+    /// Returns the authentication scheme name, ex. `"Basic"`.
+    fn scheme(&self) -> &'static str;
+
+    /// Returns the error code carried by the challenge, if any,
+    /// ex. `"invalid_token"` for the "Bearer" one.
+    fn error_code(&self) -> Option<&'static str> {
+        None
+    }
+
     /// Converts the challenge into a bytes suitable for HTTP transmission.
     fn to_bytes(&self) -> Bytes;
 }
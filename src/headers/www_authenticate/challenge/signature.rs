@@ -0,0 +1,132 @@
+//! Challenge for the "Signature" HTTP Authentication Scheme
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str;
+
+use actix_web::http::header::{
+    HeaderValue, IntoHeaderValue, InvalidHeaderValueBytes,
+};
+use bytes::{Bytes, BytesMut};
+
+use super::Challenge;
+use crate::headers::www_authenticate::ext_value;
+
+/// Challenge for [`WWW-Authenticate`] header with HTTP Signature auth scheme,
+/// described in [HTTP Signatures draft](https://tools.ietf.org/html/draft-cavage-http-signatures-12#section-3.1)
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web_httpauth::headers::www_authenticate::signature::Signature;
+/// let challenge = Signature::new()
+///     .realm("Example")
+///     .headers("(request-target) (created) host");
+///
+/// assert_eq!(
+///     challenge.to_string(),
+///     "Signature realm=\"Example\", headers=\"(request-target) (created) host\""
+/// );
+/// ```
+///
+/// [`WWW-Authenticate`]: ../struct.WwwAuthenticate.html
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Clone)]
+pub struct Signature {
+    pub(crate) realm: Option<Cow<'static, str>>,
+    pub(crate) headers: Option<Cow<'static, str>>,
+}
+
+impl Signature {
+    /// Creates new `Signature` challenge without any attributes.
+    pub fn new() -> Signature {
+        Signature::default()
+    }
+
+    /// Set the `realm` attribute.
+    pub fn realm<T>(mut self, value: T) -> Signature
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.realm = Some(value.into());
+        self
+    }
+
+    /// Set the `headers` attribute, the space-delimited list
+    /// of headers which the client is expected to sign.
+    pub fn headers<T>(mut self, value: T) -> Signature
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.headers = Some(value.into());
+        self
+    }
+}
+
+#[doc(hidden)]
+impl Challenge for Signature {
+    fn scheme(&self) -> &'static str {
+        "Signature"
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        // 9 is for `"Signature"` and `", realm=\"\""`, 12 for `",
+        // headers=\"\""`
+        let capacity = 9
+            + self.realm.as_ref().map_or(0, |realm| realm.len() + 10)
+            + self
+                .headers
+                .as_ref()
+                .map_or(0, |headers| headers.len() + 12);
+        let mut buffer = BytesMut::with_capacity(capacity);
+        buffer.extend_from_slice(b"Signature");
+
+        if let Some(ref realm) = self.realm {
+            ext_value::put_param(&mut buffer, "realm", realm);
+        }
+
+        if let Some(ref headers) = self.headers {
+            ext_value::put_param(&mut buffer, "headers", headers);
+        }
+
+        buffer.freeze()
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let bytes = self.to_bytes();
+        let repr = str::from_utf8(&bytes)
+            // Should not happen since challenges are crafted manually
+            // from `&'static str`'s and Strings
+            .map_err(|_| fmt::Error)?;
+
+        f.write_str(repr)
+    }
+}
+
+impl IntoHeaderValue for Signature {
+    type Error = InvalidHeaderValueBytes;
+
+    fn try_into(self) -> Result<HeaderValue, <Self as IntoHeaderValue>::Error> {
+        HeaderValue::from_shared(self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::IntoHeaderValue;
+
+    use super::Signature;
+
+    #[test]
+    fn test_into_header_value() {
+        assert_eq!(Signature::new().try_into().unwrap(), "Signature");
+        assert_eq!(
+            Signature::new()
+                .headers("(created) date")
+                .try_into()
+                .unwrap(),
+            "Signature headers=\"(created) date\""
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Challenge for the "TSKey" HTTP Authentication Scheme
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str;
+
+use actix_web::http::header::{
+    HeaderValue, IntoHeaderValue, InvalidHeaderValueBytes,
+};
+use bytes::{Bytes, BytesMut};
+
+use super::Challenge;
+use crate::headers::www_authenticate::ext_value;
+
+/// `TSKey` authentication error types.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Error {
+    /// The timestamp is outside of the allowed window.
+    Stale,
+
+    /// The key is unknown or the signature does not match.
+    Invalid,
+}
+
+impl Error {
+    #[doc(hidden)]
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Error::Stale => "stale",
+            Error::Invalid => "invalid",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Challenge for [`WWW-Authenticate`] header with the `TSKey` auth scheme.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web_httpauth::headers::www_authenticate::timestamp_key::{Error, TimestampKey};
+/// let challenge = TimestampKey::new().realm("internal").error(Error::Stale);
+///
+/// assert_eq!(
+///     challenge.to_string(),
+///     "TSKey realm=\"internal\", error=\"stale\""
+/// );
+/// ```
+///
+/// [`WWW-Authenticate`]: ../struct.WwwAuthenticate.html
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Clone)]
+pub struct TimestampKey {
+    pub(crate) realm: Option<Cow<'static, str>>,
+    pub(crate) error: Option<Error>,
+}
+
+impl TimestampKey {
+    /// Creates new `TSKey` challenge without any attributes.
+    pub fn new() -> TimestampKey {
+        TimestampKey::default()
+    }
+
+    /// Set the `realm` attribute.
+    pub fn realm<T>(mut self, value: T) -> TimestampKey
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.realm = Some(value.into());
+        self
+    }
+
+    /// Set the `error` attribute.
+    pub fn error(mut self, value: Error) -> TimestampKey {
+        self.error = Some(value);
+        self
+    }
+}
+
+#[doc(hidden)]
+impl Challenge for TimestampKey {
+    fn scheme(&self) -> &'static str {
+        "TSKey"
+    }
+
+    fn error_code(&self) -> Option<&'static str> {
+        self.error.as_ref().map(Error::as_str)
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        // 5 is for `"TSKey"`, 10 for `", realm=\"\""`, 18 for the error one
+        let capacity = 5
+            + self.realm.as_ref().map_or(0, |realm| realm.len() + 10)
+            + self.error.map_or(0, |_| 18);
+        let mut buffer = BytesMut::with_capacity(capacity);
+        buffer.extend_from_slice(b"TSKey");
+
+        if let Some(ref realm) = self.realm {
+            ext_value::put_param(&mut buffer, "realm", realm);
+        }
+
+        if let Some(ref error) = self.error {
+            ext_value::put_param(&mut buffer, "error", error.as_str());
+        }
+
+        buffer.freeze()
+    }
+}
+
+impl fmt::Display for TimestampKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let bytes = self.to_bytes();
+        let repr = str::from_utf8(&bytes)
+            // Should not happen since challenges are crafted manually
+            // from `&'static str`'s and Strings
+            .map_err(|_| fmt::Error)?;
+
+        f.write_str(repr)
+    }
+}
+
+impl IntoHeaderValue for TimestampKey {
+    type Error = InvalidHeaderValueBytes;
+
+    fn try_into(self) -> Result<HeaderValue, <Self as IntoHeaderValue>::Error> {
+        HeaderValue::from_shared(self.to_bytes())
+    }
+}
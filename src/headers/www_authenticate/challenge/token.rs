@@ -0,0 +1,86 @@
+//! Challenge for the GitHub-style "Token" HTTP Authentication Scheme
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str;
+
+use actix_web::http::header::{
+    HeaderValue, IntoHeaderValue, InvalidHeaderValueBytes,
+};
+use bytes::{Bytes, BytesMut};
+
+use super::Challenge;
+use crate::headers::www_authenticate::ext_value;
+
+/// Challenge for [`WWW-Authenticate`] header with the `Token` auth scheme.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web_httpauth::headers::www_authenticate::token::Token;
+/// let challenge = Token::with_realm("api");
+///
+/// assert_eq!(challenge.to_string(), "Token realm=\"api\"");
+/// ```
+///
+/// [`WWW-Authenticate`]: ../struct.WwwAuthenticate.html
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Clone)]
+pub struct Token {
+    pub(crate) realm: Option<Cow<'static, str>>,
+}
+
+impl Token {
+    /// Creates new `Token` challenge with an empty `realm` field.
+    pub fn new() -> Token {
+        Token::default()
+    }
+
+    /// Creates new `Token` challenge from the provided `realm` field value.
+    pub fn with_realm<T>(value: T) -> Token
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        Token {
+            realm: Some(value.into()),
+        }
+    }
+}
+
+#[doc(hidden)]
+impl Challenge for Token {
+    fn scheme(&self) -> &'static str {
+        "Token"
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        // 5 is for `"Token"`, 9 is for `" realm=\"\""`
+        let length = 5 + self.realm.as_ref().map_or(0, |realm| realm.len() + 9);
+        let mut buffer = BytesMut::with_capacity(length);
+        buffer.extend_from_slice(b"Token");
+        if let Some(ref realm) = self.realm {
+            ext_value::put_param(&mut buffer, "realm", realm);
+        }
+
+        buffer.freeze()
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let bytes = self.to_bytes();
+        let repr = str::from_utf8(&bytes)
+            // Should not happen since challenges are crafted manually
+            // from `&'static str`'s and Strings
+            .map_err(|_| fmt::Error)?;
+
+        f.write_str(repr)
+    }
+}
+
+impl IntoHeaderValue for Token {
+    type Error = InvalidHeaderValueBytes;
+
+    fn try_into(self) -> Result<HeaderValue, <Self as IntoHeaderValue>::Error> {
+        HeaderValue::from_shared(self.to_bytes())
+    }
+}
@@ -0,0 +1,162 @@
+//! RFC 8187 extended parameter values, ex. `realm*=UTF-8''My%20Realm`.
+
+use bytes::BytesMut;
+
+use crate::utils;
+
+/// Characters which are allowed in the extended value unescaped,
+/// [RFC 8187, Section 3.2.1](https://tools.ietf.org/html/rfc8187#section-3.2.1).
+fn is_attr_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte)
+}
+
+/// Encodes the `value` as the `UTF-8` extended value.
+pub(crate) fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(7 + value.len());
+    encoded.push_str("UTF-8''");
+    for &byte in value.as_bytes() {
+        if is_attr_char(byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    encoded
+}
+
+/// Puts the challenge parameter with the `name`,
+/// uses the extended notation if the `value` is not the ASCII one.
+///
+/// Parameters are separated from the scheme with a space and from each
+/// other with a comma, as required by
+/// [RFC 7235](https://tools.ietf.org/html/rfc7235#section-2.1);
+/// empty `value` is skipped entirely.
+pub(crate) fn put_param(buffer: &mut BytesMut, name: &str, value: &str) {
+    if value.is_empty() {
+        return;
+    }
+
+    put_separator(buffer);
+    buffer.extend_from_slice(name.as_bytes());
+    if value.is_ascii() {
+        buffer.extend_from_slice(b"=\"");
+        utils::put_quoted(buffer, value);
+        buffer.extend_from_slice(b"\"");
+    } else {
+        buffer.extend_from_slice(b"*=");
+        buffer.extend_from_slice(encode(value).as_bytes());
+    }
+}
+
+/// Puts the separator before the next challenge parameter.
+///
+/// Challenge starts with the scheme token, which has no spaces,
+/// so the buffer without any spaces has no parameters yet.
+pub(crate) fn put_separator(buffer: &mut BytesMut) {
+    if buffer.contains(&b' ') {
+        buffer.extend_from_slice(b", ");
+    } else {
+        buffer.extend_from_slice(b" ");
+    }
+}
+
+fn hex(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Decodes the [RFC 8187](https://tools.ietf.org/html/rfc8187) extended
+/// parameter value, ex. the `realm*` one sent by server:
+/// `UTF-8''My%20Realm`.
+///
+/// `UTF-8` and `ISO-8859-1` charsets are supported, language tag is ignored.
+/// Returns `None` if value is malformed.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web_httpauth::headers::www_authenticate::decode_extended_value;
+/// let realm = decode_extended_value("UTF-8'en'%E2%82%AC%20rates");
+///
+/// assert_eq!(realm.unwrap(), "€ rates");
+/// ```
+pub fn decode_extended_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let charset = parts.next()?;
+    let _language = parts.next()?;
+    let encoded = parts.next()?.as_bytes();
+
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        match encoded[i] {
+            b'%' => {
+                let high = hex(*encoded.get(i + 1)?)?;
+                let low = hex(*encoded.get(i + 2)?)?;
+                bytes.push(high << 4 | low);
+                i += 3;
+            }
+            byte if is_attr_char(byte) => {
+                bytes.push(byte);
+                i += 1;
+            }
+            _ => return None,
+        }
+    }
+
+    if charset.eq_ignore_ascii_case("UTF-8") {
+        String::from_utf8(bytes).ok()
+    } else if charset.eq_ignore_ascii_case("ISO-8859-1") {
+        Some(bytes.into_iter().map(char::from).collect())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_extended_value, encode};
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode("My Realm"), "UTF-8''My%20Realm");
+        assert_eq!(encode("Überall"), "UTF-8''%C3%9Cberall");
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            decode_extended_value("UTF-8''My%20Realm").unwrap(),
+            "My Realm"
+        );
+        assert_eq!(
+            decode_extended_value("utf-8'de'%C3%9Cberall").unwrap(),
+            "Überall"
+        );
+        assert_eq!(
+            decode_extended_value("iso-8859-1'en'%A3%20rates").unwrap(),
+            "£ rates"
+        );
+    }
+
+    #[test]
+    fn test_decode_malformed() {
+        assert!(decode_extended_value("My%20Realm").is_none());
+        assert!(decode_extended_value("UTF-8''My Realm").is_none());
+        assert!(decode_extended_value("UTF-8''%C3").is_none());
+        assert!(decode_extended_value("UTF-8''%4").is_none());
+        assert!(decode_extended_value("KOI8-R''%C1").is_none());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let realm = "Зона ограниченного доступа";
+
+        assert_eq!(decode_extended_value(&encode(realm)).unwrap(), realm);
+    }
+}
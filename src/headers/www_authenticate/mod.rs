@@ -1,9 +1,18 @@
 //! `WWW-Authenticate` header and various auth challenges
 
 mod challenge;
+mod ext_value;
 mod header;
+mod params;
 
 pub use self::challenge::basic;
 pub use self::challenge::bearer;
+pub use self::challenge::bearer_or_basic;
+pub use self::challenge::digest;
+pub use self::challenge::signature;
+pub use self::challenge::timestamp_key;
+pub use self::challenge::token;
 pub use self::challenge::Challenge;
+pub use self::ext_value::decode_extended_value;
 pub use self::header::WwwAuthenticate;
+pub use self::params::ChallengeParams;
@@ -0,0 +1,202 @@
+use std::str;
+
+use actix_web::http::header::{HeaderValue, InvalidHeaderValueBytes};
+use bytes::BytesMut;
+
+use super::ext_value;
+use super::Challenge;
+use crate::headers::authorization::{parse_auth_params, split_scheme};
+
+/// Canonical order of the standard challenge parameters,
+/// the other ones are serialized after them.
+const CANONICAL_ORDER: [&str; 6] = [
+    "realm",
+    "domain",
+    "scope",
+    "error",
+    "error_description",
+    "error_uri",
+];
+
+/// Returns the serialization rank of the `name` parameter,
+/// the same one for all non-standard parameters.
+pub(crate) fn param_rank(name: &str) -> usize {
+    CANONICAL_ORDER
+        .iter()
+        .position(|param| param.eq_ignore_ascii_case(name))
+        .unwrap_or(CANONICAL_ORDER.len())
+}
+
+/// Parameters of the challenge, which are about to be sent
+/// in the `WWW-Authenticate` header, see
+/// [`AuthenticationError::with_challenge_interceptor`].
+///
+/// Parameter names are matched case-insensitively. Values are serialized
+/// back as the quoted strings, or as the [RFC 8187] extended values
+/// if they are not ASCII. The standard parameters are kept in the
+/// canonical order: `realm`, `domain`, `scope`, `error`,
+/// `error_description` and `error_uri`, followed by the other ones.
+///
+/// [`AuthenticationError::with_challenge_interceptor`]: ../../extractors/struct.AuthenticationError.html#method.with_challenge_interceptor
+/// [RFC 8187]: https://tools.ietf.org/html/rfc8187
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChallengeParams {
+    scheme: String,
+    params: Vec<(String, String)>,
+}
+
+impl ChallengeParams {
+    /// Splits the serialized `challenge` into the parameters,
+    /// `None` is returned if it can't be parsed.
+    pub(crate) fn from_challenge<C: Challenge>(
+        challenge: &C,
+    ) -> Option<ChallengeParams> {
+        let bytes = challenge.to_bytes();
+        let (scheme, params) = split_scheme(str::from_utf8(&bytes).ok()?);
+        let params = parse_auth_params(params.unwrap_or(""))
+            .ok()?
+            .into_iter()
+            .map(
+                |(name, value): (&str, String)| match name.strip_suffix('*') {
+                    Some(name) => ext_value::decode_extended_value(&value)
+                        .map(|value| (name.to_string(), value)),
+                    None => Some((name.to_string(), value)),
+                },
+            )
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(ChallengeParams {
+            scheme: scheme.to_string(),
+            params,
+        })
+    }
+
+    /// Returns the authentication scheme name, ex. `"Bearer"`.
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// Returns the value of the `name` parameter.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the value of the `name` parameter. New standard parameters
+    /// are inserted at their canonical position, the other ones
+    /// are appended after the existing ones.
+    pub fn set<N, V>(&mut self, name: N, value: V)
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        let name = name.into();
+        let value = value.into();
+        match self
+            .params
+            .iter_mut()
+            .find(|(param, _)| param.eq_ignore_ascii_case(&name))
+        {
+            Some(param) => param.1 = value,
+            None => {
+                let rank = param_rank(&name);
+                let index = self
+                    .params
+                    .iter()
+                    .position(|(param, _)| param_rank(param) > rank)
+                    .unwrap_or(self.params.len());
+                self.params.insert(index, (name, value));
+            }
+        }
+    }
+
+    /// Removes the `name` parameter, returning its value.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let index = self
+            .params
+            .iter()
+            .position(|(param, _)| param.eq_ignore_ascii_case(name))?;
+
+        Some(self.params.remove(index).1)
+    }
+
+    /// Returns the iterator over the parameter names and values,
+    /// in the order of serialization.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub(crate) fn to_header_value(
+        &self,
+    ) -> Result<HeaderValue, InvalidHeaderValueBytes> {
+        let mut buffer = BytesMut::with_capacity(64);
+        buffer.extend_from_slice(self.scheme.as_bytes());
+        for (name, value) in &self.params {
+            ext_value::put_param(&mut buffer, name, value);
+        }
+
+        HeaderValue::from_shared(buffer.freeze())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChallengeParams;
+    use crate::headers::www_authenticate::basic::Basic;
+    use crate::headers::www_authenticate::bearer::{Bearer, Error};
+
+    #[test]
+    fn test_round_trip() {
+        let challenge = Bearer::build()
+            .realm("€ rates")
+            .scope("openid")
+            .error(Error::InvalidToken)
+            .finish();
+        let mut params = ChallengeParams::from_challenge(&challenge).unwrap();
+
+        assert_eq!(params.scheme(), "Bearer");
+        assert_eq!(params.get("Realm"), Some("€ rates"));
+        assert_eq!(params.remove("scope").as_deref(), Some("openid"));
+        params.set("error", "invalid_request");
+        params.set("notice", "Authorized use \"only\"");
+
+        assert_eq!(
+            params.to_header_value().unwrap(),
+            "Bearer realm*=UTF-8''%E2%82%AC%20rates, error=\"invalid_request\", \
+             notice=\"Authorized use \\\"only\\\"\""
+        );
+    }
+
+    #[test]
+    fn test_canonical_order() {
+        let challenge = Bearer::build()
+            .error(Error::InsufficientScope)
+            .param("tenant", "acme")
+            .finish();
+        let mut params = ChallengeParams::from_challenge(&challenge).unwrap();
+        params.set("error_uri", "https://example.com/errors");
+        params.set("scope", "admin");
+        params.set("realm", "example");
+
+        assert_eq!(
+            params.to_header_value().unwrap(),
+            "Bearer realm=\"example\", scope=\"admin\", \
+             error=\"insufficient_scope\", \
+             error_uri=\"https://example.com/errors\", tenant=\"acme\""
+        );
+
+        let mut params =
+            ChallengeParams::from_challenge(&Basic::new()).unwrap();
+        params.set("charset", "UTF-8");
+        params.set("realm", "Restricted area");
+
+        assert_eq!(
+            params.to_header_value().unwrap(),
+            "Basic realm=\"Restricted area\", charset=\"UTF-8\""
+        );
+    }
+}
@@ -9,6 +9,9 @@
 //!
 //!  * `Basic`, as defined in [RFC7617](https://tools.ietf.org/html/rfc7617)
 //!  * `Bearer`, as defined in [RFC6750](https://tools.ietf.org/html/rfc6750)
+//!  * `Token`, as used by GitHub and similar APIs
+//!  * `Signature` with HMAC, as defined in [HTTP Signatures draft](https://tools.ietf.org/html/draft-cavage-http-signatures-12)
+//!  * `TSKey`, the pre-shared key HMAC of the request timestamp
 //!
 //! [Authorization]: `crate::headers::authorization::Authorization`
 //! [WWW-Authenticate]: `crate::headers::www_authenticate::WwwAuthenticate`
@@ -20,7 +23,14 @@
 #![deny(unused)]
 #![cfg_attr(feature = "nightly", feature(test))]
 
+#[cfg(all(test, feature = "nightly"))]
+extern crate test;
+
+mod cidr;
+pub mod clock;
 pub mod extractors;
 pub mod headers;
 pub mod middleware;
+#[cfg(feature = "tower")]
+pub mod tower;
 mod utils;
@@ -0,0 +1,119 @@
+//! Failed authentication attempts accounting per client connection
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Connections without failures for this long are forgotten.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Maximum number of the tracked connections, the least recently
+/// failed ones are evicted beyond it.
+const MAX_TRACKED: usize = 1024;
+
+/// Failed attempts counters, keyed by the connection peer address.
+///
+/// actix-web does not expose the connection-scoped state to the
+/// middlewares, but the peer address and port pair identifies the
+/// connection while it is open.
+#[derive(Debug)]
+pub(crate) struct ConnectionAttempts {
+    limit: u32,
+    failures: Mutex<Failures>,
+}
+
+#[derive(Debug, Default)]
+struct Failures {
+    /// Failures count, the last failure time and its tick.
+    peers: HashMap<SocketAddr, (u32, Instant, u64)>,
+    /// Peers ordered by the tick of their last failure.
+    order: BTreeMap<u64, SocketAddr>,
+    tick: u64,
+}
+
+impl ConnectionAttempts {
+    pub(crate) fn new(limit: u32) -> ConnectionAttempts {
+        ConnectionAttempts {
+            limit,
+            failures: Mutex::new(Failures::default()),
+        }
+    }
+
+    /// Returns `true` if the connection has used up all of its attempts.
+    pub(crate) fn is_exhausted(&self, peer: Option<SocketAddr>) -> bool {
+        let peer = match peer {
+            Some(peer) => peer,
+            None => return false,
+        };
+
+        let failures = self.failures.lock().expect("Attempts lock is poisoned");
+        failures.peers.get(&peer).is_some_and(|(count, last, _)| {
+            *count >= self.limit && last.elapsed() < IDLE_TIMEOUT
+        })
+    }
+
+    /// Counts the failed attempt of the connection.
+    pub(crate) fn record_failure(&self, peer: Option<SocketAddr>) {
+        let peer = match peer {
+            Some(peer) => peer,
+            None => return,
+        };
+
+        let now = Instant::now();
+        let mut failures =
+            self.failures.lock().expect("Attempts lock is poisoned");
+        let failures = &mut *failures;
+        failures.tick += 1;
+        let tick = failures.tick;
+
+        let entry = failures.peers.entry(peer).or_insert((0, now, tick));
+        failures.order.remove(&entry.2);
+        if now - entry.1 >= IDLE_TIMEOUT {
+            // Address is reused by the new connection
+            entry.0 = 0;
+        }
+        entry.0 += 1;
+        entry.1 = now;
+        entry.2 = tick;
+        failures.order.insert(tick, peer);
+
+        while failures.peers.len() > MAX_TRACKED {
+            let oldest = match failures.order.pop_first() {
+                Some((_, oldest)) => oldest,
+                None => break,
+            };
+            failures.peers.remove(&oldest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::{ConnectionAttempts, MAX_TRACKED};
+
+    #[test]
+    fn test_evict_oldest() {
+        let attempts = ConnectionAttempts::new(1);
+        let peer = |port: u16| Some(SocketAddr::from(([10, 0, 0, 1], port)));
+
+        attempts.record_failure(peer(1));
+        for port in 2..=MAX_TRACKED as u16 {
+            attempts.record_failure(peer(port));
+        }
+        // Refreshed peer is not the oldest one anymore
+        attempts.record_failure(peer(1));
+        attempts.record_failure(peer(0));
+
+        let failures = attempts.failures.lock().unwrap();
+        assert_eq!(failures.peers.len(), MAX_TRACKED);
+        assert_eq!(failures.order.len(), MAX_TRACKED);
+        drop(failures);
+        assert!(attempts.is_exhausted(peer(0)));
+        assert!(attempts.is_exhausted(peer(1)));
+        assert!(!attempts.is_exhausted(peer(2)));
+        assert!(attempts.is_exhausted(peer(3)));
+    }
+}
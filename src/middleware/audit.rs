@@ -0,0 +1,178 @@
+//! Audit trail of the authentication decisions
+
+use std::fmt::Debug;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use actix_web::dev::ServiceRequest;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::http::Method;
+
+use crate::cidr::{self, IpNet};
+use crate::clock::Clock;
+use crate::headers::authorization::split_scheme;
+
+/// Destination of the [`AuditEvent`]s, see [`HttpAuthentication::audit`].
+///
+/// Sink is called synchronously, right when the decision is made,
+/// so the slow ones should hand the events over to the background task.
+///
+/// [`AuditEvent`]: ./struct.AuditEvent.html
+/// [`HttpAuthentication::audit`]: ./struct.HttpAuthentication.html#method.audit
+pub trait AuditSink: Debug + Send + Sync {
+    /// Records the authentication decision.
+    fn record(&self, event: &AuditEvent);
+}
+
+/// Authentication decision made for the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuditOutcome {
+    /// Credentials were extracted and validated.
+    Passed,
+    /// Credentials are missing, malformed or rejected by the validator.
+    Rejected,
+    /// Request was passed without authentication,
+    /// ex. from the bypassed network.
+    Bypassed,
+}
+
+/// Authentication decision details, see [`AuditSink`].
+///
+/// [`AuditSink`]: ./trait.AuditSink.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    /// Decision time, read from the [`HttpAuthentication::audit_clock`].
+    ///
+    /// [`HttpAuthentication::audit_clock`]: ./struct.HttpAuthentication.html#method.audit_clock
+    pub timestamp: SystemTime,
+    /// Request method.
+    pub method: Method,
+    /// Request path.
+    pub path: String,
+    /// Scheme name of the `Authorization` header, if present.
+    pub scheme: Option<String>,
+    /// Identity claimed by the credentials, if it is not a secret,
+    /// see [`AuthExtractor::subject`].
+    ///
+    /// [`AuthExtractor::subject`]: ../extractors/trait.AuthExtractor.html#method.subject
+    pub subject: Option<String>,
+    /// Client IP address, resolved with the
+    /// [`HttpAuthentication::trusted_proxies`] taken into account.
+    ///
+    /// [`HttpAuthentication::trusted_proxies`]: ./struct.HttpAuthentication.html#method.trusted_proxies
+    pub ip: Option<IpAddr>,
+    /// The decision made.
+    pub outcome: AuditOutcome,
+}
+
+/// [`AuditSink`](./trait.AuditSink.html) which emits the events
+/// as the `tracing` ones at the `INFO` level, with the
+/// `actix_web_httpauth::audit` target.
+///
+/// Available with the `tracing` feature only.
+#[cfg(feature = "tracing")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingAuditSink;
+
+#[cfg(feature = "tracing")]
+impl AuditSink for TracingAuditSink {
+    fn record(&self, event: &AuditEvent) {
+        tracing::info!(
+            target: "actix_web_httpauth::audit",
+            timestamp = ?event.timestamp,
+            method = %event.method,
+            path = %event.path,
+            scheme = ?event.scheme,
+            subject = ?event.subject,
+            ip = ?event.ip,
+            outcome = ?event.outcome,
+            "Authentication decision"
+        );
+    }
+}
+
+/// Details of the failed authentication attempt, see
+/// [`HttpAuthentication::on_failure`].
+///
+/// It never includes the secrets, ex. the password or the token,
+/// so it is safe to be logged, ex. to detect the credential stuffing.
+///
+/// [`HttpAuthentication::on_failure`]: ./struct.HttpAuthentication.html#method.on_failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttemptInfo {
+    /// Identity claimed by the credentials, ex. the "Basic" user-ID,
+    /// see [`AuthExtractor::subject`].
+    ///
+    /// It is `None` if extraction failed, or if the credentials
+    /// are secrets on their own, ex. the bearer tokens.
+    ///
+    /// [`AuthExtractor::subject`]: ../extractors/trait.AuthExtractor.html#method.subject
+    pub username: Option<String>,
+    /// Scheme name of the `Authorization` header, if present.
+    pub scheme: Option<String>,
+}
+
+impl AttemptInfo {
+    /// Collects the scheme name of the `req` credentials,
+    /// the username is known once they are extracted.
+    pub(crate) fn new(req: &ServiceRequest) -> AttemptInfo {
+        AttemptInfo {
+            username: None,
+            scheme: credentials_scheme(req),
+        }
+    }
+}
+
+/// Returns the scheme name of the `Authorization` header.
+fn credentials_scheme(req: &ServiceRequest) -> Option<String> {
+    req.headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| split_scheme(value).0)
+        .filter(|scheme| !scheme.is_empty())
+        .map(str::to_string)
+}
+
+/// Request details collected before the request is passed
+/// to the extractor, completed into the events once decided.
+#[derive(Debug)]
+pub(crate) struct AuditTrail {
+    sink: Arc<dyn AuditSink>,
+    clock: Arc<dyn Clock>,
+    method: Method,
+    path: String,
+    scheme: Option<String>,
+    ip: Option<IpAddr>,
+}
+
+impl AuditTrail {
+    pub(crate) fn new(
+        sink: Arc<dyn AuditSink>,
+        clock: Arc<dyn Clock>,
+        req: &ServiceRequest,
+        trusted_proxies: &[IpNet],
+    ) -> AuditTrail {
+        AuditTrail {
+            sink,
+            clock,
+            method: req.method().clone(),
+            path: req.path().to_string(),
+            scheme: credentials_scheme(req),
+            ip: cidr::client_ip(req.head(), trusted_proxies),
+        }
+    }
+
+    /// Records the decision made for the request.
+    pub(crate) fn record(&self, subject: Option<&str>, outcome: AuditOutcome) {
+        self.sink.record(&AuditEvent {
+            timestamp: self.clock.now(),
+            method: self.method.clone(),
+            path: self.path.clone(),
+            scheme: self.scheme.clone(),
+            subject: subject.map(str::to_string),
+            ip: self.ip,
+            outcome,
+        });
+    }
+}
@@ -0,0 +1,237 @@
+use actix_web::dev::ServiceRequest;
+use actix_web::Error;
+use futures::future::{self, Future, IntoFuture};
+
+use super::HttpAuthentication;
+use crate::extractors::credentials::{
+    self, ConfiguredScheme, CredentialScheme,
+};
+use crate::extractors::form_login::FormBody;
+use crate::extractors::{basic, bearer, token, Credentials};
+
+/// Builder of the [`HttpAuthentication`] middleware accepting any of
+/// the configured schemes, ex. for the APIs called both by the users
+/// and by the services.
+///
+/// Schemes are tried in the order they were added, with the extractor
+/// configs provided here instead of the app data ones. Validation callback
+/// receives the [`Credentials`] of the first matching scheme; when none
+/// of them matches, the error response contains challenges
+/// for all of them.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::{error, App};
+/// # use futures::future;
+/// # use actix_web_httpauth::extractors::{basic, bearer, token, Credentials};
+/// # use actix_web_httpauth::middleware::AuthBuilder;
+/// let middleware = AuthBuilder::new()
+///     .bearer(bearer::Config::default().realm("api"))
+///     .basic(basic::Config::default().realm("api"))
+///     .api_key(token::Config::default().realm("api"))
+///     .build(|req, credentials| match credentials {
+///         Credentials::Bearer(ref bearer) if bearer.token() == "mF_9.B5f-4.1JqM" => {
+///             future::ok(req)
+///         }
+///         _ => future::err(error::ErrorForbidden("Unknown credentials")),
+///     });
+///
+/// let app = App::new().wrap(middleware);
+/// ```
+///
+/// [`HttpAuthentication`]: ./struct.HttpAuthentication.html
+/// [`Credentials`]: ../extractors/enum.Credentials.html
+#[derive(Debug, Clone, Default)]
+pub struct AuthBuilder {
+    schemes: Vec<ConfiguredScheme>,
+}
+
+impl AuthBuilder {
+    /// Creates new builder without any schemes.
+    pub fn new() -> AuthBuilder {
+        AuthBuilder::default()
+    }
+
+    /// Accept the "Bearer" tokens, extracted with the `config`.
+    pub fn bearer(self, config: bearer::Config) -> AuthBuilder {
+        self.scheme(ConfiguredScheme::Bearer(config))
+    }
+
+    /// Accept the "Basic" credentials, extracted with the `config`.
+    pub fn basic(self, config: basic::Config) -> AuthBuilder {
+        self.scheme(ConfiguredScheme::Basic(config))
+    }
+
+    /// Accept the "Token" API keys, extracted with the `config`.
+    pub fn api_key(self, config: token::Config) -> AuthBuilder {
+        self.scheme(ConfiguredScheme::Token(config))
+    }
+
+    /// Adds the scheme, or replaces the config of the already added one,
+    /// keeping its position.
+    fn scheme(mut self, config: ConfiguredScheme) -> AuthBuilder {
+        match self
+            .schemes
+            .iter_mut()
+            .find(|scheme| scheme.scheme() == config.scheme())
+        {
+            Some(scheme) => *scheme = config,
+            None => self.schemes.push(config),
+        }
+        self
+    }
+
+    /// Construct the middleware with the validation callback `F`.
+    ///
+    /// ## Panics
+    ///
+    /// If no schemes were added.
+    pub fn build<F, O>(
+        self,
+        process_fn: F,
+    ) -> HttpAuthentication<
+        (),
+        impl Fn(
+            ServiceRequest,
+            (),
+        ) -> Box<dyn Future<Item = ServiceRequest, Error = Error>>,
+    >
+    where
+        F: Fn(ServiceRequest, Credentials) -> O + 'static,
+        O: IntoFuture<Item = ServiceRequest, Error = Error>,
+        O::Future: 'static,
+    {
+        assert!(!self.schemes.is_empty(), "At least one scheme is required");
+        let form_body = FormBody {
+            login: self.has_scheme(CredentialScheme::Basic),
+            token: self.has_scheme(CredentialScheme::Bearer),
+        };
+        let schemes = self.schemes;
+
+        let mut middleware =
+            HttpAuthentication::with_fn(move |req: ServiceRequest, ()| {
+                match credentials::extract_configured(&req, &schemes) {
+                    Ok(credentials) => {
+                        Box::new(process_fn(req, credentials).into_future())
+                            as Box<
+                                dyn Future<
+                                    Item = ServiceRequest,
+                                    Error = Error,
+                                >,
+                            >
+                    }
+                    Err(error) => Box::new(future::err(error)),
+                }
+            });
+        middleware.options.form_body = form_body;
+
+        middleware
+    }
+
+    fn has_scheme(&self, scheme: CredentialScheme) -> bool {
+        self.schemes
+            .iter()
+            .any(|configured| configured.scheme() == scheme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_service::Service;
+    use actix_web::dev::ServiceResponse;
+    use actix_web::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::{error, web, App, Error, HttpResponse};
+    use futures::future;
+
+    use super::AuthBuilder;
+    use crate::extractors::{basic, bearer, token, Credentials};
+    use crate::middleware::tests::status;
+
+    /// Returns the challenges of the rendered `req` error.
+    fn challenges<S, R, B>(app: &mut S, req: R) -> Vec<String>
+    where
+        S: Service<Request = R, Response = ServiceResponse<B>, Error = Error>,
+    {
+        let err = test::block_on(app.call(req)).err().unwrap();
+        let res = err.as_response_error().error_response();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        res.headers()
+            .get_all(WWW_AUTHENTICATE)
+            .map(|value| value.to_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_builder() {
+        let middleware = AuthBuilder::new()
+            .bearer(bearer::Config::default().realm("api"))
+            .basic(basic::Config::default().realm("api"))
+            .api_key(token::Config::default().realm("api"))
+            .build(|req, credentials| match credentials {
+                Credentials::Bearer(_)
+                | Credentials::Basic(_)
+                | Credentials::Token(_) => future::ok(req),
+                _ => future::err(error::ErrorForbidden("Unexpected scheme")),
+            });
+        let mut app = test::init_service(
+            App::new()
+                .wrap(middleware)
+                .route("/", web::get().to(HttpResponse::Ok)),
+        );
+
+        for credentials in &[
+            "Bearer mF_9.B5f-4.1JqM",
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+            "Token key",
+        ] {
+            let req = TestRequest::with_header(AUTHORIZATION, *credentials)
+                .to_request();
+            assert_eq!(
+                status(&mut app, req),
+                StatusCode::OK,
+                "{}",
+                credentials
+            );
+        }
+
+        let req = TestRequest::with_header(AUTHORIZATION, "Digest username=x")
+            .to_request();
+        assert_eq!(
+            challenges(&mut app, req),
+            vec![
+                "Bearer realm=\"api\"",
+                "Basic realm=\"api\"",
+                "Token realm=\"api\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replace_scheme() {
+        let middleware = AuthBuilder::new()
+            .basic(basic::Config::default().realm("old"))
+            .bearer(bearer::Config::default())
+            .basic(basic::Config::default().realm("new"))
+            .build(|req, _credentials| future::ok(req));
+        let mut app = test::init_service(
+            App::new()
+                .wrap(middleware)
+                .route("/", web::get().to(HttpResponse::Ok)),
+        );
+
+        assert_eq!(
+            challenges(&mut app, TestRequest::default().to_request()),
+            vec!["Basic realm=\"new\"", "Bearer"]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "At least one scheme is required")]
+    fn test_no_schemes() {
+        AuthBuilder::new().build(|req, _credentials| future::ok(req));
+    }
+}
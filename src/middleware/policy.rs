@@ -0,0 +1,301 @@
+use std::fmt;
+use std::sync::Arc;
+
+use actix_web::dev::ServiceRequest;
+use actix_web::Error;
+use futures::future::{self, FutureResult};
+
+use super::HttpAuthentication;
+use crate::cidr::{self, IpNet};
+use crate::extractors::bearer::{self, BearerAuth, Config};
+use crate::extractors::AuthenticationError;
+
+/// Permissions granted to the bearer token,
+/// resolved by the [`AuthPolicy`] resolver function.
+///
+/// [`AuthPolicy`]: ./struct.AuthPolicy.html
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Grants {
+    /// Token scopes.
+    pub scopes: Vec<String>,
+    /// Roles of the token owner.
+    pub roles: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Rule {
+    AllowCidr(Vec<IpNet>),
+    RequireScope(String),
+    RequireRole(String),
+    Deny,
+}
+
+type Resolver = dyn Fn(&BearerAuth) -> Option<Grants> + Send + Sync;
+
+/// Declarative access policy, compiled into the [`HttpAuthentication`]
+/// middleware for the "Bearer" authentication scheme.
+///
+/// Rules are evaluated top-to-bottom and the first matching rule terminates
+/// the evaluation: `allow_cidr`, `require_scope` and `require_role` rules
+/// are allowing the request if it matches them, while `deny` one rejects any
+/// request. Requests which are not matching any rule are rejected too.
+///
+/// Bearer token permissions are provided by the resolver function,
+/// which returns `None` for the invalid tokens. Rejected requests are
+/// receiving the `HTTP 401` challenge if the token is missing or invalid,
+/// and the `HTTP 403` `insufficient_scope` one otherwise; challenge is
+/// formed from the [`Config`] instance fetched from the app data.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::App;
+/// # use actix_web_httpauth::middleware::{AuthPolicy, Grants};
+/// let policy = AuthPolicy::new(|credentials| match credentials.token() {
+///     "mF_9.B5f-4.1JqM" => Some(Grants {
+///         scopes: vec!["reports:read".to_string()],
+///         roles: vec![],
+///     }),
+///     _ => None,
+/// })
+/// .allow_cidr(&["10.0.0.0/8"])
+/// .require_scope("reports:read")
+/// .deny();
+///
+/// let app = App::new().wrap(policy.into_middleware());
+/// ```
+///
+/// [`HttpAuthentication`]: ./struct.HttpAuthentication.html
+/// [`Config`]: ../extractors/bearer/struct.Config.html
+pub struct AuthPolicy {
+    rules: Vec<Rule>,
+    trusted_proxies: Vec<IpNet>,
+    resolver: Box<Resolver>,
+}
+
+impl AuthPolicy {
+    /// Creates an empty policy, which uses the `resolver` function
+    /// to get the bearer token permissions.
+    pub fn new<F>(resolver: F) -> AuthPolicy
+    where
+        F: Fn(&BearerAuth) -> Option<Grants> + Send + Sync + 'static,
+    {
+        AuthPolicy {
+            rules: Vec::new(),
+            trusted_proxies: Vec::new(),
+            resolver: Box::new(resolver),
+        }
+    }
+
+    /// Allow the requests from the listed IP `networks`,
+    /// see [`HttpAuthentication::bypass_cidr`].
+    ///
+    /// ## Panics
+    ///
+    /// If any of `networks` is not a valid CIDR notation.
+    ///
+    /// [`HttpAuthentication::bypass_cidr`]: ./struct.HttpAuthentication.html#method.bypass_cidr
+    pub fn allow_cidr<I>(mut self, networks: I) -> AuthPolicy
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.rules.push(Rule::AllowCidr(IpNet::parse_all(networks)));
+        self
+    }
+
+    /// Allow the requests with the bearer token which has the `scope`.
+    pub fn require_scope<T: Into<String>>(mut self, scope: T) -> AuthPolicy {
+        self.rules.push(Rule::RequireScope(scope.into()));
+        self
+    }
+
+    /// Allow the requests with the bearer token which owner has the `role`.
+    pub fn require_role<T: Into<String>>(mut self, role: T) -> AuthPolicy {
+        self.rules.push(Rule::RequireRole(role.into()));
+        self
+    }
+
+    /// Reject all the requests, which reached this rule.
+    pub fn deny(mut self) -> AuthPolicy {
+        self.rules.push(Rule::Deny);
+        self
+    }
+
+    /// Set the IP networks of the reverse proxies, which are trusted
+    /// to provide the client address for the `allow_cidr` rules,
+    /// see [`HttpAuthentication::trusted_proxies`].
+    ///
+    /// ## Panics
+    ///
+    /// If any of `networks` is not a valid CIDR notation.
+    ///
+    /// [`HttpAuthentication::trusted_proxies`]: ./struct.HttpAuthentication.html#method.trusted_proxies
+    pub fn trusted_proxies<I>(mut self, networks: I) -> AuthPolicy
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.trusted_proxies = IpNet::parse_all(networks);
+        self
+    }
+
+    /// Compiles the policy into the middleware.
+    #[allow(clippy::type_complexity)]
+    pub fn into_middleware(
+        self,
+    ) -> HttpAuthentication<
+        Option<BearerAuth>,
+        impl Fn(
+            ServiceRequest,
+            Option<BearerAuth>,
+        ) -> FutureResult<ServiceRequest, Error>,
+    > {
+        let policy = Arc::new(self);
+
+        HttpAuthentication::with_fn(move |req, credentials| {
+            policy.check(req, credentials)
+        })
+    }
+
+    fn check(
+        &self,
+        req: ServiceRequest,
+        credentials: Option<BearerAuth>,
+    ) -> FutureResult<ServiceRequest, Error> {
+        let grants = credentials.as_ref().and_then(|c| (self.resolver)(c));
+        let client_ip = cidr::client_ip(req.head(), &self.trusted_proxies);
+
+        for rule in &self.rules {
+            let allowed = match rule {
+                Rule::AllowCidr(networks) => client_ip.is_some_and(|ip| {
+                    networks.iter().any(|net| net.contains(ip))
+                }),
+                Rule::RequireScope(scope) => grants
+                    .as_ref()
+                    .is_some_and(|grants| grants.scopes.contains(scope)),
+                Rule::RequireRole(role) => grants
+                    .as_ref()
+                    .is_some_and(|grants| grants.roles.contains(role)),
+                Rule::Deny => break,
+            };
+
+            if allowed {
+                return future::ok(req);
+            }
+        }
+
+        let config = req
+            .app_data::<Config>()
+            .map(|config| config.get_ref().clone())
+            .unwrap_or_default();
+        let error = match (credentials, grants) {
+            (None, _) => AuthenticationError::from(config),
+            (Some(_), None) => AuthenticationError::from(config)
+                .with_error(bearer::Error::InvalidToken),
+            (Some(_), Some(_)) => {
+                let scopes = self.rules.iter().filter_map(|rule| match rule {
+                    Rule::RequireScope(scope) => Some(scope),
+                    _ => None,
+                });
+                AuthenticationError::insufficient_scope(config, scopes)
+            }
+        };
+
+        future::err(error.into())
+    }
+}
+
+impl fmt::Debug for AuthPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AuthPolicy")
+            .field("rules", &self.rules)
+            .field("trusted_proxies", &self.trusted_proxies)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_service::Service;
+    use actix_web::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+
+    use super::{AuthPolicy, Grants};
+    use crate::middleware::tests::status;
+
+    fn policy() -> AuthPolicy {
+        AuthPolicy::new(|credentials| match credentials.token() {
+            "reader" => Some(Grants {
+                scopes: vec!["read".to_string()],
+                roles: vec![],
+            }),
+            "admin" => Some(Grants {
+                scopes: vec![],
+                roles: vec!["admin".to_string()],
+            }),
+            "nobody" => Some(Grants::default()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_rules_order() {
+        let policy = policy()
+            .allow_cidr(&["10.0.0.0/8"])
+            .require_scope("read")
+            .deny()
+            .require_role("admin");
+        let mut app = test::init_service(
+            App::new()
+                .wrap(policy.into_middleware())
+                .route("/", web::get().to(HttpResponse::Ok)),
+        );
+        let mut call = |peer: &str, token: Option<&str>| {
+            let mut req = TestRequest::default();
+            if let Some(token) = token {
+                req = req.header(AUTHORIZATION, format!("Bearer {}", token));
+            }
+            let mut req = req.to_request();
+            req.head_mut().peer_addr = Some(peer.parse().unwrap());
+
+            status(&mut app, req)
+        };
+
+        assert_eq!(call("10.0.0.1:80", None), StatusCode::OK);
+        assert_eq!(call("127.0.0.1:80", Some("reader")), StatusCode::OK);
+        assert_eq!(call("127.0.0.1:80", None), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            call("127.0.0.1:80", Some("unknown")),
+            StatusCode::UNAUTHORIZED
+        );
+        // `require_role` rule is never reached
+        assert_eq!(call("127.0.0.1:80", Some("admin")), StatusCode::FORBIDDEN);
+        assert_eq!(call("127.0.0.1:80", Some("nobody")), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_insufficient_scope_challenge() {
+        let policy = policy().require_scope("read").require_role("admin");
+        let mut app = test::init_service(
+            App::new()
+                .wrap(policy.into_middleware())
+                .route("/", web::get().to(HttpResponse::Ok)),
+        );
+
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer admin")
+            .to_request();
+        assert_eq!(status(&mut app, req), StatusCode::OK);
+
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer nobody")
+            .to_request();
+        let res = test::block_on(app.call(req));
+        let response = res.err().unwrap().as_response_error().error_response();
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer scope=\"read\", error=\"insufficient_scope\""
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use actix_web::dev::ServiceRequest;
+use actix_web::error::InternalError;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::Error;
+use futures::future;
+use futures::{Future, IntoFuture};
+
+use super::{Extract, HttpAuthentication, Validation};
+use crate::extractors::{self, basic, bearer, AuthExtractor};
+use crate::headers::authorization;
+
+type ErasedValidator = dyn Fn(ServiceRequest) -> Validation + Send + Sync;
+type KeyResolver = dyn Fn(&ServiceRequest) -> Option<String> + Send + Sync;
+
+/// Validators registered by the key, ex. by the authentication scheme
+/// or by the tenant, which are picked at the request time,
+/// see [`HttpAuthentication::registry`].
+///
+/// Each validator is registered along with its own extractor, so the
+/// differently authenticated requests can be served by one middleware.
+/// Validators can be registered and removed while the application is
+/// running, ex. once the tenant configuration is reloaded.
+///
+/// ## Example
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// # use actix_web::App;
+/// # use futures::future;
+/// # use actix_web_httpauth::extractors::basic::BasicAuth;
+/// # use actix_web_httpauth::extractors::bearer::BearerAuth;
+/// # use actix_web_httpauth::middleware::{HttpAuthentication, ValidatorRegistry};
+/// let registry = Arc::new(ValidatorRegistry::new());
+/// registry.register("basic", |req, _credentials: BasicAuth| future::ok(req));
+/// registry.register("bearer", |req, _credentials: BearerAuth| future::ok(req));
+///
+/// let app = App::new().wrap(HttpAuthentication::registry(registry.clone()));
+/// ```
+///
+/// [`HttpAuthentication::registry`]: ./struct.HttpAuthentication.html#method.registry
+pub struct ValidatorRegistry {
+    resolver: Box<KeyResolver>,
+    validators: RwLock<HashMap<String, Arc<ErasedValidator>>>,
+}
+
+impl ValidatorRegistry {
+    /// Creates an empty registry, which picks the validators by the
+    /// lowercase authentication scheme name from the `Authorization`
+    /// header, ex. `"basic"` or `"bearer"`.
+    pub fn new() -> ValidatorRegistry {
+        ValidatorRegistry::with_key(|req| {
+            let value = req.headers().get(AUTHORIZATION)?.to_str().ok()?;
+            let (scheme, _credentials) = authorization::split_scheme(value);
+
+            Some(scheme.to_ascii_lowercase())
+        })
+    }
+
+    /// Creates an empty registry, which picks the validators by the key
+    /// returned by the `resolver`, ex. by the `X-Tenant` header value.
+    pub fn with_key<F>(resolver: F) -> ValidatorRegistry
+    where
+        F: Fn(&ServiceRequest) -> Option<String> + Send + Sync + 'static,
+    {
+        ValidatorRegistry {
+            resolver: Box::new(resolver),
+            validators: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Register the validation callback `F` with the auth extractor `T`
+    /// under the `key`, replacing the previously registered one.
+    pub fn register<K, T, F, O>(&self, key: K, process_fn: F)
+    where
+        K: Into<String>,
+        T: AuthExtractor + 'static,
+        <T::Future as IntoFuture>::Future: 'static,
+        T::Error: 'static,
+        F: Fn(ServiceRequest, T) -> O + Send + Sync + 'static,
+        O: IntoFuture<Item = ServiceRequest, Error = Error> + 'static,
+        O::Future: 'static,
+    {
+        let process_fn = Arc::new(process_fn);
+        let validator = move |req| {
+            let process_fn = process_fn.clone();
+            Box::new(
+                Extract::<T>::new(req).map_err(|(err, _req)| err).and_then(
+                    move |(req, credentials)| process_fn(req, credentials),
+                ),
+            ) as Validation
+        };
+
+        self.validators
+            .write()
+            .expect("Validator registry lock is poisoned")
+            .insert(key.into(), Arc::new(validator));
+    }
+
+    /// Remove the validator registered under the `key`.
+    ///
+    /// Returns `true` if the validator was registered.
+    pub fn unregister(&self, key: &str) -> bool {
+        self.validators
+            .write()
+            .expect("Validator registry lock is poisoned")
+            .remove(key)
+            .is_some()
+    }
+
+    /// Returns `true` if there is a validator registered under the `key`.
+    pub fn contains(&self, key: &str) -> bool {
+        self.validators
+            .read()
+            .expect("Validator registry lock is poisoned")
+            .contains_key(key)
+    }
+
+    fn validate(&self, req: ServiceRequest) -> Validation {
+        let validator = (self.resolver)(&req).and_then(|key| {
+            self.validators
+                .read()
+                .expect("Validator registry lock is poisoned")
+                .get(&key)
+                .cloned()
+        });
+
+        match validator {
+            Some(validator) => validator(req),
+            None => {
+                let response = extractors::challenge_response(
+                    req.head(),
+                    req.app_data::<basic::Config>()
+                        .as_ref()
+                        .map(|c| c.get_ref()),
+                    req.app_data::<bearer::Config>()
+                        .as_ref()
+                        .map(|c| c.get_ref()),
+                );
+                let error = InternalError::from_response(
+                    "No validator found",
+                    response,
+                );
+
+                Box::new(future::err(error.into()))
+            }
+        }
+    }
+}
+
+impl Default for ValidatorRegistry {
+    fn default() -> Self {
+        ValidatorRegistry::new()
+    }
+}
+
+impl fmt::Debug for ValidatorRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let validators = self
+            .validators
+            .read()
+            .expect("Validator registry lock is poisoned");
+
+        f.debug_struct("ValidatorRegistry")
+            .field("keys", &validators.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl HttpAuthentication<(), ()> {
+    /// Construct `HttpAuthentication` middleware, which runs the validator
+    /// picked from the `registry` at the request time.
+    ///
+    /// Requests without the validator for their key are rejected with the
+    /// `HTTP 401` response, formed the same way as the
+    /// [`respond_unauthorized`] one.
+    ///
+    /// [`respond_unauthorized`]: ../extractors/fn.respond_unauthorized.html
+    pub fn registry(
+        registry: Arc<ValidatorRegistry>,
+    ) -> HttpAuthentication<(), impl Fn(ServiceRequest, ()) -> Validation> {
+        HttpAuthentication::with_fn(move |req, ()| registry.validate(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_service::Service;
+    use actix_web::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+    use futures::future;
+
+    use super::ValidatorRegistry;
+    use crate::extractors::basic::BasicAuth;
+    use crate::extractors::bearer::BearerAuth;
+    use crate::middleware::tests::status;
+    use crate::middleware::HttpAuthentication;
+
+    #[test]
+    fn test_registry() {
+        let registry = Arc::new(ValidatorRegistry::with_key(|req| {
+            let tenant = req.headers().get("X-Tenant")?;
+            tenant.to_str().ok().map(String::from)
+        }));
+        registry.register("acme", |req, credentials: BearerAuth| {
+            if credentials.token() == "acme-token" {
+                future::ok(req)
+            } else {
+                future::err(actix_web::error::ErrorForbidden("invalid"))
+            }
+        });
+        registry
+            .register("legacy", |req, _credentials: BasicAuth| future::ok(req));
+
+        let mut app = test::init_service(
+            App::new()
+                .wrap(HttpAuthentication::registry(registry.clone()))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        );
+        let mut call = |tenant: &str, authorization: &str| {
+            let req = TestRequest::with_header(AUTHORIZATION, authorization)
+                .header("X-Tenant", tenant)
+                .to_request();
+
+            status(&mut app, req)
+        };
+
+        assert_eq!(call("acme", "Bearer acme-token"), StatusCode::OK);
+        assert_eq!(call("acme", "Bearer other"), StatusCode::FORBIDDEN);
+        assert_eq!(
+            call("acme", "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=="),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            call("legacy", "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=="),
+            StatusCode::OK
+        );
+        assert_eq!(
+            call("unknown", "Bearer acme-token"),
+            StatusCode::UNAUTHORIZED
+        );
+
+        // Tenants can be removed on the fly
+        assert!(registry.unregister("acme"));
+        assert!(!registry.contains("acme"));
+        assert_eq!(call("acme", "Bearer acme-token"), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_scheme_keys() {
+        let registry = Arc::new(ValidatorRegistry::new());
+        registry.register("bearer", |req, _credentials: BearerAuth| {
+            future::ok(req)
+        });
+
+        let mut app = test::init_service(
+            App::new()
+                .wrap(HttpAuthentication::registry(registry))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        );
+
+        let req = TestRequest::with_header(AUTHORIZATION, "Bearer token")
+            .to_request();
+        assert_eq!(status(&mut app, req), StatusCode::OK);
+
+        let req = TestRequest::with_header(
+            AUTHORIZATION,
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+        )
+        .to_request();
+        assert_eq!(status(&mut app, req), StatusCode::UNAUTHORIZED);
+
+        let req = TestRequest::default().to_request();
+        let err = test::block_on(app.call(req)).unwrap_err();
+        let res = err.as_response_error().error_response();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(res.headers().get(WWW_AUTHENTICATE).unwrap(), "Bearer");
+    }
+}
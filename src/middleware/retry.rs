@@ -0,0 +1,361 @@
+use std::collections::hash_map::RandomState;
+use std::error::Error as StdError;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use actix_web::dev::ServiceRequest;
+use actix_web::http::StatusCode;
+use actix_web::{error, Error, HttpResponse, ResponseError};
+use futures::future::{self, Either, Future, IntoFuture, Loop};
+use tokio_timer::Delay;
+
+use crate::extractors::errors;
+
+type TransientCheck = dyn Fn(&Error) -> bool + Send + Sync;
+
+/// Wrapper for the flaky credentials checks, ex. the remote introspection
+/// calls, which retries them on the transient errors with the exponential
+/// backoff.
+///
+/// Check is called with the credentials only, since the request is owned
+/// by the validator until the check succeeds. By default the errors
+/// resolving into the `HTTP 5xx` status codes are transient, so the
+/// genuine authentication failures, ex. `HTTP 401` for the invalid token,
+/// are returned right away; see [`retry_if`](#method.retry_if).
+///
+/// Once all attempts have failed with the transient errors, request is
+/// rejected with the `HTTP 503` error, rather than the `HTTP 401` one,
+/// so clients are not discarding the valid credentials.
+///
+/// ## Example
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use actix_web::{error, App};
+/// # use futures::future;
+/// # use actix_web_httpauth::extractors::bearer::BearerAuth;
+/// # use actix_web_httpauth::middleware::{HttpAuthentication, RetryingValidator};
+/// let validator = RetryingValidator::new(|credentials: &BearerAuth| {
+///     // Call the remote endpoint here
+///     match credentials.token() {
+///         "mF_9.B5f-4.1JqM" => future::ok(()),
+///         _ => future::err(error::ErrorServiceUnavailable("Endpoint is down")),
+///     }
+/// })
+/// .max_attempts(4)
+/// .backoff(Duration::from_millis(20), Duration::from_millis(500));
+///
+/// let middleware = HttpAuthentication::bearer(move |req, credentials| {
+///     validator.validate(req, credentials)
+/// });
+/// let app = App::new().wrap(middleware);
+/// ```
+pub struct RetryingValidator<F> {
+    check: Arc<F>,
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    is_transient: Arc<TransientCheck>,
+}
+
+impl<F> RetryingValidator<F> {
+    /// Creates new validator, which calls the `check` at most 3 times,
+    /// waiting 50 ms before the first retry and doubling
+    /// the delay up to 1 s for the next ones.
+    pub fn new(check: F) -> Self {
+        RetryingValidator {
+            check: Arc::new(check),
+            max_attempts: 3,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(1),
+            jitter: true,
+            is_transient: Arc::new(|error: &Error| {
+                // Authentication errors are not rendered,
+                // so their observers are not called
+                errors::status_of(error)
+                    .unwrap_or_else(|| {
+                        error.as_response_error().error_response().status()
+                    })
+                    .is_server_error()
+            }),
+        }
+    }
+
+    /// Set the maximum number of the `check` calls, including the first one.
+    ///
+    /// ## Panics
+    ///
+    /// If `value` is zero.
+    pub fn max_attempts(mut self, value: u32) -> Self {
+        assert!(value > 0, "At least one attempt is required");
+        self.max_attempts = value;
+        self
+    }
+
+    /// Set the delay before the first retry, which is doubled for each
+    /// next one, but does not exceed the `max` delay.
+    pub fn backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.base_delay = base;
+        self.max_delay = max;
+        self
+    }
+
+    /// Randomize the delays by up to a half, so the clients failed
+    /// at once are not retrying at once. Enabled by default.
+    pub fn jitter(mut self, value: bool) -> Self {
+        self.jitter = value;
+        self
+    }
+
+    /// Set the predicate deciding which errors are transient,
+    /// only those are retried.
+    pub fn retry_if<P>(mut self, predicate: P) -> Self
+    where
+        P: Fn(&Error) -> bool + Send + Sync + 'static,
+    {
+        self.is_transient = Arc::new(predicate);
+        self
+    }
+
+    /// Returns the delay before the `retry`-th retry, starting from 1.
+    fn delay(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let delay = self
+            .base_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay));
+        if !self.jitter {
+            return delay;
+        }
+
+        let random =
+            RandomState::new().hash_one(retry) as f64 / u64::MAX as f64;
+        delay.mul_f64(1.0 - random / 2.0)
+    }
+}
+
+impl<F: 'static> RetryingValidator<F> {
+    /// Validates the `credentials`, suitable to be called
+    /// from the [`HttpAuthentication`] validation callback.
+    ///
+    /// [`HttpAuthentication`]: ./struct.HttpAuthentication.html
+    pub fn validate<T, O>(
+        &self,
+        req: ServiceRequest,
+        credentials: T,
+    ) -> Box<dyn Future<Item = ServiceRequest, Error = Error>>
+    where
+        F: Fn(&T) -> O,
+        T: 'static,
+        O: IntoFuture<Item = (), Error = Error>,
+        O::Future: 'static,
+    {
+        let this = Rc::new(self.clone());
+        let credentials = Rc::new(credentials);
+
+        let f = future::loop_fn(1, move |attempt| {
+            let this = this.clone();
+            (this.check)(&credentials)
+                .into_future()
+                .then(move |result| {
+                    let error = match result {
+                        Ok(()) => {
+                            return Either::A(future::ok(Loop::Break(())))
+                        }
+                        Err(error) => error,
+                    };
+                    if !(this.is_transient)(&error) {
+                        return Either::A(future::err(error));
+                    }
+                    if attempt >= this.max_attempts {
+                        return Either::A(future::err(
+                            RetriesExhausted(error).into(),
+                        ));
+                    }
+
+                    let delay =
+                        Delay::new(Instant::now() + this.delay(attempt));
+                    Either::B(
+                        delay
+                            .map(move |()| Loop::Continue(attempt + 1))
+                            .map_err(error::ErrorInternalServerError),
+                    )
+                })
+        });
+
+        Box::new(f.map(move |()| req))
+    }
+}
+
+impl<F> Clone for RetryingValidator<F> {
+    fn clone(&self) -> Self {
+        RetryingValidator {
+            check: self.check.clone(),
+            max_attempts: self.max_attempts,
+            base_delay: self.base_delay,
+            max_delay: self.max_delay,
+            jitter: self.jitter,
+            is_transient: self.is_transient.clone(),
+        }
+    }
+}
+
+impl<F> fmt::Debug for RetryingValidator<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RetryingValidator")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("jitter", &self.jitter)
+            .finish()
+    }
+}
+
+/// All attempts have failed with the transient errors,
+/// resolves into the `HTTP 503` status code.
+#[derive(Debug)]
+struct RetriesExhausted(Error);
+
+impl fmt::Display for RetriesExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Credentials check failed after retries: {}", self.0)
+    }
+}
+
+impl StdError for RetriesExhausted {}
+
+impl ResponseError for RetriesExhausted {
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::new(StatusCode::SERVICE_UNAVAILABLE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::{error, Error};
+    use futures::future::{self, FutureResult};
+
+    use super::RetryingValidator;
+    use crate::extractors::AuthenticationError;
+    use crate::headers::www_authenticate::bearer::Bearer;
+    use crate::middleware::tests::{validation_status, Fixture};
+
+    /// Returns the validator failing with the `errors` first,
+    /// each call is counted by the `fixture`.
+    fn flaky(
+        errors: Vec<StatusCode>,
+        fixture: &Fixture,
+    ) -> RetryingValidator<impl Fn(&()) -> FutureResult<(), Error>> {
+        let counter = fixture.clone();
+        RetryingValidator::new(move |_: &()| {
+            let call = counter.call();
+            match errors.get(call) {
+                Some(status) => future::err(
+                    error::InternalError::new("failed", *status).into(),
+                ),
+                None => future::ok(()),
+            }
+        })
+        .backoff(Duration::from_millis(1), Duration::from_millis(4))
+    }
+
+    fn status<F>(validator: &RetryingValidator<F>) -> StatusCode
+    where
+        F: Fn(&()) -> FutureResult<(), Error> + 'static,
+    {
+        let req = TestRequest::default().to_srv_request();
+        validation_status(validator.validate(req, ()))
+    }
+
+    #[test]
+    fn test_retry_transient() {
+        let fixture = Fixture::new();
+        let validator = flaky(
+            vec![StatusCode::BAD_GATEWAY, StatusCode::SERVICE_UNAVAILABLE],
+            &fixture,
+        );
+
+        assert_eq!(status(&validator), StatusCode::OK);
+        assert_eq!(fixture.calls(), 3);
+    }
+
+    #[test]
+    fn test_not_retry_rejection() {
+        let fixture = Fixture::new();
+        let validator = flaky(
+            vec![StatusCode::UNAUTHORIZED, StatusCode::BAD_GATEWAY],
+            &fixture,
+        );
+
+        assert_eq!(status(&validator), StatusCode::UNAUTHORIZED);
+        assert_eq!(fixture.calls(), 1);
+
+        // Custom predicate retries the rejections too
+        let fixture = Fixture::new();
+        let validator =
+            flaky(vec![StatusCode::UNAUTHORIZED], &fixture).retry_if(|_| true);
+        assert_eq!(status(&validator), StatusCode::OK);
+        assert_eq!(fixture.calls(), 2);
+    }
+
+    #[test]
+    fn test_classify_without_rendering() {
+        let observed = Arc::new(AtomicUsize::new(0));
+        let counter = observed.clone();
+        let validator = RetryingValidator::new(move |_: &()| {
+            let counter = counter.clone();
+            let error = AuthenticationError::new(Bearer::default())
+                .with_observer(move |_scheme, _error, _status| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                });
+            future::err::<(), Error>(error.into())
+        });
+
+        let req = TestRequest::default().to_srv_request();
+        let error = test::block_on(validator.validate(req, ())).err().unwrap();
+        assert_eq!(observed.load(Ordering::SeqCst), 0);
+
+        error.as_response_error().error_response();
+        assert_eq!(observed.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_exhausted() {
+        let fixture = Fixture::new();
+        let validator =
+            flaky(vec![StatusCode::INTERNAL_SERVER_ERROR; 5], &fixture)
+                .max_attempts(4);
+
+        assert_eq!(status(&validator), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(fixture.calls(), 4);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let validator = flaky(Vec::new(), &Fixture::new())
+            .backoff(Duration::from_millis(10), Duration::from_millis(50))
+            .jitter(false);
+        let delays = (1..=5).map(|retry| validator.delay(retry));
+        assert_eq!(
+            delays.collect::<Vec<_>>(),
+            [10, 20, 40, 50, 50].map(Duration::from_millis)
+        );
+
+        let validator = validator.jitter(true);
+        for (retry, max) in (1..=5).zip(&[10, 20, 40, 50, 50]) {
+            let max = Duration::from_millis(*max);
+            let delay = validator.delay(retry);
+            assert!(delay >= max / 2 && delay <= max);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use futures::future::{self, Either, Future, IntoFuture};
+use futures::sync::oneshot;
+
+/// Calls waiting for the in-flight check with the same key.
+type Waiters<V, E> = Vec<oneshot::Sender<Result<V, E>>>;
+
+type InFlight<V, E> = Mutex<HashMap<String, Waiters<V, E>>>;
+
+/// Wrapper for the expensive credentials checks, ex. the remote lookups,
+/// which shares one check between the concurrent calls with the same key,
+/// ex. the token, so the burst of requests with the new token does not
+/// flood the remote endpoint.
+///
+/// Check result, either the value or the error, is cloned for each of
+/// the waiting calls. If the call which started the check is dropped
+/// before it completes, the waiting calls are checking on their own.
+///
+/// Clones are sharing the in-flight checks.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::{error, App};
+/// # use futures::future::{self, Future};
+/// # use actix_web_httpauth::middleware::{HttpAuthentication, SingleFlight};
+/// let lookup = SingleFlight::new(|token: &str| {
+///     // Call the remote endpoint here
+///     match token {
+///         "mF_9.B5f-4.1JqM" => future::ok(()),
+///         _ => future::err("Unknown token"),
+///     }
+/// });
+///
+/// let middleware = HttpAuthentication::bearer(move |req, credentials| {
+///     lookup
+///         .call(credentials.token())
+///         .map(move |()| req)
+///         .map_err(error::ErrorUnauthorized)
+/// });
+/// let app = App::new().wrap(middleware);
+/// ```
+pub struct SingleFlight<F, V, E> {
+    check: Arc<F>,
+    in_flight: Arc<InFlight<V, E>>,
+}
+
+impl<F, O, V, E> SingleFlight<F, V, E>
+where
+    F: Fn(&str) -> O + 'static,
+    O: IntoFuture<Item = V, Error = E>,
+    O::Future: 'static,
+    V: Clone + 'static,
+    E: Clone + 'static,
+{
+    /// Creates new wrapper, which shares the `check` calls.
+    pub fn new(check: F) -> Self {
+        SingleFlight {
+            check: Arc::new(check),
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Calls the check for the `key`, or joins the in-flight one.
+    pub fn call(&self, key: &str) -> Box<dyn Future<Item = V, Error = E>> {
+        let mut in_flight = lock(&self.in_flight);
+        if let Some(waiters) = in_flight.get_mut(key) {
+            let (sender, receiver) = oneshot::channel();
+            waiters.push(sender);
+
+            let this = self.clone();
+            let key = key.to_string();
+            return Box::new(receiver.then(move |result| match result {
+                Ok(result) => Either::A(future::result(result)),
+                // Leading call was dropped before the check completed
+                Err(oneshot::Canceled) => Either::B(this.call(&key)),
+            }));
+        }
+        in_flight.insert(key.to_string(), Vec::new());
+        drop(in_flight);
+
+        let flight = Flight {
+            in_flight: self.in_flight.clone(),
+            key: Some(key.to_string()),
+        };
+        Box::new((self.check)(key).into_future().then(move |result| {
+            flight.land(&result);
+            result
+        }))
+    }
+}
+
+impl<F, V, E> Clone for SingleFlight<F, V, E> {
+    fn clone(&self) -> Self {
+        SingleFlight {
+            check: self.check.clone(),
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+impl<F, V, E> fmt::Debug for SingleFlight<F, V, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SingleFlight")
+            .field("in_flight", &lock(&self.in_flight).len())
+            .finish()
+    }
+}
+
+/// In-flight check, which releases its waiters once it lands
+/// or is dropped.
+struct Flight<V, E> {
+    in_flight: Arc<InFlight<V, E>>,
+    key: Option<String>,
+}
+
+impl<V: Clone, E: Clone> Flight<V, E> {
+    /// Shares the check `result` with the waiters.
+    fn land(mut self, result: &Result<V, E>) {
+        let waiters = self
+            .key
+            .take()
+            .and_then(|key| lock(&self.in_flight).remove(&key));
+        for waiter in waiters.into_iter().flatten() {
+            // Waiter might be gone already
+            let _ = waiter.send(result.clone());
+        }
+    }
+}
+
+impl<V, E> Drop for Flight<V, E> {
+    fn drop(&mut self) {
+        // Dropping the senders cancels the waiters,
+        // so they are checking on their own
+        if let Some(ref key) = self.key {
+            lock(&self.in_flight).remove(key);
+        }
+    }
+}
+
+fn lock<V, E>(
+    in_flight: &InFlight<V, E>,
+) -> MutexGuard<'_, HashMap<String, Waiters<V, E>>> {
+    match in_flight.lock() {
+        Ok(in_flight) => in_flight,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use actix_web::test;
+    use futures::future::{self, Either, Future};
+    use futures::sync::oneshot;
+
+    use super::SingleFlight;
+
+    #[test]
+    fn test_single_flight() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let (sender, receiver) = oneshot::channel::<u32>();
+        let receiver = Mutex::new(Some(receiver));
+        let flight = SingleFlight::new(move |key: &str| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            match receiver.lock().unwrap().take() {
+                Some(receiver) if key == "token" => {
+                    Either::A(receiver.map_err(|_| "cancelled"))
+                }
+                _ => Either::B(future::ok(0)),
+            }
+        });
+
+        let first = flight.call("token");
+        let second = flight.call("token");
+        let other = flight.call("other");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        sender.send(42).unwrap();
+        assert_eq!(test::block_on(first), Ok(42));
+        assert_eq!(test::block_on(second), Ok(42));
+        assert_eq!(test::block_on(other), Ok(0));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // Landed checks are not shared anymore
+        assert_eq!(test::block_on(flight.call("token")), Ok(0));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_shared_error() {
+        let (sender, receiver) = oneshot::channel::<Result<(), &str>>();
+        let receiver = Mutex::new(Some(receiver));
+        let flight = SingleFlight::new(move |_: &str| {
+            let receiver = receiver.lock().unwrap().take().unwrap();
+            receiver.then(|result| result.unwrap())
+        });
+
+        let first = flight.call("token");
+        let second = flight.call("token");
+        sender.send(Err("Endpoint is down")).unwrap();
+        assert_eq!(test::block_on(first), Err("Endpoint is down"));
+        assert_eq!(test::block_on(second), Err("Endpoint is down"));
+    }
+}
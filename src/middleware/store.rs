@@ -0,0 +1,495 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use actix_web::dev::ServiceRequest;
+use actix_web::Error;
+use futures::future::{self, FutureResult, Loop};
+use futures::{Future, IntoFuture};
+
+use super::{HttpAuthentication, Validation};
+use crate::extractors::basic::{self, BasicAuth};
+use crate::extractors::bearer::{self, BearerAuth};
+use crate::utils;
+
+/// Result of the credentials verification by the [`CredentialStore`].
+///
+/// [`CredentialStore`]: ./trait.CredentialStore.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// Credentials are valid.
+    Granted,
+    /// Credentials are known to the store, but are not valid,
+    /// ex. user exists, but the password is wrong.
+    Denied,
+    /// Credentials are not known to the store.
+    NotFound,
+}
+
+impl Outcome {
+    /// Returns `true` if the credentials are valid.
+    pub fn is_granted(self) -> bool {
+        self == Outcome::Granted
+    }
+}
+
+/// Storage of the credentials, which can back both "Basic" and "Bearer"
+/// authentication schemes, see [`HttpAuthentication::basic_store`]
+/// and [`HttpAuthentication::bearer_store`].
+///
+/// Since the verification may require some I/O, ex. database query,
+/// it is returning the future.
+///
+/// [`HttpAuthentication::basic_store`]: ./struct.HttpAuthentication.html#method.basic_store
+/// [`HttpAuthentication::bearer_store`]: ./struct.HttpAuthentication.html#method.bearer_store
+pub trait CredentialStore {
+    /// Future that resolves into the verification outcome.
+    type Future: IntoFuture<Item = Outcome, Error = Error>;
+
+    /// Verify the "Basic" scheme user ID and password.
+    fn verify_basic(
+        &self,
+        user_id: &str,
+        password: Option<&str>,
+    ) -> Self::Future;
+
+    /// Verify the "Bearer" scheme token.
+    fn verify_bearer(&self, token: &str) -> Self::Future;
+}
+
+impl<S: CredentialStore + ?Sized> CredentialStore for Arc<S> {
+    type Future = S::Future;
+
+    fn verify_basic(
+        &self,
+        user_id: &str,
+        password: Option<&str>,
+    ) -> Self::Future {
+        (**self).verify_basic(user_id, password)
+    }
+
+    fn verify_bearer(&self, token: &str) -> Self::Future {
+        (**self).verify_bearer(token)
+    }
+}
+
+/// [`CredentialStore`] which keeps all credentials in memory,
+/// mostly useful for the tests and the small static deployments.
+///
+/// Passwords and tokens are compared in the constant time.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::App;
+/// # use actix_web_httpauth::middleware::{HttpAuthentication, MemoryStore};
+/// let store = MemoryStore::new()
+///     .user("Aladdin", "open sesame")
+///     .token("mF_9.B5f-4.1JqM");
+///
+/// let app = App::new().wrap(HttpAuthentication::bearer_store(store));
+/// ```
+///
+/// [`CredentialStore`]: ./trait.CredentialStore.html
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    users: HashMap<String, Option<String>>,
+    tokens: HashSet<String>,
+}
+
+impl MemoryStore {
+    /// Creates an empty store.
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+
+    /// Add the "Basic" scheme user with the `password`.
+    pub fn user<U, P>(mut self, user_id: U, password: P) -> MemoryStore
+    where
+        U: Into<String>,
+        P: Into<String>,
+    {
+        self.users.insert(user_id.into(), Some(password.into()));
+        self
+    }
+
+    /// Add the "Basic" scheme user, which has no password.
+    pub fn user_without_password<U: Into<String>>(
+        mut self,
+        user_id: U,
+    ) -> MemoryStore {
+        self.users.insert(user_id.into(), None);
+        self
+    }
+
+    /// Add the "Bearer" scheme token.
+    pub fn token<T: Into<String>>(mut self, token: T) -> MemoryStore {
+        self.tokens.insert(token.into());
+        self
+    }
+}
+
+impl CredentialStore for MemoryStore {
+    type Future = FutureResult<Outcome, Error>;
+
+    fn verify_basic(
+        &self,
+        user_id: &str,
+        password: Option<&str>,
+    ) -> Self::Future {
+        let outcome = match self.users.get(user_id) {
+            None => Outcome::NotFound,
+            Some(expected) => {
+                let matches = match (expected, password) {
+                    (Some(expected), Some(password)) => {
+                        utils::constant_time_eq(
+                            expected.as_bytes(),
+                            password.as_bytes(),
+                        )
+                    }
+                    (None, None) => true,
+                    _ => false,
+                };
+
+                if matches {
+                    Outcome::Granted
+                } else {
+                    Outcome::Denied
+                }
+            }
+        };
+
+        future::ok(outcome)
+    }
+
+    fn verify_bearer(&self, token: &str) -> Self::Future {
+        let found = self.tokens.iter().any(|known| {
+            utils::constant_time_eq(known.as_bytes(), token.as_bytes())
+        });
+
+        future::ok(if found {
+            Outcome::Granted
+        } else {
+            Outcome::NotFound
+        })
+    }
+}
+
+type OutcomeFuture = Box<dyn Future<Item = Outcome, Error = Error>>;
+
+/// Object-safe counterpart of the [`CredentialStore`], used by the
+/// [`FallthroughStore`] to keep the stores of different types.
+trait BoxedStore {
+    fn verify_basic(
+        &self,
+        user_id: &str,
+        password: Option<&str>,
+    ) -> OutcomeFuture;
+
+    fn verify_bearer(&self, token: &str) -> OutcomeFuture;
+}
+
+impl<S> BoxedStore for S
+where
+    S: CredentialStore,
+    <S::Future as IntoFuture>::Future: 'static,
+{
+    fn verify_basic(
+        &self,
+        user_id: &str,
+        password: Option<&str>,
+    ) -> OutcomeFuture {
+        Box::new(
+            CredentialStore::verify_basic(self, user_id, password)
+                .into_future(),
+        )
+    }
+
+    fn verify_bearer(&self, token: &str) -> OutcomeFuture {
+        Box::new(CredentialStore::verify_bearer(self, token).into_future())
+    }
+}
+
+/// [`CredentialStore`] which queries the inner stores in order,
+/// ex. during the users migration from one database to another.
+///
+/// Verification succeeds on the first [`Outcome::Granted`] one.
+/// Only [`Outcome::NotFound`] proceeds to the next store:
+/// credentials [`Outcome::Denied`] by some store are denied, even
+/// if they are valid for some of the following stores.
+///
+/// ## Example
+///
+/// ```rust
+/// # use actix_web::App;
+/// # use actix_web_httpauth::middleware::{
+/// #     FallthroughStore, HttpAuthentication, MemoryStore,
+/// # };
+/// let legacy = MemoryStore::new().user("Aladdin", "open sesame");
+/// let current = MemoryStore::new().user("Jafar", "iago");
+/// let store = FallthroughStore::new().store(current).store(legacy);
+///
+/// let app = App::new().wrap(HttpAuthentication::basic_store(store));
+/// ```
+///
+/// [`CredentialStore`]: ./trait.CredentialStore.html
+/// [`Outcome::Granted`]: ./enum.Outcome.html#variant.Granted
+/// [`Outcome::NotFound`]: ./enum.Outcome.html#variant.NotFound
+/// [`Outcome::Denied`]: ./enum.Outcome.html#variant.Denied
+#[derive(Clone, Default)]
+pub struct FallthroughStore {
+    stores: Vec<Arc<dyn BoxedStore>>,
+}
+
+impl FallthroughStore {
+    /// Creates an empty store, which does not know any credentials.
+    pub fn new() -> FallthroughStore {
+        FallthroughStore::default()
+    }
+
+    /// Append the `store` to be queried after the already added ones.
+    pub fn store<S>(mut self, store: S) -> FallthroughStore
+    where
+        S: CredentialStore + 'static,
+        <S::Future as IntoFuture>::Future: 'static,
+    {
+        self.stores.push(Arc::new(store));
+        self
+    }
+
+    /// Queries the stores with `verify` until one of them knows
+    /// the credentials.
+    fn verify<F>(&self, verify: F) -> OutcomeFuture
+    where
+        F: Fn(&dyn BoxedStore) -> OutcomeFuture + 'static,
+    {
+        let stores = self.stores.clone();
+
+        Box::new(future::loop_fn(0, move |index| match stores.get(index) {
+            Some(store) => {
+                Box::new(verify(&**store).map(move |outcome| match outcome {
+                    Outcome::NotFound => Loop::Continue(index + 1),
+                    outcome => Loop::Break(outcome),
+                })) as Box<dyn Future<Item = _, Error = _>>
+            }
+            None => Box::new(future::ok(Loop::Break(Outcome::NotFound))),
+        }))
+    }
+}
+
+impl fmt::Debug for FallthroughStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FallthroughStore")
+            .field("stores", &self.stores.len())
+            .finish()
+    }
+}
+
+impl CredentialStore for FallthroughStore {
+    type Future = OutcomeFuture;
+
+    fn verify_basic(
+        &self,
+        user_id: &str,
+        password: Option<&str>,
+    ) -> Self::Future {
+        let user_id = user_id.to_string();
+        let password = password.map(String::from);
+
+        self.verify(move |store| {
+            store.verify_basic(&user_id, password.as_deref())
+        })
+    }
+
+    fn verify_bearer(&self, token: &str) -> Self::Future {
+        let token = token.to_string();
+
+        self.verify(move |store| store.verify_bearer(&token))
+    }
+}
+
+impl HttpAuthentication<BasicAuth, ()> {
+    /// Construct `HttpAuthentication` middleware for the HTTP "Basic"
+    /// authentication scheme, which verifies the credentials
+    /// with the `store`.
+    ///
+    /// Any outcome except the [`Outcome::Granted`] results in `HTTP 401`
+    /// challenge, formed from the [`Config`] instance fetched from the app
+    /// data.
+    ///
+    /// [`Outcome::Granted`]: ./enum.Outcome.html#variant.Granted
+    /// [`Config`]: ../extractors/basic/struct.Config.html
+    pub fn basic_store<S>(
+        store: S,
+    ) -> HttpAuthentication<
+        BasicAuth,
+        impl Fn(ServiceRequest, BasicAuth) -> Validation,
+    >
+    where
+        S: CredentialStore + 'static,
+        <S::Future as IntoFuture>::Future: 'static,
+    {
+        HttpAuthentication::basic(move |req, credentials: BasicAuth| {
+            let password = credentials.password().map(|password| &**password);
+            let outcome = store.verify_basic(credentials.user_id(), password);
+
+            Box::new(outcome.into_future().and_then(move |outcome| {
+                if outcome.is_granted() {
+                    return Ok(req);
+                }
+
+                let config = req
+                    .app_data::<basic::Config>()
+                    .map(|config| config.get_ref().clone())
+                    .unwrap_or_default();
+
+                Err(config.challenge_error(req.head()).into())
+            })) as Validation
+        })
+    }
+}
+
+impl HttpAuthentication<BearerAuth, ()> {
+    /// Construct `HttpAuthentication` middleware for the HTTP "Bearer"
+    /// authentication scheme, which verifies the token with the `store`.
+    ///
+    /// Any outcome except the [`Outcome::Granted`] results in `HTTP 401`
+    /// `invalid_token` challenge, formed from the [`Config`] instance
+    /// fetched from the app data.
+    ///
+    /// [`Outcome::Granted`]: ./enum.Outcome.html#variant.Granted
+    /// [`Config`]: ../extractors/bearer/struct.Config.html
+    pub fn bearer_store<S>(
+        store: S,
+    ) -> HttpAuthentication<
+        BearerAuth,
+        impl Fn(ServiceRequest, BearerAuth) -> Validation,
+    >
+    where
+        S: CredentialStore + 'static,
+        <S::Future as IntoFuture>::Future: 'static,
+    {
+        HttpAuthentication::bearer(move |req, credentials: BearerAuth| {
+            let outcome = store.verify_bearer(credentials.token());
+
+            Box::new(outcome.into_future().and_then(move |outcome| {
+                if outcome.is_granted() {
+                    return Ok(req);
+                }
+
+                let config = req
+                    .app_data::<bearer::Config>()
+                    .map(|config| config.get_ref().clone())
+                    .unwrap_or_default();
+
+                Err(config
+                    .challenge_error(req.head())
+                    .with_error(bearer::Error::InvalidToken)
+                    .into())
+            })) as Validation
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::AUTHORIZATION;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+    use futures::Future;
+
+    use super::{CredentialStore, FallthroughStore, MemoryStore, Outcome};
+    use crate::middleware::tests::status;
+    use crate::middleware::HttpAuthentication;
+
+    fn store() -> MemoryStore {
+        MemoryStore::new()
+            .user("Aladdin", "open sesame")
+            .user_without_password("guest")
+            .token("mF_9.B5f-4.1JqM")
+    }
+
+    #[test]
+    fn test_memory_store() {
+        let store = store();
+        let basic = |user_id, password| {
+            store.verify_basic(user_id, password).wait().unwrap()
+        };
+
+        assert_eq!(basic("Aladdin", Some("open sesame")), Outcome::Granted);
+        assert_eq!(basic("Aladdin", Some("open")), Outcome::Denied);
+        assert_eq!(basic("Aladdin", None), Outcome::Denied);
+        assert_eq!(basic("guest", None), Outcome::Granted);
+        assert_eq!(basic("guest", Some("")), Outcome::Denied);
+        assert_eq!(basic("Jafar", Some("open sesame")), Outcome::NotFound);
+
+        let bearer = |token| store.verify_bearer(token).wait().unwrap();
+        assert_eq!(bearer("mF_9.B5f-4.1JqM"), Outcome::Granted);
+        assert_eq!(bearer("mF_9.B5f-4.1Jq"), Outcome::NotFound);
+    }
+
+    #[test]
+    fn test_fallthrough_store() {
+        let store = FallthroughStore::new()
+            .store(MemoryStore::new().user("Aladdin", "open sesame"))
+            .store(
+                MemoryStore::new()
+                    .user("Aladdin", "new sesame")
+                    .user("Jafar", "iago")
+                    .token("mF_9.B5f-4.1JqM"),
+            );
+        let basic = |user_id, password| {
+            store.verify_basic(user_id, Some(password)).wait().unwrap()
+        };
+
+        assert_eq!(basic("Aladdin", "open sesame"), Outcome::Granted);
+        // Not found in the first store, found in the second one
+        assert_eq!(basic("Jafar", "iago"), Outcome::Granted);
+        assert_eq!(basic("Jafar", "genie"), Outcome::Denied);
+        // Denied by the first store, valid for the second one
+        assert_eq!(basic("Aladdin", "new sesame"), Outcome::Denied);
+        assert_eq!(basic("Abu", "monkey"), Outcome::NotFound);
+
+        let bearer = |token| store.verify_bearer(token).wait().unwrap();
+        assert_eq!(bearer("mF_9.B5f-4.1JqM"), Outcome::Granted);
+        assert_eq!(bearer("unknown"), Outcome::NotFound);
+
+        let empty = FallthroughStore::new();
+        assert_eq!(empty.verify_bearer("x").wait().unwrap(), Outcome::NotFound);
+    }
+
+    #[test]
+    fn test_store_middlewares() {
+        let mut app = test::init_service(
+            App::new()
+                .service(
+                    web::resource("/basic")
+                        .wrap(HttpAuthentication::basic_store(store()))
+                        .to(HttpResponse::Ok),
+                )
+                .service(
+                    web::resource("/bearer")
+                        .wrap(HttpAuthentication::bearer_store(store()))
+                        .to(HttpResponse::Ok),
+                ),
+        );
+        let mut call = |path: &str, authorization: &str| {
+            let req = TestRequest::with_header(AUTHORIZATION, authorization)
+                .uri(path)
+                .to_request();
+
+            status(&mut app, req)
+        };
+
+        // Aladdin:open sesame
+        let aladdin = "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==";
+        // Aladdin:sesame
+        let wrong = "Basic QWxhZGRpbjpzZXNhbWU=";
+
+        assert_eq!(call("/basic", aladdin), StatusCode::OK);
+        assert_eq!(call("/basic", wrong), StatusCode::UNAUTHORIZED);
+        assert_eq!(call("/bearer", "Bearer mF_9.B5f-4.1JqM"), StatusCode::OK);
+        assert_eq!(call("/bearer", "Bearer unknown"), StatusCode::UNAUTHORIZED);
+    }
+}
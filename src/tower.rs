@@ -0,0 +1,330 @@
+//! [Tower](https://github.com/tower-rs/tower) adaptation of the
+//! authentication, for the services built around the `http::Request`
+//! and `http::Response` types instead of the actix-web ones.
+//!
+//! Credentials are parsed from the `Authorization` header with the same
+//! [`Scheme`] implementations the extractors are using, and rejections are
+//! rendered from the [`AuthenticationError`], so the challenges, the
+//! `Cache-Control` header and the error observers are behaving the same.
+//!
+//! Available with the `tower` feature only.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # use std::task::{Context, Poll};
+//! # use std::future::{self, Ready};
+//! # use http::{Request, Response};
+//! use actix_web_httpauth::extractors::AuthenticationError;
+//! use actix_web_httpauth::headers::authorization::Bearer;
+//! use actix_web_httpauth::headers::www_authenticate::bearer;
+//! use actix_web_httpauth::tower::AuthLayer;
+//! use tower_layer::Layer;
+//! # struct Handler;
+//! # impl tower_service::Service<Request<()>> for Handler {
+//! #     type Response = Response<String>;
+//! #     type Error = ();
+//! #     type Future = Ready<Result<Response<String>, ()>>;
+//! #     fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+//! #         Poll::Ready(Ok(()))
+//! #     }
+//! #     fn call(&mut self, _req: Request<()>) -> Self::Future {
+//! #         future::ready(Ok(Response::new(String::new())))
+//! #     }
+//! # }
+//!
+//! let challenge = bearer::Bearer::build().realm("api").finish();
+//! let layer = AuthLayer::new(challenge.clone(), move |credentials: &Bearer| {
+//!     if credentials.token() == "mF_9.B5f-4.1JqM" {
+//!         Ok(())
+//!     } else {
+//!         let error = AuthenticationError::new(challenge.clone())
+//!             .with_error(bearer::Error::InvalidToken);
+//!         Err(error.into())
+//!     }
+//! });
+//! let service = layer.layer(Handler);
+//! ```
+//!
+//! [`Scheme`]: ../headers/authorization/trait.Scheme.html
+//! [`AuthenticationError`]: ../extractors/struct.AuthenticationError.html
+
+use std::fmt;
+use std::future::{self, Future};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::Error;
+use http::{HeaderMap, Request, Response};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::extractors::AuthenticationError;
+use crate::headers::authorization::{ParseError, Scheme};
+use crate::headers::www_authenticate::Challenge;
+
+/// Future returned by the [`AuthService`].
+///
+/// [`AuthService`]: ./struct.AuthService.html
+pub type ResponseFuture<B, E> =
+    Pin<Box<dyn Future<Output = Result<Response<B>, E>> + Send>>;
+
+/// Layer wrapping the services into the [`AuthService`].
+///
+/// [`AuthService`]: ./struct.AuthService.html
+pub struct AuthLayer<S, C: Challenge, F> {
+    challenge: C,
+    validator: Arc<F>,
+    scheme: PhantomData<fn() -> S>,
+}
+
+impl<S, C, F> AuthLayer<S, C, F>
+where
+    S: Scheme,
+    C: Challenge,
+    F: Fn(&S) -> Result<(), Error>,
+{
+    /// Creates new layer, which responds with the `challenge` to the
+    /// requests without the valid `S` credentials, and passes the parsed
+    /// ones to the `validator`, rejecting the request with its error.
+    ///
+    /// Validated credentials are stored in the request extensions
+    /// for the inner service.
+    pub fn new(challenge: C, validator: F) -> Self {
+        AuthLayer {
+            challenge,
+            validator: Arc::new(validator),
+            scheme: PhantomData,
+        }
+    }
+}
+
+impl<Svc, S, C: Challenge, F> Layer<Svc> for AuthLayer<S, C, F> {
+    type Service = AuthService<Svc, S, C, F>;
+
+    fn layer(&self, inner: Svc) -> Self::Service {
+        AuthService {
+            inner,
+            challenge: self.challenge.clone(),
+            validator: self.validator.clone(),
+            scheme: PhantomData,
+        }
+    }
+}
+
+impl<S, C: Challenge, F> Clone for AuthLayer<S, C, F> {
+    fn clone(&self) -> Self {
+        AuthLayer {
+            challenge: self.challenge.clone(),
+            validator: self.validator.clone(),
+            scheme: PhantomData,
+        }
+    }
+}
+
+impl<S, C: Challenge, F> fmt::Debug for AuthLayer<S, C, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AuthLayer")
+            .field("challenge", &self.challenge)
+            .finish()
+    }
+}
+
+/// Tower service authenticating the requests before passing them
+/// to the inner one, see the [`AuthLayer`].
+///
+/// Rejected requests are answered with the rendered authentication error
+/// status and headers, while the response body is `ResBody::default()`.
+///
+/// [`AuthLayer`]: ./struct.AuthLayer.html
+pub struct AuthService<Svc, S, C: Challenge, F> {
+    inner: Svc,
+    challenge: C,
+    validator: Arc<F>,
+    scheme: PhantomData<fn() -> S>,
+}
+
+impl<Svc, S, C, F> AuthService<Svc, S, C, F>
+where
+    S: Scheme,
+    C: Challenge + 'static,
+    F: Fn(&S) -> Result<(), Error>,
+{
+    fn authenticate(&self, headers: &HeaderMap) -> Result<S, Error> {
+        let credentials = headers
+            .get(AUTHORIZATION)
+            .ok_or(ParseError::MissingHeader)
+            .and_then(S::parse)
+            .map_err(|err| {
+                AuthenticationError::new(self.challenge.clone())
+                    .with_parse_error(err)
+            })?;
+        (self.validator)(&credentials)?;
+
+        Ok(credentials)
+    }
+}
+
+impl<Svc, S, C, F, ReqBody, ResBody> Service<Request<ReqBody>>
+    for AuthService<Svc, S, C, F>
+where
+    Svc: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    Svc::Future: Send + 'static,
+    Svc::Error: Send + 'static,
+    ResBody: Default + Send + 'static,
+    S: Scheme + 'static,
+    C: Challenge + 'static,
+    F: Fn(&S) -> Result<(), Error>,
+{
+    type Response = Response<ResBody>;
+    type Error = Svc::Error;
+    type Future = ResponseFuture<ResBody, Svc::Error>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        match self.authenticate(req.headers()) {
+            Ok(credentials) => {
+                req.extensions_mut().insert(credentials);
+                Box::pin(self.inner.call(req))
+            }
+            Err(error) => Box::pin(future::ready(Ok(reject(&error)))),
+        }
+    }
+}
+
+impl<Svc: Clone, S, C: Challenge, F> Clone for AuthService<Svc, S, C, F> {
+    fn clone(&self) -> Self {
+        AuthService {
+            inner: self.inner.clone(),
+            challenge: self.challenge.clone(),
+            validator: self.validator.clone(),
+            scheme: PhantomData,
+        }
+    }
+}
+
+impl<Svc: fmt::Debug, S, C: Challenge, F> fmt::Debug
+    for AuthService<Svc, S, C, F>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AuthService")
+            .field("inner", &self.inner)
+            .field("challenge", &self.challenge)
+            .finish()
+    }
+}
+
+/// Converts the rendered `error` into the response without the body.
+fn reject<B: Default>(error: &Error) -> Response<B> {
+    let rendered = error.as_response_error().error_response();
+    let mut response = Response::new(B::default());
+    *response.status_mut() = rendered.status();
+    for (name, value) in rendered.headers().iter() {
+        response.headers_mut().append(name.clone(), value.clone());
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::{self, Future, Ready};
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    use actix_web::http::header::{
+        AUTHORIZATION, CACHE_CONTROL, WWW_AUTHENTICATE,
+    };
+    use actix_web::http::StatusCode;
+    use http::{Request, Response};
+    use tower_layer::Layer;
+    use tower_service::Service;
+
+    use super::AuthLayer;
+    use crate::extractors::AuthenticationError;
+    use crate::headers::authorization::Bearer;
+    use crate::headers::www_authenticate::bearer;
+
+    /// Responds with the token of the validated credentials.
+    struct Handler;
+
+    impl Service<Request<()>> for Handler {
+        type Response = Response<String>;
+        type Error = ();
+        type Future = Ready<Result<Response<String>, ()>>;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: Request<()>) -> Self::Future {
+            let token = req.extensions().get::<Bearer>().unwrap().token();
+            future::ready(Ok(Response::new(token.to_string())))
+        }
+    }
+
+    fn call(authorization: Option<&str>) -> Response<String> {
+        let challenge = bearer::Bearer::build().realm("api").finish();
+        let layer =
+            AuthLayer::new(challenge.clone(), move |bearer: &Bearer| {
+                if bearer.token() == "valid" {
+                    Ok(())
+                } else {
+                    let error = AuthenticationError::new(challenge.clone())
+                        .with_error(bearer::Error::InvalidToken);
+                    Err(error.into())
+                }
+            });
+        let mut service = layer.layer(Handler);
+
+        let mut req = Request::new(());
+        if let Some(authorization) = authorization {
+            req.headers_mut()
+                .insert(AUTHORIZATION, authorization.parse().unwrap());
+        }
+        let mut cx = Context::from_waker(Waker::noop());
+        let response = pin!(service.call(req)).poll(&mut cx);
+        match response {
+            Poll::Ready(Ok(response)) => response,
+            _ => panic!("Response is not ready"),
+        }
+    }
+
+    #[test]
+    fn test_authenticated() {
+        let response = call(Some("Bearer valid"));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), "valid");
+    }
+
+    #[test]
+    fn test_rejected() {
+        let response = call(None);
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer realm=\"api\""
+        );
+        assert_eq!(response.headers().get(CACHE_CONTROL).unwrap(), "no-store");
+        assert!(response.body().is_empty());
+
+        let response = call(Some("Bearer invalid"));
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer realm=\"api\", error=\"invalid_token\""
+        );
+    }
+}
@@ -1,5 +1,10 @@
+use std::fmt;
+use std::fmt::Write;
+use std::ops::Deref;
 use std::str;
+use std::sync::Arc;
 
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
 use bytes::BytesMut;
 
 enum State {
@@ -13,7 +18,7 @@ struct Quoted<'a> {
 }
 
 impl<'a> Quoted<'a> {
-    pub fn new(s: &'a str) -> Quoted {
+    pub fn new(s: &'a str) -> Quoted<'a> {
         Quoted {
             inner: s.split('"').peekable(),
             state: State::YieldStr,
@@ -51,13 +56,136 @@ pub fn put_quoted(buf: &mut BytesMut, value: &str) {
     }
 }
 
+/// Compares the `left` and `right` bytes in the constant time,
+/// so the comparison duration does not leak the matching prefix length.
+pub fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0, |diff, (left, right)| diff | (left ^ right))
+            == 0
+}
+
+/// Appends `values` to the `name` header values, preserving their order.
+///
+/// `HeaderMap::append` puts the second header value in front of the first
+/// one, so all values are re-inserted in the expected order.
+pub fn append_header_values<I>(
+    headers: &mut HeaderMap,
+    name: HeaderName,
+    values: I,
+) where
+    I: IntoIterator<Item = HeaderValue>,
+{
+    let mut values = headers
+        .get_all(&name)
+        .cloned()
+        .chain(values)
+        .collect::<Vec<_>>()
+        .into_iter();
+    headers.remove(&name);
+
+    match (values.next(), values.next()) {
+        (Some(first), Some(second)) => {
+            headers.append(name.clone(), second);
+            headers.append(name.clone(), first);
+        }
+        (Some(first), None) => headers.insert(name.clone(), first),
+        _ => return,
+    }
+    for value in values {
+        headers.append(name.clone(), value);
+    }
+}
+
+/// Shared user-provided callback, stored in the configs.
+///
+/// Exists mostly to provide the `Debug` implementation,
+/// which closures are lacking.
+pub struct Callback<F: ?Sized>(Arc<F>);
+
+impl<F: ?Sized> Callback<F> {
+    pub fn new(f: Arc<F>) -> Callback<F> {
+        Callback(f)
+    }
+}
+
+impl<F: ?Sized> Clone for Callback<F> {
+    fn clone(&self) -> Self {
+        Callback(self.0.clone())
+    }
+}
+
+impl<F: ?Sized> Deref for Callback<F> {
+    type Target = F;
+
+    fn deref(&self) -> &F {
+        &self.0
+    }
+}
+
+impl<F: ?Sized> fmt::Debug for Callback<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+/// Returns the new unpredictable token, ex. nonce or session ID:
+/// 128 bits read from the OS random number generator, as 32 hex digits.
+///
+/// ## Panics
+///
+/// If the OS random number generator is unavailable, since no secure
+/// token can be issued without it.
+pub fn random_token() -> String {
+    let mut bytes = [0; 16];
+    getrandom::getrandom(&mut bytes)
+        .expect("OS random number generator is unavailable");
+
+    bytes
+        .iter()
+        .fold(String::with_capacity(32), |mut token, byte| {
+            let _ = write!(token, "{:02x}", byte);
+            token
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use std::str;
 
+    use actix_web::http::header::{HeaderMap, HeaderValue, LINK};
     use bytes::BytesMut;
 
-    use super::put_quoted;
+    use super::{append_header_values, put_quoted, random_token};
+
+    #[test]
+    fn test_append_header_values() {
+        let mut headers = HeaderMap::new();
+        headers.insert(LINK, HeaderValue::from_static("1"));
+        append_header_values(
+            &mut headers,
+            LINK,
+            vec![HeaderValue::from_static("2"), HeaderValue::from_static("3")],
+        );
+        append_header_values(
+            &mut headers,
+            LINK,
+            vec![HeaderValue::from_static("4")],
+        );
+
+        let values = headers.get_all(LINK).collect::<Vec<_>>();
+        assert_eq!(values, vec!["1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn test_random_token() {
+        let token = random_token();
+        assert_eq!(token.len(), 32);
+        assert!(token.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        assert_ne!(token, random_token());
+    }
 
     #[test]
     fn test_quote_str() {